@@ -0,0 +1,545 @@
+//! Algorithm
+//!
+//! Whole-graph analytics over the vertexes & edges of a category, loaded in memory
+//! (see `GraphService::connected_components` & co.). Edges are directed: `source -> target`,
+//! and edges whose endpoints are not among the given vertexes are ignored.
+//!
+//! Results implementing `VertexValues` can be written back as a vertex property,
+//! see `GraphService::write_vertex_values`.
+
+use std::cmp::Reverse;
+use std::collections::{BinaryHeap, HashMap, VecDeque};
+
+use mongodb::bson::oid::ObjectId;
+use mongodb::bson::Bson;
+
+use super::model::Edge;
+use super::{Pyo3MongoError, Pyo3MongoResult};
+
+/// a value per vertex, to be stored as a vertex property
+pub trait VertexValues {
+    fn vertex_values(&self) -> Vec<(ObjectId, Bson)>;
+}
+
+// vertexes by index, ids are sorted so that every result is deterministic
+struct Topology<'a> {
+    ids: Vec<ObjectId>,
+    index: HashMap<ObjectId, usize>,
+    // vertex -> (neighbour, edge)
+    outgoing: Vec<Vec<(usize, &'a Edge)>>,
+    incoming: Vec<Vec<(usize, &'a Edge)>>,
+}
+
+impl<'a> Topology<'a> {
+    fn new(vertexes: &[ObjectId], edges: &'a [Edge]) -> Self {
+        let mut ids = vertexes.to_vec();
+        ids.sort();
+        ids.dedup();
+        let index = ids
+            .iter()
+            .enumerate()
+            .map(|(i, id)| (*id, i))
+            .collect::<HashMap<_, _>>();
+
+        let mut outgoing = vec![vec![]; ids.len()];
+        let mut incoming = vec![vec![]; ids.len()];
+        for e in edges {
+            if let (Some(&s), Some(&t)) = (index.get(&e.source), index.get(&e.target)) {
+                outgoing[s].push((t, e));
+                incoming[t].push((s, e));
+            }
+        }
+
+        Topology {
+            ids,
+            index,
+            outgoing,
+            incoming,
+        }
+    }
+
+    fn len(&self) -> usize {
+        self.ids.len()
+    }
+}
+
+/// groups of vertexes, the largest first, ids are sorted within a group
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Components {
+    pub components: Vec<Vec<ObjectId>>,
+}
+
+impl Components {
+    fn new(mut components: Vec<Vec<ObjectId>>) -> Self {
+        components.iter_mut().for_each(|c| c.sort());
+        components.sort_by(|a, b| b.len().cmp(&a.len()).then_with(|| a[0].cmp(&b[0])));
+        Components { components }
+    }
+
+    pub fn len(&self) -> usize {
+        self.components.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.components.is_empty()
+    }
+
+    /// vertex -> index of its component
+    pub fn membership(&self) -> HashMap<ObjectId, usize> {
+        self.components
+            .iter()
+            .enumerate()
+            .flat_map(|(i, c)| c.iter().map(move |id| (*id, i)))
+            .collect()
+    }
+}
+
+/// the index of the component
+impl VertexValues for Components {
+    fn vertex_values(&self) -> Vec<(ObjectId, Bson)> {
+        self.components
+            .iter()
+            .enumerate()
+            .flat_map(|(i, c)| c.iter().map(move |id| (*id, Bson::Int64(i as i64))))
+            .collect()
+    }
+}
+
+/// vertexes in an order where every edge goes forward
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct TopologicalOrder {
+    pub order: Vec<ObjectId>,
+}
+
+/// the position in the order
+impl VertexValues for TopologicalOrder {
+    fn vertex_values(&self) -> Vec<(ObjectId, Bson)> {
+        self.order
+            .iter()
+            .enumerate()
+            .map(|(i, id)| (*id, Bson::Int64(i as i64)))
+            .collect()
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PageRankOptions {
+    /// probability to follow an edge rather than to jump to a random vertex
+    pub damping: f64,
+    pub max_iterations: usize,
+    /// stop once the sum of score changes of an iteration is below it
+    pub tolerance: f64,
+}
+
+impl Default for PageRankOptions {
+    fn default() -> Self {
+        PageRankOptions {
+            damping: 0.85,
+            max_iterations: 100,
+            tolerance: 1e-6,
+        }
+    }
+}
+
+/// scores sum up to 1
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct PageRank {
+    pub scores: HashMap<ObjectId, f64>,
+    pub iterations: usize,
+    pub converged: bool,
+}
+
+/// the score
+impl VertexValues for PageRank {
+    fn vertex_values(&self) -> Vec<(ObjectId, Bson)> {
+        self.scores
+            .iter()
+            .map(|(id, s)| (*id, Bson::Double(*s)))
+            .collect()
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct Degree {
+    pub in_degree: usize,
+    pub out_degree: usize,
+    /// `(in + out) / (n - 1)`, 0 for a single vertex
+    pub centrality: f64,
+}
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct DegreeCentrality {
+    pub degrees: HashMap<ObjectId, Degree>,
+}
+
+/// the centrality
+impl VertexValues for DegreeCentrality {
+    fn vertex_values(&self) -> Vec<(ObjectId, Bson)> {
+        self.degrees
+            .iter()
+            .map(|(id, d)| (*id, Bson::Double(d.centrality)))
+            .collect()
+    }
+}
+
+/// weakly connected components, i.e. edge directions are ignored
+pub fn connected_components(vertexes: &[ObjectId], edges: &[Edge]) -> Components {
+    let topo = Topology::new(vertexes, edges);
+    let mut visited = vec![false; topo.len()];
+    let mut components = vec![];
+
+    for root in 0..topo.len() {
+        if visited[root] {
+            continue;
+        }
+        visited[root] = true;
+        let mut component = vec![];
+        let mut queue = VecDeque::from([root]);
+        while let Some(v) = queue.pop_front() {
+            component.push(topo.ids[v]);
+            let neighbours = topo.outgoing[v].iter().chain(topo.incoming[v].iter());
+            for &(w, _) in neighbours {
+                if !visited[w] {
+                    visited[w] = true;
+                    queue.push_back(w);
+                }
+            }
+        }
+        components.push(component);
+    }
+
+    Components::new(components)
+}
+
+/// Tarjan's algorithm, iterative so that a long path cannot overflow the stack
+pub fn strongly_connected_components(vertexes: &[ObjectId], edges: &[Edge]) -> Components {
+    let topo = Topology::new(vertexes, edges);
+    let n = topo.len();
+    let mut index = vec![usize::MAX; n];
+    let mut low = vec![0; n];
+    let mut on_stack = vec![false; n];
+    let mut stack = vec![];
+    let mut next = 0;
+    let mut components = vec![];
+
+    for root in 0..n {
+        if index[root] != usize::MAX {
+            continue;
+        }
+        // (vertex, position in its outgoing edges)
+        let mut calls = vec![(root, 0)];
+        index[root] = next;
+        low[root] = next;
+        next += 1;
+        stack.push(root);
+        on_stack[root] = true;
+
+        while let Some(top) = calls.last_mut() {
+            let v = top.0;
+            if let Some(&(w, _)) = topo.outgoing[v].get(top.1) {
+                top.1 += 1;
+                if index[w] == usize::MAX {
+                    index[w] = next;
+                    low[w] = next;
+                    next += 1;
+                    stack.push(w);
+                    on_stack[w] = true;
+                    calls.push((w, 0));
+                } else if on_stack[w] {
+                    low[v] = low[v].min(index[w]);
+                }
+                continue;
+            }
+
+            calls.pop();
+            if let Some(&(parent, _)) = calls.last() {
+                low[parent] = low[parent].min(low[v]);
+            }
+            if low[v] == index[v] {
+                let mut component = vec![];
+                while let Some(w) = stack.pop() {
+                    on_stack[w] = false;
+                    component.push(topo.ids[w]);
+                    if w == v {
+                        break;
+                    }
+                }
+                components.push(component);
+            }
+        }
+    }
+
+    Components::new(components)
+}
+
+/// edges of a directed cycle in walking order, `None` if the graph is acyclic.
+/// A self-loop is a cycle of one edge.
+pub fn find_cycle(vertexes: &[ObjectId], edges: &[Edge]) -> Option<Vec<Edge>> {
+    const WHITE: u8 = 0;
+    const GREY: u8 = 1;
+    const BLACK: u8 = 2;
+
+    let topo = Topology::new(vertexes, edges);
+    let mut color = vec![WHITE; topo.len()];
+    // the edge walked to reach a vertex
+    let mut via: Vec<Option<&Edge>> = vec![None; topo.len()];
+
+    for root in 0..topo.len() {
+        if color[root] != WHITE {
+            continue;
+        }
+        color[root] = GREY;
+        let mut calls = vec![(root, 0)];
+
+        while let Some(top) = calls.last_mut() {
+            let v = top.0;
+            let Some(&(w, e)) = topo.outgoing[v].get(top.1) else {
+                color[v] = BLACK;
+                calls.pop();
+                continue;
+            };
+            top.1 += 1;
+            match color[w] {
+                WHITE => {
+                    color[w] = GREY;
+                    via[w] = Some(e);
+                    calls.push((w, 0));
+                }
+                GREY => {
+                    // `e` goes back to `w`, which is on the current path
+                    let mut cycle = vec![e.clone()];
+                    let mut cur = v;
+                    while cur != w {
+                        let back = via[cur]?;
+                        cycle.push(back.clone());
+                        cur = topo.index[&back.source];
+                    }
+                    cycle.reverse();
+                    return Some(cycle);
+                }
+                _ => {}
+            }
+        }
+    }
+
+    None
+}
+
+/// Kahn's algorithm, ties are broken by the smallest id.
+/// Fails if the graph has a cycle, see `find_cycle`.
+pub fn topological_sort(
+    vertexes: &[ObjectId],
+    edges: &[Edge],
+) -> Pyo3MongoResult<TopologicalOrder> {
+    let topo = Topology::new(vertexes, edges);
+    let mut in_degree = topo.incoming.iter().map(|i| i.len()).collect::<Vec<_>>();
+    let mut ready = in_degree
+        .iter()
+        .enumerate()
+        .filter(|(_, d)| **d == 0)
+        .map(|(v, _)| Reverse(v))
+        .collect::<BinaryHeap<_>>();
+
+    let mut order = Vec::with_capacity(topo.len());
+    while let Some(Reverse(v)) = ready.pop() {
+        order.push(topo.ids[v]);
+        for &(w, _) in topo.outgoing[v].iter() {
+            in_degree[w] -= 1;
+            if in_degree[w] == 0 {
+                ready.push(Reverse(w));
+            }
+        }
+    }
+
+    if order.len() < topo.len() {
+        return Err(Pyo3MongoError::Common("graph has a cycle"));
+    }
+
+    Ok(TopologicalOrder { order })
+}
+
+/// power iteration, parallel edges count as many links.
+/// The score of a vertex without outgoing edge is spread over all vertexes.
+pub fn pagerank(
+    vertexes: &[ObjectId],
+    edges: &[Edge],
+    options: PageRankOptions,
+) -> Pyo3MongoResult<PageRank> {
+    if !(0.0..=1.0).contains(&options.damping) {
+        return Err(Pyo3MongoError::Common("damping should be within [0, 1]"));
+    }
+
+    let topo = Topology::new(vertexes, edges);
+    let n = topo.len();
+    if n == 0 {
+        return Ok(PageRank {
+            converged: true,
+            ..Default::default()
+        });
+    }
+
+    let d = options.damping;
+    let mut scores = vec![1.0 / n as f64; n];
+    let mut iterations = 0;
+    let mut converged = false;
+
+    while iterations < options.max_iterations {
+        iterations += 1;
+        let dangling = (0..n)
+            .filter(|v| topo.outgoing[*v].is_empty())
+            .map(|v| scores[v])
+            .sum::<f64>();
+        let base = (1.0 - d) / n as f64 + d * dangling / n as f64;
+
+        let next = (0..n)
+            .map(|v| {
+                let linked = topo.incoming[v]
+                    .iter()
+                    .map(|&(u, _)| scores[u] / topo.outgoing[u].len() as f64)
+                    .sum::<f64>();
+                base + d * linked
+            })
+            .collect::<Vec<_>>();
+
+        let delta = next
+            .iter()
+            .zip(scores.iter())
+            .map(|(a, b)| (a - b).abs())
+            .sum::<f64>();
+        scores = next;
+        if delta < options.tolerance {
+            converged = true;
+            break;
+        }
+    }
+
+    Ok(PageRank {
+        scores: topo.ids.iter().copied().zip(scores).collect(),
+        iterations,
+        converged,
+    })
+}
+
+/// a self-loop counts as both in & out edge
+pub fn degree_centrality(vertexes: &[ObjectId], edges: &[Edge]) -> DegreeCentrality {
+    let topo = Topology::new(vertexes, edges);
+    let scale = if topo.len() > 1 {
+        1.0 / (topo.len() - 1) as f64
+    } else {
+        0.0
+    };
+
+    let degrees = (0..topo.len())
+        .map(|v| {
+            let (in_degree, out_degree) = (topo.incoming[v].len(), topo.outgoing[v].len());
+            let degree = Degree {
+                in_degree,
+                out_degree,
+                centrality: (in_degree + out_degree) as f64 * scale,
+            };
+            (topo.ids[v], degree)
+        })
+        .collect();
+
+    DegreeCentrality { degrees }
+}
+
+#[cfg(test)]
+mod test_algorithm {
+    use super::*;
+
+    fn edge(source: ObjectId, target: ObjectId) -> Edge {
+        Edge {
+            id: Some(ObjectId::new()),
+            source,
+            target,
+            weight: None,
+            label: None,
+            properties: Default::default(),
+        }
+    }
+
+    fn ids(n: usize) -> Vec<ObjectId> {
+        let mut ids = (0..n).map(|_| ObjectId::new()).collect::<Vec<_>>();
+        ids.sort();
+        ids
+    }
+
+    #[test]
+    fn test_components() {
+        // 0 <-> 1 -> 2, 3 -> 4, 5 alone
+        let v = ids(6);
+        let edges = vec![
+            edge(v[0], v[1]),
+            edge(v[1], v[0]),
+            edge(v[1], v[2]),
+            edge(v[3], v[4]),
+            // dangling edge, ignored
+            edge(v[4], ObjectId::new()),
+        ];
+
+        let weak = connected_components(&v, &edges);
+        assert_eq!(
+            weak.components,
+            vec![vec![v[0], v[1], v[2]], vec![v[3], v[4]], vec![v[5]]]
+        );
+        assert_eq!(weak.membership()[&v[4]], 1);
+
+        let strong = strongly_connected_components(&v, &edges);
+        assert_eq!(strong.len(), 5);
+        assert_eq!(strong.components[0], vec![v[0], v[1]]);
+        assert_eq!(strong.vertex_values().len(), 6);
+    }
+
+    #[test]
+    fn test_cycle_and_topological_sort() {
+        let v = ids(4);
+        let mut edges = vec![edge(v[2], v[3]), edge(v[0], v[2]), edge(v[1], v[2])];
+
+        assert!(find_cycle(&v, &edges).is_none());
+        let order = topological_sort(&v, &edges).unwrap();
+        assert_eq!(order.order, vec![v[0], v[1], v[2], v[3]]);
+
+        // 3 -> 1 closes 1 -> 2 -> 3
+        edges.push(edge(v[3], v[1]));
+        let cycle = find_cycle(&v, &edges).unwrap();
+        assert_eq!(cycle.len(), 3);
+        for (a, b) in cycle.iter().zip(cycle.iter().cycle().skip(1)) {
+            assert_eq!(a.target, b.source);
+        }
+        assert!(topological_sort(&v, &edges).is_err());
+
+        let self_loop = vec![edge(v[0], v[0])];
+        assert_eq!(find_cycle(&v, &self_loop).unwrap().len(), 1);
+    }
+
+    #[test]
+    fn test_pagerank_and_degree() {
+        // a star: 1, 2, 3 -> 0
+        let v = ids(4);
+        let edges = vec![edge(v[1], v[0]), edge(v[2], v[0]), edge(v[3], v[0])];
+
+        let rank = pagerank(&v, &edges, PageRankOptions::default()).unwrap();
+        assert!(rank.converged);
+        assert!((rank.scores.values().sum::<f64>() - 1.0).abs() < 1e-9);
+        assert!(rank.scores[&v[0]] > rank.scores[&v[1]]);
+        assert!((rank.scores[&v[1]] - rank.scores[&v[3]]).abs() < 1e-12);
+
+        let options = PageRankOptions {
+            damping: 1.5,
+            ..Default::default()
+        };
+        assert!(pagerank(&v, &edges, options).is_err());
+
+        let degrees = degree_centrality(&v, &edges).degrees;
+        assert_eq!(
+            degrees[&v[0]],
+            Degree {
+                in_degree: 3,
+                out_degree: 0,
+                centrality: 1.0
+            }
+        );
+        assert_eq!(degrees[&v[1]].out_degree, 1);
+    }
+}
@@ -0,0 +1,610 @@
+//! Graph admin CLI
+//!
+//! ```sh
+//! p3m -u $MONGO_URI create-vertex --name node-1 --properties '{"age": 3}'
+//! p3m -u $MONGO_URI subgraph 62a0... --label follow --depth 2
+//! p3m -u $MONGO_URI export graph.graphml
+//! p3m -u $MONGO_URI export-tables vertex.parquet edge.parquet --vertex 62a0... --depth 2
+//! p3m -u $MONGO_URI ensure-indexes --unique endpoints-label
+//! p3m -u $MONGO_URI explain graph-lookup 62a0... --verbosity executionStats
+//! p3m -u $MONGO_URI analyze pagerank --write rank
+//! p3m -u $MONGO_URI truncate --dry-run
+//! ```
+
+use std::fs::File;
+use std::io::{BufReader, BufWriter};
+
+use bson::oid::ObjectId;
+use bson::{Bson, Document};
+use clap::{ArgEnum, ArgGroup, Parser, Subcommand};
+use p3m::{
+    Direction, EdgeDto, EdgeUniqueness, ExplainVerbosity, FindEdgeByVertexDto, GraphFormat,
+    GraphService, PageRankOptions, Pyo3MongoError, Pyo3MongoResult, TableFormat, Traversal,
+    VertexDto, VertexValues,
+};
+use tokio_stream::StreamExt;
+
+#[derive(Parser, Debug)]
+#[clap(about, version, author)]
+struct Args {
+    #[clap(short, long)]
+    uri: String,
+
+    #[clap(short, long, default_value = "graph")]
+    database: String,
+
+    #[clap(short, long, default_value = "dev")]
+    category: String,
+
+    #[clap(subcommand)]
+    command: Command,
+}
+
+#[derive(Subcommand, Debug)]
+enum Command {
+    /// create a vertex
+    CreateVertex {
+        #[clap(long)]
+        name: String,
+
+        /// JSON object
+        #[clap(long, parse(try_from_str = parse_properties))]
+        properties: Option<Document>,
+    },
+
+    /// create an edge, both endpoints must exist
+    CreateEdge {
+        #[clap(long)]
+        source: ObjectId,
+
+        #[clap(long)]
+        target: ObjectId,
+
+        #[clap(long)]
+        weight: Option<f64>,
+
+        #[clap(long)]
+        label: Option<String>,
+
+        /// JSON object
+        #[clap(long, parse(try_from_str = parse_properties))]
+        properties: Option<Document>,
+    },
+
+    GetVertex {
+        id: ObjectId,
+    },
+
+    GetEdge {
+        id: ObjectId,
+    },
+
+    /// rename a vertex, properties are kept unless given
+    UpdateVertex {
+        id: ObjectId,
+
+        #[clap(long)]
+        name: String,
+
+        /// JSON object
+        #[clap(long, parse(try_from_str = parse_properties))]
+        properties: Option<Document>,
+    },
+
+    /// overwrite endpoints, weight & label of an edge, properties are kept unless given
+    UpdateEdge {
+        id: ObjectId,
+
+        #[clap(long)]
+        source: ObjectId,
+
+        #[clap(long)]
+        target: ObjectId,
+
+        #[clap(long)]
+        weight: Option<f64>,
+
+        #[clap(long)]
+        label: Option<String>,
+
+        /// JSON object
+        #[clap(long, parse(try_from_str = parse_properties))]
+        properties: Option<Document>,
+    },
+
+    /// delete a vertex and all its edges
+    DeleteVertex {
+        id: ObjectId,
+    },
+
+    DeleteEdge {
+        id: ObjectId,
+    },
+
+    /// list all vertexes
+    Vertexes,
+
+    /// list all edges, optionally of a label
+    Edges {
+        #[clap(long)]
+        label: Option<String>,
+    },
+
+    /// edges of a vertex and the vertexes they reach, within `hops` edges
+    Neighbors {
+        id: ObjectId,
+
+        #[clap(long, default_value = "1")]
+        hops: u32,
+
+        /// out, in or both
+        #[clap(long, default_value = "out")]
+        direction: Direction,
+
+        #[clap(long)]
+        label: Option<String>,
+    },
+
+    /// edges reachable from a vertex (`$graphLookup`), along with their targets
+    Subgraph {
+        id: ObjectId,
+
+        #[clap(long)]
+        label: Option<String>,
+
+        /// max depth of the lookup, edges of the vertex are at depth 0
+        #[clap(long)]
+        depth: Option<i32>,
+    },
+
+    /// import a JSON Lines or GraphML file, vertexes get new ids
+    Import {
+        path: String,
+
+        /// jsonl or graphml, guessed by file extension if absent
+        #[clap(long)]
+        format: Option<GraphFormat>,
+
+        #[clap(long, default_value = "10000")]
+        batch_size: usize,
+    },
+
+    /// export the whole category
+    Export {
+        path: String,
+
+        /// jsonl or graphml, guessed by file extension if absent
+        #[clap(long)]
+        format: Option<GraphFormat>,
+    },
+
+    /// export the category, or a subgraph, as vertex & edge tables
+    ExportTables {
+        vertex_path: String,
+
+        edge_path: String,
+
+        /// parquet or ipc, guessed by the extension of `vertex_path` if absent
+        #[clap(long)]
+        format: Option<TableFormat>,
+
+        /// only the subgraph reachable from this vertex, see `subgraph`
+        #[clap(long)]
+        vertex: Option<ObjectId>,
+
+        #[clap(long, requires = "vertex")]
+        label: Option<String>,
+
+        #[clap(long, requires = "vertex")]
+        depth: Option<i32>,
+    },
+
+    /// vertex & edge counts, and degree distribution
+    Stats,
+
+    /// create the indexes used by traversals, idempotent
+    EnsureIndexes {
+        /// none, endpoints (source, target) or endpoints-label (source, target, label)
+        #[clap(long, default_value = "none")]
+        unique: EdgeUniqueness,
+    },
+
+    /// query plan of a traversal starting from a vertex
+    Explain {
+        #[clap(arg_enum)]
+        method: ExplainMethod,
+
+        id: ObjectId,
+
+        /// out, in or both, ignored by graph-lookup & delete-vertex
+        #[clap(long, default_value = "both")]
+        direction: Direction,
+
+        #[clap(long)]
+        label: Option<String>,
+
+        /// max depth of graph-lookup
+        #[clap(long)]
+        depth: Option<i32>,
+
+        /// queryPlanner, executionStats or allPlansExecution
+        #[clap(long, default_value = "queryPlanner")]
+        verbosity: ExplainVerbosity,
+    },
+
+    /// run a graph algorithm over the whole category
+    Analyze {
+        #[clap(arg_enum)]
+        algorithm: Algorithm,
+
+        /// store the result as this property of each vertex, ignored by cycle
+        #[clap(long)]
+        write: Option<String>,
+
+        /// damping factor of pagerank
+        #[clap(long, default_value = "0.85")]
+        damping: f64,
+    },
+
+    /// delete all vertexes & edges of the category
+    #[clap(group(ArgGroup::new("confirm").required(true).args(&["yes", "dry-run"])))]
+    Truncate {
+        /// really delete
+        #[clap(long)]
+        yes: bool,
+
+        /// only show what would be deleted
+        #[clap(long)]
+        dry_run: bool,
+    },
+}
+
+/// traversal methods, see `Traversal`
+#[derive(ArgEnum, Clone, Copy, Debug)]
+enum ExplainMethod {
+    EdgesByVertex,
+    DeleteVertex,
+    GraphLookup,
+    /// a single hop
+    Neighborhood,
+}
+
+/// see `p3m::algorithm`
+#[derive(ArgEnum, Clone, Copy, Debug)]
+enum Algorithm {
+    /// weakly connected components
+    Components,
+    StronglyConnected,
+    Cycle,
+    TopologicalSort,
+    Pagerank,
+    Degree,
+}
+
+fn parse_properties(s: &str) -> Result<Document, String> {
+    match serde_json::from_str(s).map_err(|e| e.to_string())? {
+        serde_json::Value::Object(map) => Document::try_from(map).map_err(|e| e.to_string()),
+        _ => Err("properties should be a JSON object".to_owned()),
+    }
+}
+
+fn graph_format(path: &str, format: Option<GraphFormat>) -> Pyo3MongoResult<GraphFormat> {
+    format
+        .or_else(|| GraphFormat::from_path(path))
+        .ok_or(Pyo3MongoError::Common("unknown graph format"))
+}
+
+fn edge_dto(
+    source: ObjectId,
+    target: ObjectId,
+    weight: Option<f64>,
+    label: Option<&str>,
+    properties: Option<Document>,
+) -> EdgeDto<'_> {
+    let dto = EdgeDto::new(source, target, weight, label);
+    match properties {
+        Some(p) => dto.with_properties(p),
+        None => dto,
+    }
+}
+
+#[tokio::main]
+async fn main() -> Pyo3MongoResult<()> {
+    let args = Args::parse();
+
+    let gs = GraphService::new(&args.uri, &args.database, &args.category).await?;
+
+    match args.command {
+        Command::CreateVertex { name, properties } => {
+            let mut dto = VertexDto::new(&name);
+            if let Some(p) = properties {
+                dto = dto.with_properties(p);
+            }
+            println!("{:?}", gs.create_vertex(dto).await?);
+        }
+        Command::CreateEdge {
+            source,
+            target,
+            weight,
+            label,
+            properties,
+        } => {
+            let dto = edge_dto(source, target, weight, label.as_deref(), properties);
+            println!("{:?}", gs.create_edge(dto).await?);
+        }
+        Command::GetVertex { id } => println!("{:?}", gs.get_vertex(id).await?),
+        Command::GetEdge { id } => println!("{:?}", gs.get_edge(id).await?),
+        Command::UpdateVertex {
+            id,
+            name,
+            properties,
+        } => {
+            let mut dto = VertexDto::new(&name);
+            if let Some(p) = properties {
+                dto = dto.with_properties(p);
+            }
+            gs.update_vertex(id, dto).await?;
+            println!("{:?}", gs.get_vertex(id).await?);
+        }
+        Command::UpdateEdge {
+            id,
+            source,
+            target,
+            weight,
+            label,
+            properties,
+        } => {
+            let dto = edge_dto(source, target, weight, label.as_deref(), properties);
+            gs.update_edge(id, dto).await?;
+            println!("{:?}", gs.get_edge(id).await?);
+        }
+        Command::DeleteVertex { id } => {
+            let edges = gs
+                .get_edges_by_vertex(FindEdgeByVertexDto::Bidirectional(id))
+                .await?;
+            gs.delete_vertex(id).await?;
+            println!("deleted vertex {id} and {} edges", edges.len());
+        }
+        Command::DeleteEdge { id } => {
+            gs.delete_edge(id).await?;
+            println!("deleted edge {id}");
+        }
+        Command::Vertexes => {
+            let mut vertexes = gs.stream_vertexes().await?;
+            while let Some(v) = vertexes.next().await {
+                println!("{:?}", v?);
+            }
+        }
+        Command::Edges { label } => {
+            let mut edges = gs.stream_edges(label.as_deref()).await?;
+            while let Some(e) = edges.next().await {
+                println!("{:?}", e?);
+            }
+        }
+        Command::Neighbors {
+            id,
+            hops,
+            direction,
+            label,
+        } => {
+            let hood = gs
+                .neighborhood(id, hops, direction, label.as_deref())
+                .await?;
+            for v in hood.vertexes {
+                println!("hop {}: {:?}", v.hop, v.vertex);
+            }
+            for e in hood.edges {
+                println!("hop {}: {:?}", e.hop, e.edge);
+            }
+        }
+        Command::Subgraph { id, label, depth } => {
+            let (edges, vertexes) = gs
+                .get_graph_from_vertex_by_label(id, label.as_deref(), depth)
+                .await?;
+            for v in vertexes {
+                println!("{:?}", v);
+            }
+            for e in edges {
+                println!("{:?}", e);
+            }
+        }
+        Command::Import {
+            path,
+            format,
+            batch_size,
+        } => {
+            let format = graph_format(&path, format)?;
+            let reader = BufReader::new(File::open(&path)?);
+            let summary = gs.import_graph(format, reader, batch_size).await?;
+            println!(
+                "imported {} vertexes and {} edges",
+                summary.vertexes, summary.edges
+            );
+        }
+        Command::Export { path, format } => {
+            let format = graph_format(&path, format)?;
+            let writer = BufWriter::new(File::create(&path)?);
+            let summary = gs.export_graph(format, writer).await?;
+            println!(
+                "exported {} vertexes and {} edges",
+                summary.vertexes, summary.edges
+            );
+        }
+        Command::ExportTables {
+            vertex_path,
+            edge_path,
+            format,
+            vertex,
+            label,
+            depth,
+        } => {
+            let format = format
+                .or_else(|| TableFormat::from_path(&vertex_path))
+                .ok_or(Pyo3MongoError::Common("unknown table format"))?;
+            let tables = match vertex {
+                Some(id) => gs.subgraph_tables(id, label.as_deref(), depth).await?,
+                None => gs.to_tables().await?,
+            };
+            let summary = tables.write(
+                format,
+                BufWriter::new(File::create(&vertex_path)?),
+                BufWriter::new(File::create(&edge_path)?),
+            )?;
+            println!(
+                "exported {} vertexes and {} edges",
+                summary.vertexes, summary.edges
+            );
+        }
+        Command::Stats => {
+            let stats = gs.stats().await?;
+            println!("vertexes: {}", stats.vertexes);
+            println!("edges: {}", stats.edges);
+            println!("degree distribution (degree: vertexes):");
+            for (degree, count) in stats.degrees {
+                println!("  {degree}: {count}");
+            }
+        }
+        Command::EnsureIndexes { unique } => {
+            for name in gs.ensure_indexes(unique).await? {
+                println!("{name}");
+            }
+        }
+        Command::Explain {
+            method,
+            id,
+            direction,
+            label,
+            depth,
+            verbosity,
+        } => {
+            let ids = [id];
+            let label = label.as_deref();
+            let traversal = match method {
+                ExplainMethod::EdgesByVertex => {
+                    Traversal::EdgesByVertex(FindEdgeByVertexDto::new(id, direction))
+                }
+                ExplainMethod::DeleteVertex => Traversal::DeleteVertex(id),
+                ExplainMethod::GraphLookup => Traversal::GraphLookup {
+                    vertex_id: id,
+                    label,
+                    depth,
+                },
+                ExplainMethod::Neighborhood => Traversal::AdjacentEdges {
+                    vertexes: &ids,
+                    direction,
+                    label,
+                },
+            };
+            let plan = gs.explain(traversal, verbosity).await?;
+            let json = Bson::Document(plan).into_relaxed_extjson();
+            println!("{}", serde_json::to_string_pretty(&json)?);
+        }
+        Command::Analyze {
+            algorithm,
+            write,
+            damping,
+        } => {
+            let values: Box<dyn VertexValues> = match algorithm {
+                Algorithm::Components | Algorithm::StronglyConnected => {
+                    let components = if let Algorithm::Components = algorithm {
+                        gs.connected_components().await?
+                    } else {
+                        gs.strongly_connected_components().await?
+                    };
+                    for (i, c) in components.components.iter().enumerate() {
+                        println!("component {i}: {} vertexes", c.len());
+                    }
+                    Box::new(components)
+                }
+                Algorithm::Cycle => {
+                    match gs.find_cycle().await? {
+                        Some(cycle) => cycle.iter().for_each(|e| println!("{:?}", e)),
+                        None => println!("no cycle"),
+                    }
+                    return Ok(());
+                }
+                Algorithm::TopologicalSort => {
+                    let order = gs.topological_sort().await?;
+                    order.order.iter().for_each(|id| println!("{id}"));
+                    Box::new(order)
+                }
+                Algorithm::Pagerank => {
+                    let options = PageRankOptions {
+                        damping,
+                        ..Default::default()
+                    };
+                    let rank = gs.pagerank(options).await?;
+                    let mut scores = rank.scores.iter().collect::<Vec<_>>();
+                    scores.sort_by(|a, b| b.1.total_cmp(a.1));
+                    scores.iter().for_each(|(id, s)| println!("{id}: {s}"));
+                    println!(
+                        "{} iterations, converged: {}",
+                        rank.iterations, rank.converged
+                    );
+                    Box::new(rank)
+                }
+                Algorithm::Degree => {
+                    let centrality = gs.degree_centrality().await?;
+                    for (id, d) in centrality.degrees.iter() {
+                        println!(
+                            "{id}: in {}, out {}, centrality {}",
+                            d.in_degree, d.out_degree, d.centrality
+                        );
+                    }
+                    Box::new(centrality)
+                }
+            };
+            if let Some(key) = write {
+                let updated = gs.write_vertex_values(&key, values.as_ref()).await?;
+                println!("wrote property {key} of {updated} vertexes");
+            }
+        }
+        Command::Truncate { dry_run, .. } => {
+            let (vertexes, edges) = (gs.count_vertexes().await?, gs.count_edges().await?);
+            if dry_run {
+                println!(
+                    "would delete {vertexes} vertexes and {edges} edges of category {}",
+                    args.category
+                );
+            } else {
+                gs.truncate_all().await?;
+                println!(
+                    "deleted {vertexes} vertexes and {edges} edges of category {}",
+                    args.category
+                );
+            }
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod test_cli {
+    use clap::CommandFactory;
+
+    use super::*;
+
+    #[test]
+    fn test_args() {
+        Args::command().debug_assert();
+
+        let parse = |cmd: &[&str]| Args::try_parse_from([&["p3m", "-u", "uri"], cmd].concat());
+        assert!(
+            parse(&["truncate"]).is_err(),
+            "truncate needs a confirmation"
+        );
+        assert!(parse(&["truncate", "--yes", "--dry-run"]).is_err());
+        assert!(parse(&["truncate", "--dry-run"]).is_ok());
+        assert!(parse(&["neighbors", "62a0a2b0e8d6c8f5b7a9e3c1", "--direction", "up"]).is_err());
+        assert!(parse(&["create-vertex", "--name", "n", "--properties", "[1]"]).is_err());
+        assert!(parse(&["export-tables", "v.parquet", "e.parquet", "--depth", "2"]).is_err());
+        assert!(parse(&["export-tables", "v.arrow", "e.arrow", "--format", "csv"]).is_err());
+        assert!(parse(&["ensure-indexes", "--unique", "endpoints-label"]).is_ok());
+        assert!(parse(&["ensure-indexes", "--unique", "label"]).is_err());
+        assert!(parse(&["explain", "graph-lookup", "62a0a2b0e8d6c8f5b7a9e3c1"]).is_ok());
+        assert!(parse(&["explain", "shortest-path", "62a0a2b0e8d6c8f5b7a9e3c1"]).is_err());
+        assert!(parse(&["analyze", "strongly-connected", "--write", "scc"]).is_ok());
+        assert!(parse(&["analyze", "betweenness"]).is_err());
+    }
+}
@@ -0,0 +1,305 @@
+//! Columnar
+//!
+//! A graph as two Arrow tables, for analysis by Polars, Pandas, DuckDB...
+//!
+//! 1. vertexes: `id`, `name`, `properties`
+//! 1. edges: `id`, `source`, `target`, `weight`, `label`, `properties`
+//!
+//! Ids are hex strings. Properties differ from one document to another, hence they are
+//! written as relaxed extended JSON strings (null if empty), same as GraphML; e.g. Polars
+//! decodes them by `pl.col("properties").str.json_decode()`.
+//!
+//! Tables are written to Parquet or Arrow IPC files, one file per table.
+
+use std::io::Write;
+use std::path::Path;
+use std::str::FromStr;
+
+use arrow2::array::{Array, MutableArray, MutablePrimitiveArray, MutableUtf8Array};
+use arrow2::chunk::Chunk;
+use arrow2::datatypes::{DataType, Field, Schema};
+use arrow2::io::ipc::write as ipc_write;
+use arrow2::io::parquet::write as parquet_write;
+use mongodb::bson::{Bson, Document};
+
+use super::model::{Edge, Vertex};
+use super::portable::BulkSummary;
+use super::{Pyo3MongoError, Pyo3MongoResult};
+
+/// file format of an exported table
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TableFormat {
+    Parquet,
+    /// Arrow IPC file, a.k.a. Feather v2
+    Ipc,
+}
+
+impl TableFormat {
+    /// guess the format by file extension
+    pub fn from_path<P: AsRef<Path>>(path: P) -> Option<Self> {
+        let ext = path.as_ref().extension()?.to_str()?;
+        ext.parse().ok()
+    }
+}
+
+impl FromStr for TableFormat {
+    type Err = Pyo3MongoError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "parquet" | "pq" => Ok(TableFormat::Parquet),
+            "ipc" | "arrow" | "feather" => Ok(TableFormat::Ipc),
+            _ => Err(Pyo3MongoError::Common("unknown table format")),
+        }
+    }
+}
+
+/// an Arrow table in a single chunk
+#[derive(Debug, Clone)]
+pub struct Table {
+    pub schema: Schema,
+    pub chunk: Chunk<Box<dyn Array>>,
+}
+
+impl Table {
+    pub fn len(&self) -> usize {
+        self.chunk.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.chunk.is_empty()
+    }
+
+    /// write by arrow2 itself, to any writer, e.g. the in-memory buffer read by Polars.
+    ///
+    /// `sqlx-arrow2::Datagrid` has writers of both formats, but that crate needs a nightly
+    /// compiler (`type_alias_impl_trait`), and `arrow-ipc` only writes to a file path.
+    pub fn write<W: Write>(&self, format: TableFormat, writer: W) -> Pyo3MongoResult<()> {
+        match format {
+            TableFormat::Parquet => self.write_parquet(writer),
+            TableFormat::Ipc => self.write_ipc(writer),
+        }
+    }
+
+    // a single row group, compressed by snappy, with statistics
+    fn write_parquet<W: Write>(&self, writer: W) -> Pyo3MongoResult<()> {
+        let options = parquet_write::WriteOptions {
+            write_statistics: true,
+            compression: parquet_write::CompressionOptions::Snappy,
+            version: parquet_write::Version::V2,
+            data_pagesize_limit: None,
+        };
+
+        let encodings = self
+            .schema
+            .fields
+            .iter()
+            .map(|f| parquet_write::transverse(f.data_type(), |_| parquet_write::Encoding::Plain))
+            .collect();
+        let row_groups = parquet_write::RowGroupIterator::try_new(
+            vec![Ok(self.chunk.clone())].into_iter(),
+            &self.schema,
+            options,
+            encodings,
+        )?;
+
+        let mut fw = parquet_write::FileWriter::try_new(writer, self.schema.clone(), options)?;
+        for group in row_groups {
+            fw.write(group?)?;
+        }
+        fw.end(None)?;
+
+        Ok(())
+    }
+
+    fn write_ipc<W: Write>(&self, writer: W) -> Pyo3MongoResult<()> {
+        let options = ipc_write::WriteOptions { compression: None };
+        let mut fw = ipc_write::FileWriter::new(writer, self.schema.clone(), None, options);
+
+        fw.start()?;
+        fw.write(&self.chunk, None)?;
+        fw.finish()?;
+
+        Ok(())
+    }
+}
+
+/// vertexes & edges tables of a graph, see `TablesBuilder`
+#[derive(Debug, Clone)]
+pub struct GraphTables {
+    pub vertexes: Table,
+    pub edges: Table,
+}
+
+impl GraphTables {
+    pub fn new(vertexes: &[Vertex], edges: &[Edge]) -> Self {
+        let mut builder = TablesBuilder::default();
+        vertexes.iter().for_each(|v| builder.push_vertex(v));
+        edges.iter().for_each(|e| builder.push_edge(e));
+        builder.finish()
+    }
+
+    /// write each table to its own file
+    pub fn write<V: Write, E: Write>(
+        &self,
+        format: TableFormat,
+        vertex_writer: V,
+        edge_writer: E,
+    ) -> Pyo3MongoResult<BulkSummary> {
+        self.vertexes.write(format, vertex_writer)?;
+        self.edges.write(format, edge_writer)?;
+
+        Ok(BulkSummary {
+            vertexes: self.vertexes.len(),
+            edges: self.edges.len(),
+        })
+    }
+}
+
+/// columns of both tables, filled vertex by vertex & edge by edge, so that a graph
+/// read from a cursor never needs to be loaded as documents
+#[derive(Default)]
+pub struct TablesBuilder {
+    vertex_id: MutableUtf8Array<i32>,
+    vertex_name: MutableUtf8Array<i32>,
+    vertex_properties: MutableUtf8Array<i32>,
+    edge_id: MutableUtf8Array<i32>,
+    edge_source: MutableUtf8Array<i32>,
+    edge_target: MutableUtf8Array<i32>,
+    edge_weight: MutablePrimitiveArray<f64>,
+    edge_label: MutableUtf8Array<i32>,
+    edge_properties: MutableUtf8Array<i32>,
+}
+
+impl TablesBuilder {
+    pub fn push_vertex(&mut self, vertex: &Vertex) {
+        self.vertex_id.push(vertex.id.map(|id| id.to_hex()));
+        self.vertex_name.push(Some(&vertex.name));
+        self.vertex_properties
+            .push(properties_json(&vertex.properties));
+    }
+
+    pub fn push_edge(&mut self, edge: &Edge) {
+        self.edge_id.push(edge.id.map(|id| id.to_hex()));
+        self.edge_source.push(Some(edge.source.to_hex()));
+        self.edge_target.push(Some(edge.target.to_hex()));
+        self.edge_weight.push(edge.weight);
+        self.edge_label.push(edge.label.as_ref());
+        self.edge_properties.push(properties_json(&edge.properties));
+    }
+
+    pub fn finish(mut self) -> GraphTables {
+        let vertexes = Table {
+            schema: Schema::from(vec![
+                Field::new("id", DataType::Utf8, true),
+                Field::new("name", DataType::Utf8, false),
+                Field::new("properties", DataType::Utf8, true),
+            ]),
+            chunk: Chunk::new(vec![
+                self.vertex_id.as_box(),
+                self.vertex_name.as_box(),
+                self.vertex_properties.as_box(),
+            ]),
+        };
+        let edges = Table {
+            schema: Schema::from(vec![
+                Field::new("id", DataType::Utf8, true),
+                Field::new("source", DataType::Utf8, false),
+                Field::new("target", DataType::Utf8, false),
+                Field::new("weight", DataType::Float64, true),
+                Field::new("label", DataType::Utf8, true),
+                Field::new("properties", DataType::Utf8, true),
+            ]),
+            chunk: Chunk::new(vec![
+                self.edge_id.as_box(),
+                self.edge_source.as_box(),
+                self.edge_target.as_box(),
+                self.edge_weight.as_box(),
+                self.edge_label.as_box(),
+                self.edge_properties.as_box(),
+            ]),
+        };
+
+        GraphTables { vertexes, edges }
+    }
+}
+
+fn properties_json(properties: &Document) -> Option<String> {
+    if properties.is_empty() {
+        return None;
+    }
+    Some(
+        Bson::Document(properties.clone())
+            .into_relaxed_extjson()
+            .to_string(),
+    )
+}
+
+#[cfg(test)]
+mod test_columnar {
+    use arrow2::array::{Float64Array, Utf8Array};
+    use arrow2::io::ipc::read;
+    use mongodb::bson::{doc, oid::ObjectId};
+
+    use super::*;
+
+    #[test]
+    fn test_graph_tables() {
+        let (v1, v2) = (ObjectId::new(), ObjectId::new());
+        let vertexes = vec![
+            Vertex {
+                id: Some(v1),
+                name: "node-1".to_owned(),
+                properties: doc! {"age": 3},
+            },
+            Vertex {
+                id: Some(v2),
+                name: "node-2".to_owned(),
+                properties: doc! {},
+            },
+        ];
+        let edges = vec![Edge {
+            id: Some(ObjectId::new()),
+            source: v1,
+            target: v2,
+            weight: None,
+            label: Some("follow".to_owned()),
+            properties: doc! {},
+        }];
+
+        let tables = GraphTables::new(&vertexes, &edges);
+        assert_eq!((tables.vertexes.len(), tables.edges.len()), (2, 1));
+
+        let arrays = tables.vertexes.chunk.arrays();
+        let ids = arrays[0].as_any().downcast_ref::<Utf8Array<i32>>().unwrap();
+        assert_eq!(ids.value(1), v2.to_hex());
+        let properties = arrays[2].as_any().downcast_ref::<Utf8Array<i32>>().unwrap();
+        assert_eq!(properties.get(0), Some(r#"{"age":3}"#));
+        assert_eq!(properties.get(1), None);
+
+        let arrays = tables.edges.chunk.arrays();
+        let weights = arrays[3].as_any().downcast_ref::<Float64Array>().unwrap();
+        assert_eq!(weights.get(0), None);
+
+        // IPC round trip
+        let mut buf = vec![];
+        tables.edges.write(TableFormat::Ipc, &mut buf).unwrap();
+        let mut reader = std::io::Cursor::new(buf);
+        let metadata = read::read_file_metadata(&mut reader).unwrap();
+        assert_eq!(metadata.schema, tables.edges.schema);
+        let chunks = read::FileReader::new(reader, metadata, None, None)
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+        assert_eq!(chunks, vec![tables.edges.chunk.clone()]);
+
+        assert_eq!(
+            TableFormat::from_path("g_vertex.parquet"),
+            Some(TableFormat::Parquet)
+        );
+        assert_eq!(
+            TableFormat::from_path("g_edge.arrow"),
+            Some(TableFormat::Ipc)
+        );
+        assert_eq!(TableFormat::from_path("g.csv"), None);
+    }
+}
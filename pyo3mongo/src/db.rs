@@ -1,6 +1,9 @@
 //! MongoDB
 
-use mongodb::{error::Error as MongoError, options::ClientOptions, Client};
+use mongodb::bson::{doc, Document};
+use mongodb::change_stream::{event::ChangeStreamEvent, ChangeStream};
+use mongodb::options::{ChangeStreamOptions, ClientOptions, CreateCollectionOptions};
+use mongodb::{error::Error as MongoError, Client, ClientSession};
 
 pub type MongoResult<T> = Result<T, MongoError>;
 
@@ -27,6 +30,62 @@ impl MongoClient {
         Ok(db_names)
     }
 
+    /// transactions are only available on a replica set or a sharded cluster,
+    /// a standalone server answers `hello` without `setName`
+    pub async fn supports_transaction(&self) -> MongoResult<bool> {
+        let hello = self
+            .client
+            .database("admin")
+            .run_command(doc! {"hello": 1}, None)
+            .await?;
+
+        Ok(hello.contains_key("setName") || hello.get_str("msg") == Ok("isdbgrid"))
+    }
+
+    /// explicit session, which is supported by standalone servers as well
+    pub async fn start_session(&self) -> MongoResult<ClientSession> {
+        self.client.start_session(None).await
+    }
+
+    /// set the validator of a collection, which is created if not existed.
+    /// An empty validator removes the validation.
+    pub async fn set_validator(&self, name: &str, validator: Document) -> MongoResult<()> {
+        let db = self.client.database(&self.db);
+
+        let existed = db.list_collection_names(doc! {"name": name}).await?;
+        if existed.is_empty() {
+            let co = CreateCollectionOptions::builder()
+                .validator(validator)
+                .build();
+            db.create_collection(name, co).await?;
+        } else {
+            db.run_command(doc! {"collMod": name, "validator": validator}, None)
+                .await?;
+        }
+
+        Ok(())
+    }
+
+    /// change stream of the whole database, requires a replica set or a sharded cluster
+    pub async fn watch(
+        &self,
+        pipeline: Vec<Document>,
+        options: ChangeStreamOptions,
+    ) -> MongoResult<ChangeStream<ChangeStreamEvent<Document>>> {
+        self.client
+            .database(&self.db)
+            .watch(pipeline, options)
+            .await
+    }
+
+    /// run a database command, e.g. `explain`
+    pub async fn run_command(&self, command: Document) -> MongoResult<Document> {
+        self.client
+            .database(&self.db)
+            .run_command(command, None)
+            .await
+    }
+
     /// specify which collection to be operated, and what schema
     /// is to be used (by generic parameter `T`)
     pub fn collection<T>(&self, name: &str) -> mongodb::Collection<T> {
@@ -48,4 +107,12 @@ mod pyo3mongo_tests {
 
         println!("{:?}", db_names);
     }
+
+    #[tokio::test]
+    async fn test_supports_transaction() {
+        let client = MongoClient::new(URI, DB).await.unwrap();
+        let supported = client.supports_transaction().await;
+
+        assert!(supported.is_ok());
+    }
 }
@@ -1,13 +1,31 @@
 //! Pyo3Mongo
 
+pub mod algorithm;
+pub mod columnar;
 pub mod db;
 pub mod model;
 pub mod package;
+pub mod package_async;
+pub mod portable;
 pub mod service;
+pub mod store;
+pub mod traversal;
 
+pub use algorithm::{
+    Components, Degree, DegreeCentrality, PageRank, PageRankOptions, TopologicalOrder, VertexValues,
+};
+pub use columnar::{GraphTables, Table, TableFormat, TablesBuilder};
 pub use model::*;
+pub use portable::{BulkSummary, GraphFormat, GraphReader, GraphRecord, GraphWriter};
 pub use service::GraphService;
+pub use store::{
+    EdgeUniqueness, EntityStream, ExplainVerbosity, GraphEventStream, GraphStore, MemoryStore,
+    MongoStore, TransactionMode, Traversal,
+};
 
+pub use mongodb::change_stream::event::ResumeToken;
+
+use bson::oid::ObjectId;
 use thiserror::Error;
 
 pub type Pyo3MongoResult<T> = Result<T, Pyo3MongoError>;
@@ -17,6 +35,18 @@ pub enum Pyo3MongoError {
     #[error("common error {0}")]
     Common(&'static str),
 
+    #[error("vertex not found: {0}")]
+    VertexNotFound(ObjectId),
+
+    #[error("edge not found: {0}")]
+    EdgeNotFound(ObjectId),
+
+    #[error("transaction is not supported by a standalone deployment")]
+    TransactionUnsupported,
+
+    #[error("transaction aborted: {0}")]
+    TransactionAborted(mongodb::error::Error),
+
     #[error(transparent)]
     Mongo(#[from] mongodb::error::Error),
 
@@ -25,4 +55,16 @@ pub enum Pyo3MongoError {
 
     #[error(transparent)]
     Oid(#[from] bson::oid::Error),
+
+    #[error(transparent)]
+    Io(#[from] std::io::Error),
+
+    #[error(transparent)]
+    Json(#[from] serde_json::Error),
+
+    #[error(transparent)]
+    Xml(#[from] quick_xml::Error),
+
+    #[error(transparent)]
+    Arrow(#[from] arrow2::error::Error),
 }
@@ -7,11 +7,19 @@
 //! Creation of a graph:
 //! 1. Create two `Vertex`s `v1` and `v2`
 //! 2. Create an `Edge` that connects `v1` and `v2`
+//!
+//! Both `Vertex` and `Edge` carry a free-form `properties` document for arbitrary
+//! metadata, which can be queried by `PropertyFilter`.
+
+use std::collections::BTreeMap;
+use std::str::FromStr;
 
-use mongodb::bson::{self, oid::ObjectId, Document};
+use mongodb::bson::{self, doc, oid::ObjectId, Bson, Document};
 use pyo3::prelude::*;
 use serde::{Deserialize, Serialize};
 
+use super::Pyo3MongoError;
+
 #[derive(Serialize, Deserialize, PartialEq, Eq, Clone, Debug)]
 pub struct PureId {
     #[serde(rename = "_id")]
@@ -28,15 +36,20 @@ pub struct Edge {
     pub target: ObjectId,
     pub weight: Option<f64>,
     pub label: Option<String>,
+    // omitted when empty, so that a `$set` update keeps the stored properties
+    #[serde(default, skip_serializing_if = "Document::is_empty")]
+    pub properties: Document,
 }
 
 /// vertex
 #[pyclass]
-#[derive(Serialize, Deserialize, PartialEq, Eq, Clone, Debug)]
+#[derive(Serialize, Deserialize, PartialEq, Clone, Debug)]
 pub struct Vertex {
     #[serde(rename = "_id", skip_serializing_if = "Option::is_none")]
     pub id: Option<ObjectId>,
     pub name: String,
+    #[serde(default, skip_serializing_if = "Document::is_empty")]
+    pub properties: Document,
 }
 
 // required by Mongo query
@@ -60,6 +73,7 @@ pub struct EdgeDto<'a> {
     pub target: ObjectId,
     pub weight: Option<f64>,
     pub label: Option<&'a str>,
+    pub properties: Option<Document>,
 }
 
 impl<'a> EdgeDto<'a> {
@@ -74,8 +88,14 @@ impl<'a> EdgeDto<'a> {
             target,
             weight,
             label,
+            properties: None,
         }
     }
+
+    pub fn with_properties(mut self, properties: Document) -> Self {
+        self.properties = Some(properties);
+        self
+    }
 }
 
 impl<'a> From<EdgeDto<'a>> for Edge {
@@ -86,6 +106,7 @@ impl<'a> From<EdgeDto<'a>> for Edge {
             target: source.target,
             weight: source.weight,
             label: source.label.map(str::to_string),
+            properties: source.properties.unwrap_or_default(),
         }
     }
 }
@@ -94,11 +115,20 @@ impl<'a> From<EdgeDto<'a>> for Edge {
 #[derive(Serialize, Deserialize, Debug)]
 pub struct VertexDto<'a> {
     pub name: &'a str,
+    pub properties: Option<Document>,
 }
 
 impl<'a> VertexDto<'a> {
     pub fn new(name: &'a str) -> Self {
-        VertexDto { name }
+        VertexDto {
+            name,
+            properties: None,
+        }
+    }
+
+    pub fn with_properties(mut self, properties: Document) -> Self {
+        self.properties = Some(properties);
+        self
     }
 }
 
@@ -107,10 +137,180 @@ impl<'a> From<VertexDto<'a>> for Vertex {
         Vertex {
             id: None,
             name: source.name.to_string(),
+            properties: source.properties.unwrap_or_default(),
+        }
+    }
+}
+
+/// predicates on `properties` of vertexes or edges, combined by AND.
+///
+/// Keys are relative to `properties`, and may be dotted to reach nested fields:
+/// ```ignore
+/// PropertyFilter::new().gte("age", 18).eq("address.city", "Shanghai")
+/// ```
+#[derive(Default, Clone, Debug, PartialEq)]
+pub struct PropertyFilter(Document);
+
+impl PropertyFilter {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// raw Mongo query, e.g. `{"age": {"$gt": 3}}` or `{"$or": [{"age": 3}, {"name": "n"}]}`,
+    /// field keys are relative to `properties`
+    pub fn from_document(query: Document) -> Self {
+        PropertyFilter(query)
+    }
+
+    // several operators on the same key are merged, e.g. `{"age": {"$gt": 1, "$lt": 9}}`
+    fn with(mut self, key: &str, op: &str, value: Bson) -> Self {
+        match self.0.get_mut(key) {
+            Some(Bson::Document(ops)) => {
+                ops.insert(op, value);
+            }
+            _ => {
+                let mut ops = Document::new();
+                ops.insert(op, value);
+                self.0.insert(key, ops);
+            }
+        }
+        self
+    }
+
+    pub fn eq<V: Into<Bson>>(self, key: &str, value: V) -> Self {
+        self.with(key, "$eq", value.into())
+    }
+
+    pub fn ne<V: Into<Bson>>(self, key: &str, value: V) -> Self {
+        self.with(key, "$ne", value.into())
+    }
+
+    pub fn gt<V: Into<Bson>>(self, key: &str, value: V) -> Self {
+        self.with(key, "$gt", value.into())
+    }
+
+    pub fn gte<V: Into<Bson>>(self, key: &str, value: V) -> Self {
+        self.with(key, "$gte", value.into())
+    }
+
+    pub fn lt<V: Into<Bson>>(self, key: &str, value: V) -> Self {
+        self.with(key, "$lt", value.into())
+    }
+
+    pub fn lte<V: Into<Bson>>(self, key: &str, value: V) -> Self {
+        self.with(key, "$lte", value.into())
+    }
+
+    pub fn is_in<V: Into<Bson>>(self, key: &str, values: Vec<V>) -> Self {
+        let values = values.into_iter().map(Into::into).collect::<Vec<_>>();
+        self.with(key, "$in", Bson::Array(values))
+    }
+
+    pub fn exists(self, key: &str, exists: bool) -> Self {
+        self.with(key, "$exists", Bson::Boolean(exists))
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    /// Mongo query, with every field key prefixed by `properties.`, also within `$and`, `$or` &
+    /// `$nor`, other operators are left alone
+    pub fn to_document(&self) -> Document {
+        prefix_properties(&self.0)
+    }
+}
+
+fn prefix_properties(query: &Document) -> Document {
+    query
+        .iter()
+        .map(|(k, v)| match (k.as_str(), v) {
+            ("$and" | "$or" | "$nor", Bson::Array(clauses)) => {
+                let clauses = clauses
+                    .iter()
+                    .map(|c| match c {
+                        Bson::Document(c) => Bson::Document(prefix_properties(c)),
+                        c => c.clone(),
+                    })
+                    .collect();
+                (k.clone(), Bson::Array(clauses))
+            }
+            _ if k.starts_with('$') => (k.clone(), v.clone()),
+            _ => (format!("properties.{k}"), v.clone()),
+        })
+        .collect()
+}
+
+/// order of a page, by id, i.e. by insertion
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum SortOrder {
+    #[default]
+    Ascending,
+    Descending,
+}
+
+/// keyset pagination by id, stable while documents are inserted or deleted:
+/// ```ignore
+/// let page = gs.get_vertexes_page(&PageQuery::new(100)).await?;
+/// let query = PageQuery::new(100).after(page.next.unwrap());
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PageQuery {
+    pub limit: usize,
+    /// id of the last document of the previous page, in `order`
+    pub after: Option<ObjectId>,
+    pub order: SortOrder,
+}
+
+impl PageQuery {
+    pub fn new(limit: usize) -> Self {
+        PageQuery {
+            limit,
+            after: None,
+            order: SortOrder::default(),
+        }
+    }
+
+    pub fn after(mut self, id: ObjectId) -> Self {
+        self.after = Some(id);
+        self
+    }
+
+    pub fn order(mut self, order: SortOrder) -> Self {
+        self.order = order;
+        self
+    }
+
+    /// Mongo query of ids beyond `after`
+    pub fn to_document(&self) -> Document {
+        let op = match self.order {
+            SortOrder::Ascending => "$gt",
+            SortOrder::Descending => "$lt",
+        };
+        match self.after {
+            Some(id) => doc! {"_id": {op: id}},
+            None => Document::new(),
+        }
+    }
+
+    /// Mongo sort by id
+    pub fn sort(&self) -> Document {
+        match self.order {
+            SortOrder::Ascending => doc! {"_id": 1},
+            SortOrder::Descending => doc! {"_id": -1},
         }
     }
 }
 
+/// vertexes or edges of a `PageQuery`
+#[derive(Debug, Clone, PartialEq)]
+pub struct Page<T> {
+    pub items: Vec<T>,
+    /// `after` of the following page, `None` once the last page is reached.
+    /// A full last page is followed by an empty one.
+    pub next: Option<ObjectId>,
+}
+
 // used for mongo query lookup's orientation
 #[derive(Serialize, Deserialize, Debug)]
 pub enum FindEdgeByVertexDto {
@@ -120,6 +320,15 @@ pub enum FindEdgeByVertexDto {
 }
 
 impl FindEdgeByVertexDto {
+    /// `Outbound` edges have the vertex as source, `Inbound` edges as target
+    pub fn new(id: ObjectId, direction: Direction) -> Self {
+        match direction {
+            Direction::Outbound => FindEdgeByVertexDto::Source(id),
+            Direction::Inbound => FindEdgeByVertexDto::Target(id),
+            Direction::Both => FindEdgeByVertexDto::Bidirectional(id),
+        }
+    }
+
     pub fn id(&self) -> ObjectId {
         match self {
             FindEdgeByVertexDto::Source(id) => id.to_owned(),
@@ -128,3 +337,177 @@ impl FindEdgeByVertexDto {
         }
     }
 }
+
+/// direction of edges to follow from a vertex
+#[derive(Serialize, Deserialize, Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum Direction {
+    /// source -> target, the same as `$graphLookup`
+    #[default]
+    Outbound,
+    /// target -> source
+    Inbound,
+    Both,
+}
+
+impl FromStr for Direction {
+    type Err = Pyo3MongoError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "out" | "outbound" => Ok(Direction::Outbound),
+            "in" | "inbound" => Ok(Direction::Inbound),
+            "both" | "bidirectional" => Ok(Direction::Both),
+            _ => Err(Pyo3MongoError::Common("unknown direction")),
+        }
+    }
+}
+
+/// a path from one vertex to another, `vertexes` & `edges` are ordered from the start
+#[pyclass]
+#[derive(PartialEq, Clone, Debug)]
+pub struct GraphPath {
+    #[pyo3(get)]
+    pub vertexes: Vec<Vertex>,
+    #[pyo3(get)]
+    pub edges: Vec<Edge>,
+    /// number of edges for an unweighted path, sum of weights for a weighted one
+    #[pyo3(get)]
+    pub cost: f64,
+}
+
+/// vertex with its distance (in hops) from the start vertex
+#[pyclass]
+#[derive(PartialEq, Clone, Debug)]
+pub struct HopVertex {
+    #[pyo3(get)]
+    pub vertex: Vertex,
+    #[pyo3(get)]
+    pub hop: u32,
+}
+
+/// edge with the hop it has been reached at, edges of the start vertex are at hop 1
+#[pyclass]
+#[derive(PartialEq, Clone, Debug)]
+pub struct HopEdge {
+    #[pyo3(get)]
+    pub edge: Edge,
+    #[pyo3(get)]
+    pub hop: u32,
+}
+
+/// k-hop neighborhood of a vertex, including the vertex itself at hop 0
+#[pyclass]
+#[derive(PartialEq, Clone, Debug, Default)]
+pub struct Neighborhood {
+    #[pyo3(get)]
+    pub vertexes: Vec<HopVertex>,
+    #[pyo3(get)]
+    pub edges: Vec<HopEdge>,
+}
+
+/// kind of change of a document, see `GraphEvent`
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Operation {
+    Created,
+    /// an update or a replacement
+    Updated,
+    Deleted,
+}
+
+/// change of a vertex or an edge, read from a change stream (see `GraphService::watch`).
+///
+/// The document is the current one when the event is read rather than at the time of
+/// the change, `None` if deleted since.
+#[derive(Debug, Clone, PartialEq)]
+pub enum GraphEvent {
+    Vertex {
+        operation: Operation,
+        id: ObjectId,
+        vertex: Option<Vertex>,
+    },
+    Edge {
+        operation: Operation,
+        id: ObjectId,
+        edge: Option<Edge>,
+    },
+}
+
+impl GraphEvent {
+    pub fn operation(&self) -> Operation {
+        match self {
+            GraphEvent::Vertex { operation, .. } | GraphEvent::Edge { operation, .. } => *operation,
+        }
+    }
+
+    pub fn id(&self) -> ObjectId {
+        match self {
+            GraphEvent::Vertex { id, .. } | GraphEvent::Edge { id, .. } => *id,
+        }
+    }
+}
+
+/// size of a graph, see `GraphService::stats`
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct GraphStats {
+    pub vertexes: usize,
+    pub edges: usize,
+    /// degree (in + out edges) -> number of vertexes, isolated vertexes are at 0
+    pub degrees: BTreeMap<usize, usize>,
+}
+
+#[cfg(test)]
+mod test_model {
+    use mongodb::bson::doc;
+
+    use super::*;
+
+    #[test]
+    fn test_property_filter() {
+        let filter = PropertyFilter::new()
+            .gte("age", 18)
+            .lt("age", 60)
+            .is_in("address.city", vec!["Shanghai", "Beijing"]);
+
+        assert_eq!(
+            filter.to_document(),
+            doc! {
+                "properties.age": {"$gte": 18, "$lt": 60},
+                "properties.address.city": {"$in": ["Shanghai", "Beijing"]}
+            }
+        );
+    }
+
+    #[test]
+    fn test_property_filter_operators() {
+        let filter = PropertyFilter::from_document(doc! {
+            "$or": [{"age": {"$lt": 18}}, {"$and": [{"age": {"$gte": 60}}, {"retired": true}]}],
+            "$comment": "age",
+            "name": "n"
+        });
+
+        assert_eq!(
+            filter.to_document(),
+            doc! {
+                "$or": [
+                    {"properties.age": {"$lt": 18}},
+                    {"$and": [{"properties.age": {"$gte": 60}}, {"properties.retired": true}]}
+                ],
+                "$comment": "age",
+                "properties.name": "n"
+            }
+        );
+    }
+
+    #[test]
+    fn test_page_query() {
+        let id = ObjectId::new();
+
+        let query = PageQuery::new(10);
+        assert_eq!(query.to_document(), doc! {});
+        assert_eq!(query.sort(), doc! {"_id": 1});
+
+        let query = query.after(id).order(SortOrder::Descending);
+        assert_eq!(query.to_document(), doc! {"_id": {"$lt": id}});
+        assert_eq!(query.sort(), doc! {"_id": -1});
+    }
+}
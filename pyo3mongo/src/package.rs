@@ -1,21 +1,162 @@
 //! Pyo3 Async
 
+use std::collections::{HashMap, VecDeque};
+use std::fs::File;
+use std::io::{BufReader, BufWriter};
 use std::str::FromStr;
 
 use bson::oid::ObjectId;
-use pyo3::exceptions::{PyBaseException, PyValueError};
+use bson::{Bson, Document};
+use pyo3::create_exception;
+use pyo3::exceptions::{PyException, PyIOError, PyTypeError, PyValueError};
 use pyo3::prelude::*;
+use pyo3::types::{PyBool, PyBytes, PyDict, PyFloat, PyInt, PyList, PyString, PyTuple};
+use serde_json::Value;
 use tokio::runtime::Runtime;
 
-use crate::{Edge, EdgeDto, GraphService, Pyo3MongoError, Vertex, VertexDto};
+use crate::package_async::{AsyncPyGraph, EventOutput, EventStream};
+use crate::{
+    BulkSummary, Degree, Direction, Edge, EdgeDto, EdgeUniqueness, ExplainVerbosity,
+    FindEdgeByVertexDto, GraphFormat, GraphPath, GraphService, GraphTables, HopEdge, HopVertex,
+    Neighborhood, Page, PageQuery, PageRankOptions, PropertyFilter, Pyo3MongoError, SortOrder,
+    TableFormat, Traversal, Vertex, VertexDto,
+};
+
+// Python exceptions, all of them (but `OSError`) can be caught by `p3m.P3mError`
+create_exception!(p3m, P3mError, PyException);
+create_exception!(p3m, VertexNotFoundError, P3mError);
+create_exception!(p3m, EdgeNotFoundError, P3mError);
+create_exception!(p3m, InvalidIdError, P3mError);
+create_exception!(p3m, TransactionError, P3mError);
+create_exception!(p3m, MongoError, P3mError);
+create_exception!(p3m, FormatError, P3mError);
 
 // turn Pyo3MongoError into PyResult
 impl From<Pyo3MongoError> for PyErr {
     fn from(e: Pyo3MongoError) -> Self {
-        PyBaseException::new_err(e.to_string())
+        let msg = e.to_string();
+        match e {
+            Pyo3MongoError::Common(_) => P3mError::new_err(msg),
+            Pyo3MongoError::VertexNotFound(_) => VertexNotFoundError::new_err(msg),
+            Pyo3MongoError::EdgeNotFound(_) => EdgeNotFoundError::new_err(msg),
+            Pyo3MongoError::Oid(_) => InvalidIdError::new_err(msg),
+            Pyo3MongoError::TransactionUnsupported | Pyo3MongoError::TransactionAborted(_) => {
+                TransactionError::new_err(msg)
+            }
+            Pyo3MongoError::Mongo(_) => MongoError::new_err(msg),
+            Pyo3MongoError::De(_)
+            | Pyo3MongoError::Json(_)
+            | Pyo3MongoError::Xml(_)
+            | Pyo3MongoError::Arrow(_) => FormatError::new_err(msg),
+            Pyo3MongoError::Io(e) => PyIOError::new_err(e.to_string()),
+        }
+    }
+}
+
+// parse a Python `str` id
+pub(crate) fn oid(s: &str) -> PyResult<ObjectId> {
+    Ok(ObjectId::from_str(s).map_err(Pyo3MongoError::from)?)
+}
+
+pub(crate) fn oids(ids: &[String]) -> PyResult<Vec<ObjectId>> {
+    ids.iter().map(|s| oid(s)).collect()
+}
+
+/// `properties` document seen as a `dict` by Python.
+///
+/// BSON types without a Python counterpart are in relaxed extended JSON,
+/// e.g. an `ObjectId` is `{"$oid": "..."}`
+#[derive(Clone, Debug, Default)]
+pub struct PyProperties(pub Document);
+
+impl<'py> FromPyObject<'py> for PyProperties {
+    fn extract_bound(ob: &Bound<'py, PyAny>) -> PyResult<Self> {
+        let dict = ob.downcast::<PyDict>()?;
+        match py_to_json(dict.as_any())? {
+            Value::Object(map) => Document::try_from(map)
+                .map(PyProperties)
+                .map_err(|e| PyValueError::new_err(e.to_string())),
+            _ => Err(PyTypeError::new_err("properties should be a dict")),
+        }
+    }
+}
+
+impl<'py> IntoPyObject<'py> for PyProperties {
+    type Target = PyAny;
+    type Output = Bound<'py, PyAny>;
+    type Error = PyErr;
+
+    fn into_pyobject(self, py: Python<'py>) -> Result<Self::Output, Self::Error> {
+        json_to_py(py, &Bson::Document(self.0).into_relaxed_extjson())
     }
 }
 
+fn py_to_json(ob: &Bound<'_, PyAny>) -> PyResult<Value> {
+    // `bool` is a subclass of `int` in Python, check it first
+    if ob.is_none() {
+        Ok(Value::Null)
+    } else if let Ok(b) = ob.downcast::<PyBool>() {
+        Ok(Value::Bool(b.is_true()))
+    } else if ob.downcast::<PyInt>().is_ok() {
+        Ok(Value::from(ob.extract::<i64>()?))
+    } else if let Ok(f) = ob.downcast::<PyFloat>() {
+        serde_json::Number::from_f64(f.value())
+            .map(Value::Number)
+            .ok_or_else(|| PyValueError::new_err("NaN or infinite float in properties"))
+    } else if let Ok(s) = ob.downcast::<PyString>() {
+        Ok(Value::String(s.to_str()?.to_owned()))
+    } else if let Ok(d) = ob.downcast::<PyDict>() {
+        let mut map = serde_json::Map::new();
+        for (k, v) in d.iter() {
+            map.insert(k.extract::<String>()?, py_to_json(&v)?);
+        }
+        Ok(Value::Object(map))
+    } else if let Ok(l) = ob.downcast::<PyList>() {
+        l.iter()
+            .map(|v| py_to_json(&v))
+            .collect::<PyResult<Vec<_>>>()
+            .map(Value::Array)
+    } else if let Ok(t) = ob.downcast::<PyTuple>() {
+        t.iter()
+            .map(|v| py_to_json(&v))
+            .collect::<PyResult<Vec<_>>>()
+            .map(Value::Array)
+    } else {
+        Err(PyTypeError::new_err(format!(
+            "unsupported property type: {}",
+            ob.get_type().name()?
+        )))
+    }
+}
+
+fn json_to_py<'py>(py: Python<'py>, value: &Value) -> PyResult<Bound<'py, PyAny>> {
+    let obj = match value {
+        Value::Null => py.None().into_bound(py),
+        Value::Bool(b) => PyBool::new(py, *b).to_owned().into_any(),
+        Value::Number(n) => match n.as_i64() {
+            Some(i) => i.into_pyobject(py)?.into_any(),
+            None => n.as_f64().unwrap_or_default().into_pyobject(py)?.into_any(),
+        },
+        Value::String(s) => PyString::new(py, s).into_any(),
+        Value::Array(a) => {
+            let list = PyList::empty(py);
+            for v in a {
+                list.append(json_to_py(py, v)?)?;
+            }
+            list.into_any()
+        }
+        Value::Object(m) => {
+            let dict = PyDict::new(py);
+            for (k, v) in m {
+                dict.set_item(k, json_to_py(py, v)?)?;
+            }
+            dict.into_any()
+        }
+    };
+
+    Ok(obj)
+}
+
 // getter & setter for Vertex
 #[pymethods]
 impl Vertex {
@@ -37,6 +178,17 @@ impl Vertex {
         self.name = name.to_owned();
         Ok(())
     }
+
+    #[getter]
+    pub fn get_properties(&self) -> PyResult<PyProperties> {
+        Ok(PyProperties(self.properties.clone()))
+    }
+
+    #[setter]
+    pub fn set_properties(&mut self, value: PyProperties) -> PyResult<()> {
+        self.properties = value.0;
+        Ok(())
+    }
 }
 
 // getter & setter for Edge
@@ -105,6 +257,17 @@ impl Edge {
         self.label = Some(value.to_string());
         Ok(())
     }
+
+    #[getter]
+    pub fn get_properties(&self) -> PyResult<PyProperties> {
+        Ok(PyProperties(self.properties.clone()))
+    }
+
+    #[setter]
+    pub fn set_properties(&mut self, value: PyProperties) -> PyResult<()> {
+        self.properties = value.0;
+        Ok(())
+    }
 }
 
 #[pyclass]
@@ -124,18 +287,27 @@ pub struct EdgeInput {
     pub weight: Option<f64>,
     #[pyo3(get, set)]
     pub label: Option<String>,
+    #[pyo3(get, set)]
+    pub properties: Option<PyProperties>,
 }
 
 #[pymethods]
 impl EdgeInput {
     #[new]
-    #[pyo3(signature = (source, target, weight, label))]
-    fn new(source: String, target: String, weight: Option<f64>, label: Option<String>) -> Self {
+    #[pyo3(signature = (source, target, weight, label, properties=None))]
+    fn new(
+        source: String,
+        target: String,
+        weight: Option<f64>,
+        label: Option<String>,
+        properties: Option<PyProperties>,
+    ) -> Self {
         EdgeInput {
             source,
             target,
             weight,
             label,
+            properties,
         }
     }
 }
@@ -149,6 +321,7 @@ impl<'a> TryFrom<&'a EdgeInput> for EdgeDto<'a> {
             target: ObjectId::from_str(&value.target)?,
             weight: value.weight,
             label: value.label.as_deref(),
+            properties: value.properties.as_ref().map(|p| p.0.clone()),
         };
         Ok(v)
     }
@@ -162,6 +335,200 @@ pub struct GraphOutput {
     pub edges: Vec<Edge>,
 }
 
+#[pyclass]
+pub struct BulkOutput {
+    #[pyo3(get)]
+    pub vertexes: usize,
+    #[pyo3(get)]
+    pub edges: usize,
+}
+
+impl From<BulkSummary> for BulkOutput {
+    fn from(source: BulkSummary) -> Self {
+        BulkOutput {
+            vertexes: source.vertexes,
+            edges: source.edges,
+        }
+    }
+}
+
+#[pyclass]
+pub struct DegreeOutput {
+    #[pyo3(get)]
+    pub in_degree: usize,
+    #[pyo3(get)]
+    pub out_degree: usize,
+    #[pyo3(get)]
+    pub centrality: f64,
+}
+
+impl From<Degree> for DegreeOutput {
+    fn from(source: Degree) -> Self {
+        DegreeOutput {
+            in_degree: source.in_degree,
+            out_degree: source.out_degree,
+            centrality: source.centrality,
+        }
+    }
+}
+
+/// `next` is the `after` of the following page, `None` at the last page
+#[pyclass]
+pub struct VertexPage {
+    #[pyo3(get)]
+    pub items: Vec<Vertex>,
+    #[pyo3(get)]
+    pub next: Option<String>,
+}
+
+impl From<Page<Vertex>> for VertexPage {
+    fn from(source: Page<Vertex>) -> Self {
+        VertexPage {
+            items: source.items,
+            next: source.next.map(|id| id.to_hex()),
+        }
+    }
+}
+
+/// see `VertexPage`
+#[pyclass]
+pub struct EdgePage {
+    #[pyo3(get)]
+    pub items: Vec<Edge>,
+    #[pyo3(get)]
+    pub next: Option<String>,
+}
+
+impl From<Page<Edge>> for EdgePage {
+    fn from(source: Page<Edge>) -> Self {
+        EdgePage {
+            items: source.items,
+            next: source.next.map(|id| id.to_hex()),
+        }
+    }
+}
+
+/// iterator of all vertexes, fetched page by page, see `PyGraph.iter_vertexes`
+#[pyclass]
+pub struct VertexIterator {
+    graph: Py<PyGraph>,
+    query: PageQuery,
+    buffer: VecDeque<Vertex>,
+    done: bool,
+}
+
+#[pymethods]
+impl VertexIterator {
+    fn __iter__(slf: PyRef<'_, Self>) -> PyRef<'_, Self> {
+        slf
+    }
+
+    fn __next__(mut slf: PyRefMut<'_, Self>) -> PyResult<Option<Vertex>> {
+        if slf.buffer.is_empty() && !slf.done {
+            let graph = slf.graph.clone_ref(slf.py());
+            let graph = graph.borrow(slf.py());
+            let page = graph
+                .runtime
+                .block_on(async { graph.service.get_vertexes_page(&slf.query).await })?;
+
+            slf.done = page.next.is_none();
+            slf.query.after = page.next;
+            slf.buffer.extend(page.items);
+        }
+
+        Ok(slf.buffer.pop_front())
+    }
+}
+
+/// iterator of all edges (of a label), see `VertexIterator`
+#[pyclass]
+pub struct EdgeIterator {
+    graph: Py<PyGraph>,
+    label: Option<String>,
+    query: PageQuery,
+    buffer: VecDeque<Edge>,
+    done: bool,
+}
+
+#[pymethods]
+impl EdgeIterator {
+    fn __iter__(slf: PyRef<'_, Self>) -> PyRef<'_, Self> {
+        slf
+    }
+
+    fn __next__(mut slf: PyRefMut<'_, Self>) -> PyResult<Option<Edge>> {
+        if slf.buffer.is_empty() && !slf.done {
+            let graph = slf.graph.clone_ref(slf.py());
+            let graph = graph.borrow(slf.py());
+            let page = graph.runtime.block_on(async {
+                graph
+                    .service
+                    .get_edges_page(slf.label.as_deref(), &slf.query)
+                    .await
+            })?;
+
+            slf.done = page.next.is_none();
+            slf.query.after = page.next;
+            slf.buffer.extend(page.items);
+        }
+
+        Ok(slf.buffer.pop_front())
+    }
+}
+
+// the whole category, or the subgraph of a vertex
+fn graph_tables(
+    graph: &PyGraph,
+    vertex_id: Option<&str>,
+    label: Option<&str>,
+    depth: Option<i32>,
+) -> PyResult<GraphTables> {
+    let res = match vertex_id {
+        Some(id) => {
+            let id = oid(id)?;
+            graph
+                .runtime
+                .block_on(async { graph.service.subgraph_tables(id, label, depth).await })?
+        }
+        None => graph
+            .runtime
+            .block_on(async { graph.service.to_tables().await })?,
+    };
+
+    Ok(res)
+}
+
+// an Arrow IPC file in memory, read by `polars.read_ipc`
+fn polars_frame<'py>(py: Python<'py>, table: &crate::Table) -> PyResult<Bound<'py, PyAny>> {
+    let mut buf = vec![];
+    table.write(TableFormat::Ipc, &mut buf)?;
+    let bytes = py
+        .import("io")?
+        .call_method1("BytesIO", (PyBytes::new(py, &buf),))?;
+
+    py.import("polars")?.call_method1("read_ipc", (bytes,))
+}
+
+// `after` is the `next` of the previous page
+fn page_query(limit: usize, after: Option<&str>, descending: bool) -> PyResult<PageQuery> {
+    let mut query = PageQuery::new(limit);
+    if let Some(id) = after {
+        query = query.after(oid(id)?);
+    }
+    if descending {
+        query = query.order(SortOrder::Descending);
+    }
+    Ok(query)
+}
+
+// explicit format name first, otherwise guess by file extension
+fn graph_format(path: &str, format: Option<&str>) -> Result<GraphFormat, Pyo3MongoError> {
+    match format {
+        Some(f) => GraphFormat::from_str(f),
+        None => GraphFormat::from_path(path).ok_or(Pyo3MongoError::Common("unknown graph format")),
+    }
+}
+
 #[pymethods]
 impl PyGraph {
     #[new]
@@ -173,9 +540,17 @@ impl PyGraph {
         Ok(PyGraph { service, runtime })
     }
 
-    #[pyo3(text_signature = "($self, v)")]
-    pub fn create_vertex(self_: PyRef<'_, Self>, v: String) -> PyResult<Vertex> {
-        let dto = VertexDto::new(&v);
+    #[pyo3(text_signature = "($self, v, properties)")]
+    #[pyo3(signature = (v, properties=None))]
+    pub fn create_vertex(
+        self_: PyRef<'_, Self>,
+        v: String,
+        properties: Option<PyProperties>,
+    ) -> PyResult<Vertex> {
+        let mut dto = VertexDto::new(&v);
+        if let Some(p) = properties {
+            dto = dto.with_properties(p.0);
+        }
         let res = self_
             .runtime
             .block_on(async { self_.service.create_vertex(dto).await })?;
@@ -193,6 +568,432 @@ impl PyGraph {
         Ok(res)
     }
 
+    #[pyo3(text_signature = "($self)")]
+    pub fn show_dbs(self_: PyRef<'_, Self>) -> PyResult<Vec<String>> {
+        let res = self_
+            .runtime
+            .block_on(async { self_.service.show_dbs().await })?;
+
+        Ok(res)
+    }
+
+    /// remove all vertexes & edges of the category
+    #[pyo3(text_signature = "($self)")]
+    pub fn truncate_all(self_: PyRef<'_, Self>) -> PyResult<()> {
+        self_
+            .runtime
+            .block_on(async { self_.service.truncate_all().await })?;
+
+        Ok(())
+    }
+
+    #[pyo3(text_signature = "($self, id)")]
+    pub fn get_vertex(self_: PyRef<'_, Self>, id: &str) -> PyResult<Vertex> {
+        let id = oid(id)?;
+        let res = self_
+            .runtime
+            .block_on(async { self_.service.get_vertex(id).await })?;
+
+        Ok(res)
+    }
+
+    #[pyo3(text_signature = "($self, ids)")]
+    pub fn get_vertexes(self_: PyRef<'_, Self>, ids: Vec<String>) -> PyResult<Vec<Vertex>> {
+        let ids = oids(&ids)?;
+        let res = self_
+            .runtime
+            .block_on(async { self_.service.get_vertexes(ids).await })?;
+
+        Ok(res)
+    }
+
+    #[pyo3(text_signature = "($self)")]
+    pub fn get_all_vertexes(self_: PyRef<'_, Self>) -> PyResult<Vec<Vertex>> {
+        let res = self_
+            .runtime
+            .block_on(async { self_.service.get_all_vertexes().await })?;
+
+        Ok(res)
+    }
+
+    /// a page of vertexes ordered by id, `after` is the `next` of the previous page
+    #[pyo3(text_signature = "($self, limit, after, descending)")]
+    #[pyo3(signature = (limit, after=None, descending=false))]
+    pub fn get_vertexes_page(
+        self_: PyRef<'_, Self>,
+        limit: usize,
+        after: Option<&str>,
+        descending: bool,
+    ) -> PyResult<VertexPage> {
+        let query = page_query(limit, after, descending)?;
+        let res = self_
+            .runtime
+            .block_on(async { self_.service.get_vertexes_page(&query).await })?;
+
+        Ok(res.into())
+    }
+
+    /// iterate over all vertexes, `batch_size` vertexes are loaded at once
+    #[pyo3(text_signature = "($self, batch_size)")]
+    #[pyo3(signature = (batch_size=1000))]
+    pub fn iter_vertexes(self_: PyRef<'_, Self>, batch_size: usize) -> PyResult<VertexIterator> {
+        Ok(VertexIterator {
+            query: page_query(batch_size, None, false)?,
+            graph: self_.into(),
+            buffer: VecDeque::new(),
+            done: false,
+        })
+    }
+
+    #[pyo3(text_signature = "($self, id, name, properties)")]
+    #[pyo3(signature = (id, name, properties=None))]
+    pub fn update_vertex(
+        self_: PyRef<'_, Self>,
+        id: &str,
+        name: String,
+        properties: Option<PyProperties>,
+    ) -> PyResult<Vertex> {
+        let id = oid(id)?;
+        let mut dto = VertexDto::new(&name);
+        if let Some(p) = properties {
+            dto = dto.with_properties(p.0);
+        }
+        let res = self_
+            .runtime
+            .block_on(async { self_.service.update_vertex(id, dto).await })?;
+
+        Ok(res)
+    }
+
+    /// delete a vertex and all the edges connected to it
+    #[pyo3(text_signature = "($self, id)")]
+    pub fn delete_vertex(self_: PyRef<'_, Self>, id: &str) -> PyResult<()> {
+        let id = oid(id)?;
+        self_
+            .runtime
+            .block_on(async { self_.service.delete_vertex(id).await })?;
+
+        Ok(())
+    }
+
+    #[pyo3(text_signature = "($self, id)")]
+    pub fn get_edge(self_: PyRef<'_, Self>, id: &str) -> PyResult<Edge> {
+        let id = oid(id)?;
+        let res = self_
+            .runtime
+            .block_on(async { self_.service.get_edge(id).await })?;
+
+        Ok(res)
+    }
+
+    #[pyo3(text_signature = "($self, ids)")]
+    pub fn get_edges(self_: PyRef<'_, Self>, ids: Vec<String>) -> PyResult<Vec<Edge>> {
+        let ids = oids(&ids)?;
+        let res = self_
+            .runtime
+            .block_on(async { self_.service.get_edges(ids).await })?;
+
+        Ok(res)
+    }
+
+    #[pyo3(text_signature = "($self)")]
+    pub fn get_all_edges(self_: PyRef<'_, Self>) -> PyResult<Vec<Edge>> {
+        let res = self_
+            .runtime
+            .block_on(async { self_.service.get_all_edges().await })?;
+
+        Ok(res)
+    }
+
+    /// a page of edges (of a label) ordered by id, see `get_vertexes_page`
+    #[pyo3(text_signature = "($self, limit, after, label, descending)")]
+    #[pyo3(signature = (limit, after=None, label=None, descending=false))]
+    pub fn get_edges_page(
+        self_: PyRef<'_, Self>,
+        limit: usize,
+        after: Option<&str>,
+        label: Option<&str>,
+        descending: bool,
+    ) -> PyResult<EdgePage> {
+        let query = page_query(limit, after, descending)?;
+        let res = self_
+            .runtime
+            .block_on(async { self_.service.get_edges_page(label, &query).await })?;
+
+        Ok(res.into())
+    }
+
+    /// iterate over all edges (of a label), `batch_size` edges are loaded at once
+    #[pyo3(text_signature = "($self, label, batch_size)")]
+    #[pyo3(signature = (label=None, batch_size=1000))]
+    pub fn iter_edges(
+        self_: PyRef<'_, Self>,
+        label: Option<String>,
+        batch_size: usize,
+    ) -> PyResult<EdgeIterator> {
+        Ok(EdgeIterator {
+            label,
+            query: page_query(batch_size, None, false)?,
+            graph: self_.into(),
+            buffer: VecDeque::new(),
+            done: false,
+        })
+    }
+
+    #[pyo3(text_signature = "($self, id, v)")]
+    pub fn update_edge(self_: PyRef<'_, Self>, id: &str, v: EdgeInput) -> PyResult<Edge> {
+        let id = oid(id)?;
+        let dto = EdgeDto::try_from(&v)?;
+        let res = self_
+            .runtime
+            .block_on(async { self_.service.update_edge(id, dto).await })?;
+
+        Ok(res)
+    }
+
+    #[pyo3(text_signature = "($self, id)")]
+    pub fn delete_edge(self_: PyRef<'_, Self>, id: &str) -> PyResult<()> {
+        let id = oid(id)?;
+        self_
+            .runtime
+            .block_on(async { self_.service.delete_edge(id).await })?;
+
+        Ok(())
+    }
+
+    #[pyo3(text_signature = "($self, ids)")]
+    pub fn delete_edges(self_: PyRef<'_, Self>, ids: Vec<String>) -> PyResult<()> {
+        let ids = oids(&ids)?;
+        self_
+            .runtime
+            .block_on(async { self_.service.delete_edges(ids).await })?;
+
+        Ok(())
+    }
+
+    /// `direction`: "out" (vertex as source), "in" (vertex as target) or "both"
+    #[pyo3(text_signature = "($self, vertex_id, direction)")]
+    #[pyo3(signature = (vertex_id, direction="both"))]
+    pub fn get_edges_by_vertex(
+        self_: PyRef<'_, Self>,
+        vertex_id: &str,
+        direction: &str,
+    ) -> PyResult<Vec<Edge>> {
+        let dto = FindEdgeByVertexDto::new(oid(vertex_id)?, Direction::from_str(direction)?);
+        let res = self_
+            .runtime
+            .block_on(async { self_.service.get_edges_by_vertex(dto).await })?;
+
+        Ok(res)
+    }
+
+    /// `filter` is a Mongo query on properties, e.g. `{"age": {"$gt": 3}}`
+    #[pyo3(text_signature = "($self, filter)")]
+    pub fn find_vertexes(self_: PyRef<'_, Self>, filter: PyProperties) -> PyResult<Vec<Vertex>> {
+        let filter = PropertyFilter::from_document(filter.0);
+        let res = self_
+            .runtime
+            .block_on(async { self_.service.find_vertexes(&filter).await })?;
+
+        Ok(res)
+    }
+
+    #[pyo3(text_signature = "($self, filter, label)")]
+    #[pyo3(signature = (filter, label=None))]
+    pub fn find_edges(
+        self_: PyRef<'_, Self>,
+        filter: PyProperties,
+        label: Option<&str>,
+    ) -> PyResult<Vec<Edge>> {
+        let filter = PropertyFilter::from_document(filter.0);
+        let res = self_
+            .runtime
+            .block_on(async { self_.service.find_edges(label, &filter).await })?;
+
+        Ok(res)
+    }
+
+    /// JSON schema of vertex properties, `None` removes the validation
+    #[pyo3(text_signature = "($self, schema)")]
+    pub fn set_vertex_schema(self_: PyRef<'_, Self>, schema: Option<PyProperties>) -> PyResult<()> {
+        let schema = schema.map(|s| s.0);
+        self_
+            .runtime
+            .block_on(async { self_.service.set_vertex_schema(schema).await })?;
+
+        Ok(())
+    }
+
+    /// JSON schema of edge properties, `None` removes the validation
+    #[pyo3(text_signature = "($self, schema)")]
+    pub fn set_edge_schema(self_: PyRef<'_, Self>, schema: Option<PyProperties>) -> PyResult<()> {
+        let schema = schema.map(|s| s.0);
+        self_
+            .runtime
+            .block_on(async { self_.service.set_edge_schema(schema).await })?;
+
+        Ok(())
+    }
+
+    /// create the indexes of the edge collection, returns the names of all its indexes.
+    /// `unique`: None, "endpoints" (source, target) or "endpoints_label" (source, target, label)
+    #[pyo3(text_signature = "($self, unique)")]
+    #[pyo3(signature = (unique=None))]
+    pub fn ensure_indexes(self_: PyRef<'_, Self>, unique: Option<&str>) -> PyResult<Vec<String>> {
+        let uniqueness = unique
+            .map(EdgeUniqueness::from_str)
+            .transpose()?
+            .unwrap_or_default();
+        let res = self_
+            .runtime
+            .block_on(async { self_.service.ensure_indexes(uniqueness).await })?;
+
+        Ok(res)
+    }
+
+    /// query plan of a traversal method starting from a vertex, `method` is
+    /// "edges_by_vertex", "delete_vertex", "graph_lookup" or "neighborhood" (a single hop).
+    /// `verbosity`: "queryPlanner", "executionStats" or "allPlansExecution"
+    #[pyo3(text_signature = "($self, method, vertex_id, direction, label, depth, verbosity)")]
+    #[pyo3(signature = (method, vertex_id, direction="both", label=None, depth=None, verbosity="queryPlanner"))]
+    pub fn explain(
+        self_: PyRef<'_, Self>,
+        method: &str,
+        vertex_id: &str,
+        direction: &str,
+        label: Option<&str>,
+        depth: Option<i32>,
+        verbosity: &str,
+    ) -> PyResult<PyProperties> {
+        let ids = [oid(vertex_id)?];
+        let direction = Direction::from_str(direction)?;
+        let traversal = match method {
+            "edges_by_vertex" => {
+                Traversal::EdgesByVertex(FindEdgeByVertexDto::new(ids[0], direction))
+            }
+            "delete_vertex" => Traversal::DeleteVertex(ids[0]),
+            "graph_lookup" => Traversal::GraphLookup {
+                vertex_id: ids[0],
+                label,
+                depth,
+            },
+            "neighborhood" => Traversal::AdjacentEdges {
+                vertexes: &ids,
+                direction,
+                label,
+            },
+            _ => return Err(PyValueError::new_err(format!("unknown method {method}"))),
+        };
+        let verbosity = ExplainVerbosity::from_str(verbosity)?;
+        let res = self_
+            .runtime
+            .block_on(async { self_.service.explain(traversal, verbosity).await })?;
+
+        Ok(PyProperties(res))
+    }
+
+    #[pyo3(text_signature = "($self, names)")]
+    pub fn create_vertexes(self_: PyRef<'_, Self>, names: Vec<String>) -> PyResult<Vec<Vertex>> {
+        let dtos = names.iter().map(|n| VertexDto::new(n)).collect();
+        let res = self_
+            .runtime
+            .block_on(async { self_.service.create_vertexes(dtos).await })?;
+
+        Ok(res)
+    }
+
+    #[pyo3(text_signature = "($self, edges)")]
+    pub fn create_edges(self_: PyRef<'_, Self>, edges: Vec<EdgeInput>) -> PyResult<Vec<Edge>> {
+        let dtos = edges
+            .iter()
+            .map(EdgeDto::try_from)
+            .collect::<Result<Vec<_>, _>>()?;
+        let res = self_
+            .runtime
+            .block_on(async { self_.service.create_edges(dtos).await })?;
+
+        Ok(res)
+    }
+
+    #[pyo3(text_signature = "($self, path, format)")]
+    #[pyo3(signature = (path, format=None))]
+    pub fn export_graph(
+        self_: PyRef<'_, Self>,
+        path: &str,
+        format: Option<&str>,
+    ) -> PyResult<BulkOutput> {
+        let format = graph_format(path, format)?;
+        let writer = BufWriter::new(File::create(path)?);
+        let res = self_
+            .runtime
+            .block_on(async { self_.service.export_graph(format, writer).await })?;
+
+        Ok(res.into())
+    }
+
+    #[pyo3(text_signature = "($self, path, format, batch_size)")]
+    #[pyo3(signature = (path, format=None, batch_size=10000))]
+    pub fn import_graph(
+        self_: PyRef<'_, Self>,
+        path: &str,
+        format: Option<&str>,
+        batch_size: usize,
+    ) -> PyResult<BulkOutput> {
+        let format = graph_format(path, format)?;
+        let reader = BufReader::new(File::open(path)?);
+        let res = self_
+            .runtime
+            .block_on(async { self_.service.import_graph(format, reader, batch_size).await })?;
+
+        Ok(res.into())
+    }
+
+    /// write the whole category, or the subgraph of `vertex_id` (see `get_graph`), as
+    /// two Arrow tables. `format`: "parquet" or "ipc", guessed by file extension if absent
+    #[pyo3(text_signature = "($self, vertex_path, edge_path, format, vertex_id, label, depth)")]
+    #[pyo3(signature = (vertex_path, edge_path, format=None, vertex_id=None, label=None, depth=None))]
+    pub fn export_tables(
+        self_: PyRef<'_, Self>,
+        vertex_path: &str,
+        edge_path: &str,
+        format: Option<&str>,
+        vertex_id: Option<&str>,
+        label: Option<&str>,
+        depth: Option<i32>,
+    ) -> PyResult<BulkOutput> {
+        let format = match format {
+            Some(f) => TableFormat::from_str(f)?,
+            None => TableFormat::from_path(vertex_path)
+                .ok_or(Pyo3MongoError::Common("unknown table format"))?,
+        };
+        let tables = graph_tables(&self_, vertex_id, label, depth)?;
+        let res = tables.write(
+            format,
+            BufWriter::new(File::create(vertex_path)?),
+            BufWriter::new(File::create(edge_path)?),
+        )?;
+
+        Ok(res.into())
+    }
+
+    /// same tables as `export_tables`, as Polars DataFrames `(vertexes, edges)`
+    #[pyo3(text_signature = "($self, vertex_id, label, depth)")]
+    #[pyo3(signature = (vertex_id=None, label=None, depth=None))]
+    pub fn to_polars<'py>(
+        self_: PyRef<'py, Self>,
+        vertex_id: Option<&str>,
+        label: Option<&str>,
+        depth: Option<i32>,
+    ) -> PyResult<(Bound<'py, PyAny>, Bound<'py, PyAny>)> {
+        let tables = graph_tables(&self_, vertex_id, label, depth)?;
+        let py = self_.py();
+
+        Ok((
+            polars_frame(py, &tables.vertexes)?,
+            polars_frame(py, &tables.edges)?,
+        ))
+    }
+
     #[pyo3(text_signature = "($self, vertex_id, label, depth)")]
     #[pyo3(signature = (vertex_id, label, depth))]
     pub fn get_graph(
@@ -213,6 +1014,161 @@ impl PyGraph {
 
         Ok(res)
     }
+
+    #[pyo3(text_signature = "($self, source, target, label, max_depth, weighted)")]
+    #[pyo3(signature = (source, target, label=None, max_depth=None, weighted=false))]
+    pub fn shortest_path(
+        self_: PyRef<'_, Self>,
+        source: String,
+        target: String,
+        label: Option<&str>,
+        max_depth: Option<u32>,
+        weighted: bool,
+    ) -> PyResult<Option<GraphPath>> {
+        let res = self_.runtime.block_on(async {
+            let from = ObjectId::from_str(&source)?;
+            let to = ObjectId::from_str(&target)?;
+            if weighted {
+                self_
+                    .service
+                    .weighted_shortest_path(from, to, label, max_depth)
+                    .await
+            } else {
+                self_
+                    .service
+                    .shortest_path(from, to, label, max_depth)
+                    .await
+            }
+        })?;
+
+        Ok(res)
+    }
+
+    #[pyo3(text_signature = "($self, vertex_id, hops, direction, label)")]
+    #[pyo3(signature = (vertex_id, hops, direction="out", label=None))]
+    pub fn neighborhood(
+        self_: PyRef<'_, Self>,
+        vertex_id: String,
+        hops: u32,
+        direction: &str,
+        label: Option<&str>,
+    ) -> PyResult<Neighborhood> {
+        let res = self_.runtime.block_on(async {
+            let oid = ObjectId::from_str(&vertex_id)?;
+            let direction = Direction::from_str(direction)?;
+            self_
+                .service
+                .neighborhood(oid, hops, direction, label)
+                .await
+        })?;
+
+        Ok(res)
+    }
+
+    /// vertex ids of each component, the largest first.
+    /// The component index is stored as `write_property` of each vertex if given.
+    #[pyo3(text_signature = "($self, strongly, write_property)")]
+    #[pyo3(signature = (strongly=false, write_property=None))]
+    pub fn connected_components(
+        self_: PyRef<'_, Self>,
+        strongly: bool,
+        write_property: Option<&str>,
+    ) -> PyResult<Vec<Vec<String>>> {
+        let res = self_.runtime.block_on(async {
+            let components = if strongly {
+                self_.service.strongly_connected_components().await?
+            } else {
+                self_.service.connected_components().await?
+            };
+            if let Some(key) = write_property {
+                self_.service.write_vertex_values(key, &components).await?;
+            }
+            Ok::<_, Pyo3MongoError>(components)
+        })?;
+
+        Ok(res
+            .components
+            .iter()
+            .map(|c| c.iter().map(|id| id.to_hex()).collect())
+            .collect())
+    }
+
+    /// edges of a directed cycle, `None` if the graph is acyclic
+    #[pyo3(text_signature = "($self)")]
+    pub fn find_cycle(self_: PyRef<'_, Self>) -> PyResult<Option<Vec<Edge>>> {
+        let res = self_.runtime.block_on(self_.service.find_cycle())?;
+        Ok(res)
+    }
+
+    /// vertex ids where every edge goes forward, `P3mError` if the graph has a cycle
+    #[pyo3(text_signature = "($self, write_property)")]
+    #[pyo3(signature = (write_property=None))]
+    pub fn topological_sort(
+        self_: PyRef<'_, Self>,
+        write_property: Option<&str>,
+    ) -> PyResult<Vec<String>> {
+        let res = self_.runtime.block_on(async {
+            let order = self_.service.topological_sort().await?;
+            if let Some(key) = write_property {
+                self_.service.write_vertex_values(key, &order).await?;
+            }
+            Ok::<_, Pyo3MongoError>(order)
+        })?;
+
+        Ok(res.order.iter().map(|id| id.to_hex()).collect())
+    }
+
+    /// vertex id -> score
+    #[pyo3(text_signature = "($self, damping, max_iterations, tolerance, write_property)")]
+    #[pyo3(signature = (damping=0.85, max_iterations=100, tolerance=1e-6, write_property=None))]
+    pub fn pagerank(
+        self_: PyRef<'_, Self>,
+        damping: f64,
+        max_iterations: usize,
+        tolerance: f64,
+        write_property: Option<&str>,
+    ) -> PyResult<HashMap<String, f64>> {
+        let options = PageRankOptions {
+            damping,
+            max_iterations,
+            tolerance,
+        };
+        let res = self_.runtime.block_on(async {
+            let rank = self_.service.pagerank(options).await?;
+            if let Some(key) = write_property {
+                self_.service.write_vertex_values(key, &rank).await?;
+            }
+            Ok::<_, Pyo3MongoError>(rank)
+        })?;
+
+        Ok(res
+            .scores
+            .into_iter()
+            .map(|(id, s)| (id.to_hex(), s))
+            .collect())
+    }
+
+    /// vertex id -> degrees, the centrality is stored as `write_property` if given
+    #[pyo3(text_signature = "($self, write_property)")]
+    #[pyo3(signature = (write_property=None))]
+    pub fn degree_centrality(
+        self_: PyRef<'_, Self>,
+        write_property: Option<&str>,
+    ) -> PyResult<HashMap<String, DegreeOutput>> {
+        let res = self_.runtime.block_on(async {
+            let centrality = self_.service.degree_centrality().await?;
+            if let Some(key) = write_property {
+                self_.service.write_vertex_values(key, &centrality).await?;
+            }
+            Ok::<_, Pyo3MongoError>(centrality)
+        })?;
+
+        Ok(res
+            .degrees
+            .into_iter()
+            .map(|(id, d)| (id.to_hex(), d.into()))
+            .collect())
+    }
 }
 
 #[pymodule]
@@ -221,6 +1177,28 @@ fn p3m(m: &Bound<'_, PyModule>) -> PyResult<()> {
     m.add_class::<Edge>()?;
     m.add_class::<EdgeInput>()?;
     m.add_class::<GraphOutput>()?;
+    m.add_class::<BulkOutput>()?;
+    m.add_class::<DegreeOutput>()?;
+    m.add_class::<VertexPage>()?;
+    m.add_class::<EdgePage>()?;
+    m.add_class::<VertexIterator>()?;
+    m.add_class::<EdgeIterator>()?;
+    m.add_class::<GraphPath>()?;
+    m.add_class::<HopVertex>()?;
+    m.add_class::<HopEdge>()?;
+    m.add_class::<Neighborhood>()?;
     m.add_class::<PyGraph>()?;
+    m.add_class::<AsyncPyGraph>()?;
+    m.add_class::<EventStream>()?;
+    m.add_class::<EventOutput>()?;
+
+    let py = m.py();
+    m.add("P3mError", py.get_type::<P3mError>())?;
+    m.add("VertexNotFoundError", py.get_type::<VertexNotFoundError>())?;
+    m.add("EdgeNotFoundError", py.get_type::<EdgeNotFoundError>())?;
+    m.add("InvalidIdError", py.get_type::<InvalidIdError>())?;
+    m.add("TransactionError", py.get_type::<TransactionError>())?;
+    m.add("MongoError", py.get_type::<MongoError>())?;
+    m.add("FormatError", py.get_type::<FormatError>())?;
     Ok(())
 }
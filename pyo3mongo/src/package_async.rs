@@ -0,0 +1,419 @@
+//! Pyo3 Async
+//!
+//! `AsyncPyGraph` mirrors `PyGraph`, but every method returns an awaitable instead of
+//! blocking on a runtime, so that the Python event loop keeps running:
+//!
+//! ```python
+//! g = await p3m.AsyncPyGraph.connect(uri, "graph", "dev")
+//! v = await g.create_vertex("node-1")
+//!
+//! async for e in await g.watch():
+//!     print(e.entity, e.operation, e.id, e.resume_token)
+//! ```
+
+use std::str::FromStr;
+use std::sync::{Arc, Mutex as StdMutex};
+
+use pyo3::exceptions::PyStopAsyncIteration;
+use pyo3::prelude::*;
+use pyo3_async_runtimes::tokio::future_into_py;
+use tokio::sync::Mutex;
+use tokio_stream::StreamExt;
+
+use crate::package::{oid, oids, EdgeInput, GraphOutput, PyProperties};
+use crate::{
+    Direction, Edge, EdgeDto, FindEdgeByVertexDto, GraphEvent, GraphEventStream, GraphService,
+    Operation, PropertyFilter, Pyo3MongoResult, ResumeToken, Vertex, VertexDto,
+};
+
+#[pyclass]
+pub struct AsyncPyGraph {
+    // shared by all the pending futures
+    service: Arc<GraphService>,
+}
+
+#[pymethods]
+impl AsyncPyGraph {
+    #[staticmethod]
+    #[pyo3(text_signature = "(uri, database, category)")]
+    pub fn connect(
+        py: Python<'_>,
+        uri: String,
+        database: String,
+        category: String,
+    ) -> PyResult<Bound<'_, PyAny>> {
+        future_into_py(py, async move {
+            let service = GraphService::new(&uri, &database, &category).await?;
+            Ok(AsyncPyGraph {
+                service: Arc::new(service),
+            })
+        })
+    }
+
+    #[pyo3(text_signature = "($self, v, properties)")]
+    #[pyo3(signature = (v, properties=None))]
+    pub fn create_vertex<'py>(
+        &self,
+        py: Python<'py>,
+        v: String,
+        properties: Option<PyProperties>,
+    ) -> PyResult<Bound<'py, PyAny>> {
+        let service = self.service.clone();
+        future_into_py(py, async move {
+            let mut dto = VertexDto::new(&v);
+            if let Some(p) = properties {
+                dto = dto.with_properties(p.0);
+            }
+            Ok(service.create_vertex(dto).await?)
+        })
+    }
+
+    #[pyo3(text_signature = "($self, names)")]
+    pub fn create_vertexes<'py>(
+        &self,
+        py: Python<'py>,
+        names: Vec<String>,
+    ) -> PyResult<Bound<'py, PyAny>> {
+        let service = self.service.clone();
+        future_into_py(py, async move {
+            let dtos = names.iter().map(|n| VertexDto::new(n)).collect();
+            Ok(service.create_vertexes(dtos).await?)
+        })
+    }
+
+    #[pyo3(text_signature = "($self, id)")]
+    pub fn get_vertex<'py>(&self, py: Python<'py>, id: &str) -> PyResult<Bound<'py, PyAny>> {
+        let (service, id) = (self.service.clone(), oid(id)?);
+        future_into_py(py, async move { Ok(service.get_vertex(id).await?) })
+    }
+
+    #[pyo3(text_signature = "($self, ids)")]
+    pub fn get_vertexes<'py>(
+        &self,
+        py: Python<'py>,
+        ids: Vec<String>,
+    ) -> PyResult<Bound<'py, PyAny>> {
+        let (service, ids) = (self.service.clone(), oids(&ids)?);
+        future_into_py(py, async move { Ok(service.get_vertexes(ids).await?) })
+    }
+
+    #[pyo3(text_signature = "($self)")]
+    pub fn get_all_vertexes<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyAny>> {
+        let service = self.service.clone();
+        future_into_py(py, async move { Ok(service.get_all_vertexes().await?) })
+    }
+
+    #[pyo3(text_signature = "($self, id, name, properties)")]
+    #[pyo3(signature = (id, name, properties=None))]
+    pub fn update_vertex<'py>(
+        &self,
+        py: Python<'py>,
+        id: &str,
+        name: String,
+        properties: Option<PyProperties>,
+    ) -> PyResult<Bound<'py, PyAny>> {
+        let (service, id) = (self.service.clone(), oid(id)?);
+        future_into_py(py, async move {
+            let mut dto = VertexDto::new(&name);
+            if let Some(p) = properties {
+                dto = dto.with_properties(p.0);
+            }
+            Ok(service.update_vertex(id, dto).await?)
+        })
+    }
+
+    #[pyo3(text_signature = "($self, id)")]
+    pub fn delete_vertex<'py>(&self, py: Python<'py>, id: &str) -> PyResult<Bound<'py, PyAny>> {
+        let (service, id) = (self.service.clone(), oid(id)?);
+        future_into_py(py, async move { Ok(service.delete_vertex(id).await?) })
+    }
+
+    #[pyo3(text_signature = "($self, filter)")]
+    pub fn find_vertexes<'py>(
+        &self,
+        py: Python<'py>,
+        filter: PyProperties,
+    ) -> PyResult<Bound<'py, PyAny>> {
+        let service = self.service.clone();
+        let filter = PropertyFilter::from_document(filter.0);
+        future_into_py(py, async move { Ok(service.find_vertexes(&filter).await?) })
+    }
+
+    #[pyo3(text_signature = "($self, v)")]
+    pub fn create_edge<'py>(&self, py: Python<'py>, v: EdgeInput) -> PyResult<Bound<'py, PyAny>> {
+        let service = self.service.clone();
+        future_into_py(py, async move {
+            let dto = EdgeDto::try_from(&v)?;
+            Ok(service.create_edge(dto).await?)
+        })
+    }
+
+    #[pyo3(text_signature = "($self, edges)")]
+    pub fn create_edges<'py>(
+        &self,
+        py: Python<'py>,
+        edges: Vec<EdgeInput>,
+    ) -> PyResult<Bound<'py, PyAny>> {
+        let service = self.service.clone();
+        future_into_py(py, async move {
+            let dtos = edges
+                .iter()
+                .map(EdgeDto::try_from)
+                .collect::<Result<Vec<_>, _>>()?;
+            Ok(service.create_edges(dtos).await?)
+        })
+    }
+
+    #[pyo3(text_signature = "($self, id)")]
+    pub fn get_edge<'py>(&self, py: Python<'py>, id: &str) -> PyResult<Bound<'py, PyAny>> {
+        let (service, id) = (self.service.clone(), oid(id)?);
+        future_into_py(py, async move { Ok(service.get_edge(id).await?) })
+    }
+
+    #[pyo3(text_signature = "($self, ids)")]
+    pub fn get_edges<'py>(&self, py: Python<'py>, ids: Vec<String>) -> PyResult<Bound<'py, PyAny>> {
+        let (service, ids) = (self.service.clone(), oids(&ids)?);
+        future_into_py(py, async move { Ok(service.get_edges(ids).await?) })
+    }
+
+    #[pyo3(text_signature = "($self)")]
+    pub fn get_all_edges<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyAny>> {
+        let service = self.service.clone();
+        future_into_py(py, async move { Ok(service.get_all_edges().await?) })
+    }
+
+    #[pyo3(text_signature = "($self, id, v)")]
+    pub fn update_edge<'py>(
+        &self,
+        py: Python<'py>,
+        id: &str,
+        v: EdgeInput,
+    ) -> PyResult<Bound<'py, PyAny>> {
+        let (service, id) = (self.service.clone(), oid(id)?);
+        future_into_py(py, async move {
+            let dto = EdgeDto::try_from(&v)?;
+            Ok(service.update_edge(id, dto).await?)
+        })
+    }
+
+    #[pyo3(text_signature = "($self, id)")]
+    pub fn delete_edge<'py>(&self, py: Python<'py>, id: &str) -> PyResult<Bound<'py, PyAny>> {
+        let (service, id) = (self.service.clone(), oid(id)?);
+        future_into_py(py, async move { Ok(service.delete_edge(id).await?) })
+    }
+
+    #[pyo3(text_signature = "($self, ids)")]
+    pub fn delete_edges<'py>(
+        &self,
+        py: Python<'py>,
+        ids: Vec<String>,
+    ) -> PyResult<Bound<'py, PyAny>> {
+        let (service, ids) = (self.service.clone(), oids(&ids)?);
+        future_into_py(py, async move { Ok(service.delete_edges(ids).await?) })
+    }
+
+    #[pyo3(text_signature = "($self, filter, label)")]
+    #[pyo3(signature = (filter, label=None))]
+    pub fn find_edges<'py>(
+        &self,
+        py: Python<'py>,
+        filter: PyProperties,
+        label: Option<String>,
+    ) -> PyResult<Bound<'py, PyAny>> {
+        let service = self.service.clone();
+        let filter = PropertyFilter::from_document(filter.0);
+        future_into_py(py, async move {
+            Ok(service.find_edges(label.as_deref(), &filter).await?)
+        })
+    }
+
+    /// `direction`: "out" (vertex as source), "in" (vertex as target) or "both"
+    #[pyo3(text_signature = "($self, vertex_id, direction)")]
+    #[pyo3(signature = (vertex_id, direction="both"))]
+    pub fn get_edges_by_vertex<'py>(
+        &self,
+        py: Python<'py>,
+        vertex_id: &str,
+        direction: &str,
+    ) -> PyResult<Bound<'py, PyAny>> {
+        let service = self.service.clone();
+        let dto = FindEdgeByVertexDto::new(oid(vertex_id)?, Direction::from_str(direction)?);
+        future_into_py(
+            py,
+            async move { Ok(service.get_edges_by_vertex(dto).await?) },
+        )
+    }
+
+    #[pyo3(text_signature = "($self, vertex_id, label, depth)")]
+    #[pyo3(signature = (vertex_id, label, depth))]
+    pub fn get_graph<'py>(
+        &self,
+        py: Python<'py>,
+        vertex_id: &str,
+        label: Option<String>,
+        depth: Option<i32>,
+    ) -> PyResult<Bound<'py, PyAny>> {
+        let (service, id) = (self.service.clone(), oid(vertex_id)?);
+        future_into_py(py, async move {
+            let (edges, vertexes) = service
+                .get_graph_from_vertex_by_label(id, label.as_deref(), depth)
+                .await?;
+            Ok(GraphOutput { vertexes, edges })
+        })
+    }
+
+    #[pyo3(text_signature = "($self, source, target, label, max_depth, weighted)")]
+    #[pyo3(signature = (source, target, label=None, max_depth=None, weighted=false))]
+    pub fn shortest_path<'py>(
+        &self,
+        py: Python<'py>,
+        source: &str,
+        target: &str,
+        label: Option<String>,
+        max_depth: Option<u32>,
+        weighted: bool,
+    ) -> PyResult<Bound<'py, PyAny>> {
+        let service = self.service.clone();
+        let (from, to) = (oid(source)?, oid(target)?);
+        future_into_py(py, async move {
+            let label = label.as_deref();
+            let res = if weighted {
+                service
+                    .weighted_shortest_path(from, to, label, max_depth)
+                    .await?
+            } else {
+                service.shortest_path(from, to, label, max_depth).await?
+            };
+            Ok(res)
+        })
+    }
+
+    #[pyo3(text_signature = "($self, vertex_id, hops, direction, label)")]
+    #[pyo3(signature = (vertex_id, hops, direction="out", label=None))]
+    pub fn neighborhood<'py>(
+        &self,
+        py: Python<'py>,
+        vertex_id: &str,
+        hops: u32,
+        direction: &str,
+        label: Option<String>,
+    ) -> PyResult<Bound<'py, PyAny>> {
+        let service = self.service.clone();
+        let (id, direction) = (oid(vertex_id)?, Direction::from_str(direction)?);
+        future_into_py(py, async move {
+            Ok(service
+                .neighborhood(id, hops, direction, label.as_deref())
+                .await?)
+        })
+    }
+
+    /// resolves to an `EventStream` of the category, restarted right after the event of
+    /// `resume_after` if given
+    #[pyo3(text_signature = "($self, resume_after)")]
+    #[pyo3(signature = (resume_after=None))]
+    pub fn watch<'py>(
+        &self,
+        py: Python<'py>,
+        resume_after: Option<String>,
+    ) -> PyResult<Bound<'py, PyAny>> {
+        let service = self.service.clone();
+        let resume_after = resume_after.as_deref().map(parse_token).transpose()?;
+        future_into_py(py, async move {
+            let stream = service.watch(resume_after).await?;
+            Ok(EventStream {
+                token: Arc::new(StdMutex::new(stream.resume_token())),
+                inner: Arc::new(Mutex::new(stream)),
+            })
+        })
+    }
+}
+
+/// resume tokens are handed to Python as JSON strings
+fn parse_token(token: &str) -> Pyo3MongoResult<ResumeToken> {
+    Ok(serde_json::from_str(token)?)
+}
+
+fn format_token(token: &ResumeToken) -> Pyo3MongoResult<String> {
+    Ok(serde_json::to_string(token)?)
+}
+
+/// async iterator of `EventOutput`, see `AsyncPyGraph.watch`
+#[pyclass]
+pub struct EventStream {
+    inner: Arc<Mutex<GraphEventStream>>,
+    // token of the last event, readable while `__anext__` is pending
+    token: Arc<StdMutex<Option<ResumeToken>>>,
+}
+
+#[pymethods]
+impl EventStream {
+    fn __aiter__(slf: PyRef<'_, Self>) -> PyRef<'_, Self> {
+        slf
+    }
+
+    fn __anext__<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyAny>> {
+        let (inner, token) = (self.inner.clone(), self.token.clone());
+        future_into_py(py, async move {
+            let mut stream = inner.lock().await;
+            let event = match stream.next().await {
+                Some(event) => event?,
+                None => return Err(PyStopAsyncIteration::new_err(())),
+            };
+            let resume_token = stream.resume_token();
+            *token.lock().unwrap_or_else(|e| e.into_inner()) = resume_token.clone();
+
+            let mut output = EventOutput::from(event);
+            output.resume_token = resume_token.as_ref().map(format_token).transpose()?;
+            Ok(output)
+        })
+    }
+
+    /// token of the last event read, `None` if nothing has been read yet
+    #[getter]
+    fn resume_token(&self) -> PyResult<Option<String>> {
+        let token = self.token.lock().unwrap_or_else(|e| e.into_inner());
+        Ok(token.as_ref().map(format_token).transpose()?)
+    }
+}
+
+/// a `GraphEvent`, `entity` is "vertex" or "edge" and `operation` is "created",
+/// "updated" or "deleted"
+#[pyclass]
+pub struct EventOutput {
+    #[pyo3(get)]
+    pub entity: &'static str,
+    #[pyo3(get)]
+    pub operation: &'static str,
+    #[pyo3(get)]
+    pub id: String,
+    #[pyo3(get)]
+    pub vertex: Option<Vertex>,
+    #[pyo3(get)]
+    pub edge: Option<Edge>,
+    /// to be given to `AsyncPyGraph.watch` in order to restart right after this event
+    #[pyo3(get)]
+    pub resume_token: Option<String>,
+}
+
+impl From<GraphEvent> for EventOutput {
+    fn from(source: GraphEvent) -> Self {
+        let operation = match source.operation() {
+            Operation::Created => "created",
+            Operation::Updated => "updated",
+            Operation::Deleted => "deleted",
+        };
+        let id = source.id().to_hex();
+        let (entity, vertex, edge) = match source {
+            GraphEvent::Vertex { vertex, .. } => ("vertex", vertex, None),
+            GraphEvent::Edge { edge, .. } => ("edge", None, edge),
+        };
+        EventOutput {
+            entity,
+            operation,
+            id,
+            vertex,
+            edge,
+            resume_token: None,
+        }
+    }
+}
@@ -0,0 +1,531 @@
+//! Portable
+//!
+//! Export & import a whole category (`${cat}_vertex` & `${cat}_edge`) to & from a file.
+//!
+//! Supported formats:
+//! 1. JSON Lines: one record per line, tagged by `"type": "vertex"` or `"type": "edge"`
+//! 1. GraphML: the XML based format understood by most graph tools (Gephi, networkx...)
+//!
+//! Properties are written as relaxed extended JSON, a JSON object in JSON Lines and a
+//! JSON string in GraphML.
+//!
+//! Vertexes are always written before edges, and readers expect the same order, so that
+//! an edge can always find its endpoints among the vertexes that have been read.
+
+use std::collections::HashMap;
+use std::io::{BufRead, Lines, Write};
+use std::path::Path;
+use std::str::FromStr;
+
+use mongodb::bson::{Bson, Document};
+use quick_xml::events::{BytesDecl, BytesEnd, BytesStart, BytesText, Event};
+use quick_xml::{Reader, Writer};
+use serde::{Deserialize, Serialize};
+
+use super::model::{Edge, Vertex};
+use super::{Pyo3MongoError, Pyo3MongoResult};
+
+const GRAPHML_NS: &str = "http://graphml.graphdrawing.org/xmlns";
+
+// (id, for, attr.type) of GraphML keys, `attr.name` is the same as `id`
+const GRAPHML_KEYS: [(&str, &str, &str); 4] = [
+    ("name", "node", "string"),
+    ("weight", "edge", "double"),
+    ("label", "edge", "string"),
+    ("properties", "all", "string"),
+];
+
+/// file format of an exported graph
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GraphFormat {
+    JsonLines,
+    GraphML,
+}
+
+impl GraphFormat {
+    /// guess the format by file extension
+    pub fn from_path<P: AsRef<Path>>(path: P) -> Option<Self> {
+        let ext = path.as_ref().extension()?.to_str()?;
+        ext.parse().ok()
+    }
+}
+
+impl FromStr for GraphFormat {
+    type Err = Pyo3MongoError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "jsonl" | "ndjson" | "json-lines" => Ok(GraphFormat::JsonLines),
+            "graphml" | "xml" => Ok(GraphFormat::GraphML),
+            _ => Err(Pyo3MongoError::Common("unknown graph format")),
+        }
+    }
+}
+
+/// a vertex or an edge, detached from MongoDB
+///
+/// ids are plain strings here, since files from other tools do not use `ObjectId`
+#[derive(Serialize, Deserialize, PartialEq, Clone, Debug)]
+#[serde(tag = "type", rename_all = "lowercase")]
+pub enum GraphRecord {
+    Vertex {
+        id: String,
+        name: String,
+        #[serde(default, skip_serializing_if = "Document::is_empty", with = "ext_json")]
+        properties: Document,
+    },
+    Edge {
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        id: Option<String>,
+        source: String,
+        target: String,
+        weight: Option<f64>,
+        label: Option<String>,
+        #[serde(default, skip_serializing_if = "Document::is_empty", with = "ext_json")]
+        properties: Document,
+    },
+}
+
+// (de)serialize a document as relaxed extended JSON, so that BSON types such as
+// `ObjectId` or `DateTime` survive the round trip
+mod ext_json {
+    use mongodb::bson::{Bson, Document};
+    use serde::de::Error;
+    use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+    pub fn serialize<S: Serializer>(doc: &Document, s: S) -> Result<S::Ok, S::Error> {
+        Bson::Document(doc.clone())
+            .into_relaxed_extjson()
+            .serialize(s)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(d: D) -> Result<Document, D::Error> {
+        let map = serde_json::Map::deserialize(d)?;
+        Document::try_from(map).map_err(D::Error::custom)
+    }
+}
+
+fn properties_to_string(properties: &Document) -> String {
+    Bson::Document(properties.clone())
+        .into_relaxed_extjson()
+        .to_string()
+}
+
+fn properties_from_str(s: &str) -> Pyo3MongoResult<Document> {
+    let map = serde_json::from_str::<serde_json::Map<_, _>>(s)?;
+    Document::try_from(map).map_err(|_| Pyo3MongoError::Common("invalid extended JSON properties"))
+}
+
+impl From<&Vertex> for GraphRecord {
+    fn from(source: &Vertex) -> Self {
+        GraphRecord::Vertex {
+            id: source.id.map(|id| id.to_hex()).unwrap_or_default(),
+            name: source.name.clone(),
+            properties: source.properties.clone(),
+        }
+    }
+}
+
+impl From<&Edge> for GraphRecord {
+    fn from(source: &Edge) -> Self {
+        GraphRecord::Edge {
+            id: source.id.map(|id| id.to_hex()),
+            source: source.source.to_hex(),
+            target: source.target.to_hex(),
+            weight: source.weight,
+            label: source.label.clone(),
+            properties: source.properties.clone(),
+        }
+    }
+}
+
+/// number of vertexes and edges processed by a bulk operation
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct BulkSummary {
+    pub vertexes: usize,
+    pub edges: usize,
+}
+
+/// write vertexes & edges into a file, record by record
+pub enum GraphWriter<W: Write> {
+    JsonLines(W),
+    GraphML(Writer<W>),
+}
+
+impl<W: Write> GraphWriter<W> {
+    /// `graph_id` is only used by GraphML, as the id of `<graph>` element
+    pub fn new(format: GraphFormat, inner: W, graph_id: &str) -> Pyo3MongoResult<Self> {
+        match format {
+            GraphFormat::JsonLines => Ok(GraphWriter::JsonLines(inner)),
+            GraphFormat::GraphML => {
+                let mut w = Writer::new_with_indent(inner, b' ', 2);
+                w.write_event(Event::Decl(BytesDecl::new("1.0", Some("UTF-8"), None)))?;
+                w.write_event(Event::Start(
+                    BytesStart::new("graphml").with_attributes([("xmlns", GRAPHML_NS)]),
+                ))?;
+                for (id, domain, ty) in GRAPHML_KEYS {
+                    w.create_element("key")
+                        .with_attributes([
+                            ("id", id),
+                            ("for", domain),
+                            ("attr.name", id),
+                            ("attr.type", ty),
+                        ])
+                        .write_empty()?;
+                }
+                w.write_event(Event::Start(
+                    BytesStart::new("graph")
+                        .with_attributes([("id", graph_id), ("edgedefault", "directed")]),
+                ))?;
+
+                Ok(GraphWriter::GraphML(w))
+            }
+        }
+    }
+
+    pub fn write_vertex(&mut self, vertex: &Vertex) -> Pyo3MongoResult<()> {
+        self.write_record(&GraphRecord::from(vertex))
+    }
+
+    pub fn write_edge(&mut self, edge: &Edge) -> Pyo3MongoResult<()> {
+        self.write_record(&GraphRecord::from(edge))
+    }
+
+    pub fn write_record(&mut self, record: &GraphRecord) -> Pyo3MongoResult<()> {
+        match self {
+            GraphWriter::JsonLines(w) => {
+                serde_json::to_writer(&mut *w, record)?;
+                w.write_all(b"\n")?;
+            }
+            GraphWriter::GraphML(w) => match record {
+                GraphRecord::Vertex {
+                    id,
+                    name,
+                    properties,
+                } => {
+                    w.create_element("node")
+                        .with_attribute(("id", id.as_str()))
+                        .write_inner_content(|w| {
+                            write_data(w, "name", name)?;
+                            if !properties.is_empty() {
+                                write_data(w, "properties", &properties_to_string(properties))?;
+                            }
+                            Ok::<_, quick_xml::Error>(())
+                        })?;
+                }
+                GraphRecord::Edge {
+                    id,
+                    source,
+                    target,
+                    weight,
+                    label,
+                    properties,
+                } => {
+                    let mut start = BytesStart::new("edge");
+                    if let Some(id) = id {
+                        start.push_attribute(("id", id.as_str()));
+                    }
+                    start.push_attribute(("source", source.as_str()));
+                    start.push_attribute(("target", target.as_str()));
+
+                    w.write_event(Event::Start(start))?;
+                    if let Some(weight) = weight {
+                        write_data(w, "weight", &weight.to_string())?;
+                    }
+                    if let Some(label) = label {
+                        write_data(w, "label", label)?;
+                    }
+                    if !properties.is_empty() {
+                        write_data(w, "properties", &properties_to_string(properties))?;
+                    }
+                    w.write_event(Event::End(BytesEnd::new("edge")))?;
+                }
+            },
+        }
+
+        Ok(())
+    }
+
+    /// close all the opening tags (GraphML) and flush
+    pub fn finish(self) -> Pyo3MongoResult<W> {
+        let mut inner = match self {
+            GraphWriter::JsonLines(w) => w,
+            GraphWriter::GraphML(mut w) => {
+                w.write_event(Event::End(BytesEnd::new("graph")))?;
+                w.write_event(Event::End(BytesEnd::new("graphml")))?;
+                w.into_inner()
+            }
+        };
+        inner.flush()?;
+
+        Ok(inner)
+    }
+}
+
+fn write_data<W: Write>(w: &mut Writer<W>, key: &str, value: &str) -> quick_xml::Result<()> {
+    w.create_element("data")
+        .with_attribute(("key", key))
+        .write_text_content(BytesText::new(value))?;
+    Ok(())
+}
+
+/// read vertexes & edges from a file, record by record
+pub enum GraphReader<R: BufRead> {
+    JsonLines(Lines<R>),
+    GraphML(GraphMLReader<R>),
+}
+
+impl<R: BufRead> GraphReader<R> {
+    pub fn new(format: GraphFormat, inner: R) -> Self {
+        match format {
+            GraphFormat::JsonLines => GraphReader::JsonLines(inner.lines()),
+            GraphFormat::GraphML => GraphReader::GraphML(GraphMLReader::new(inner)),
+        }
+    }
+}
+
+impl<R: BufRead> Iterator for GraphReader<R> {
+    type Item = Pyo3MongoResult<GraphRecord>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        match self {
+            GraphReader::JsonLines(lines) => loop {
+                let line = match lines.next()? {
+                    Ok(l) => l,
+                    Err(e) => return Some(Err(e.into())),
+                };
+                // skip blank lines, e.g. the trailing one
+                if !line.trim().is_empty() {
+                    return Some(serde_json::from_str(&line).map_err(Pyo3MongoError::from));
+                }
+            },
+            GraphReader::GraphML(r) => r.read_record().transpose(),
+        }
+    }
+}
+
+/// a pull parser of GraphML, only `<key>`, `<node>`, `<edge>` and `<data>` are recognized
+pub struct GraphMLReader<R: BufRead> {
+    reader: Reader<R>,
+    buf: Vec<u8>,
+    // key id -> attr.name
+    keys: HashMap<String, String>,
+}
+
+impl<R: BufRead> GraphMLReader<R> {
+    pub fn new(inner: R) -> Self {
+        let mut reader = Reader::from_reader(inner);
+        reader.trim_text(true);
+
+        GraphMLReader {
+            reader,
+            buf: Vec::new(),
+            keys: HashMap::new(),
+        }
+    }
+
+    fn read_record(&mut self) -> Pyo3MongoResult<Option<GraphRecord>> {
+        loop {
+            self.buf.clear();
+            let (start, empty) = match self.reader.read_event_into(&mut self.buf)? {
+                Event::Start(e) => (e.into_owned(), false),
+                Event::Empty(e) => (e.into_owned(), true),
+                Event::Eof => return Ok(None),
+                _ => continue,
+            };
+
+            let mut attrs = attributes(&start)?;
+            let data = match start.local_name().as_ref() {
+                b"key" => {
+                    if let Some(id) = attrs.remove("id") {
+                        let name = attrs.remove("attr.name").unwrap_or_else(|| id.clone());
+                        self.keys.insert(id, name);
+                    }
+                    continue;
+                }
+                b"node" | b"edge" if empty => HashMap::new(),
+                b"node" | b"edge" => self.read_data(start.local_name().as_ref())?,
+                _ => continue,
+            };
+
+            let properties = match data.get("properties") {
+                Some(p) => properties_from_str(p)?,
+                None => Document::new(),
+            };
+
+            let record = if start.local_name().as_ref() == b"node" {
+                let id = attrs
+                    .remove("id")
+                    .ok_or(Pyo3MongoError::Common("GraphML node without id"))?;
+                // nodes from other tools may not carry a name, fallback to the id
+                let name = data.get("name").cloned().unwrap_or_else(|| id.clone());
+                GraphRecord::Vertex {
+                    id,
+                    name,
+                    properties,
+                }
+            } else {
+                let weight = data
+                    .get("weight")
+                    .map(|w| w.parse::<f64>())
+                    .transpose()
+                    .map_err(|_| Pyo3MongoError::Common("invalid GraphML edge weight"))?;
+                GraphRecord::Edge {
+                    id: attrs.remove("id"),
+                    source: attrs
+                        .remove("source")
+                        .ok_or(Pyo3MongoError::Common("GraphML edge without source"))?,
+                    target: attrs
+                        .remove("target")
+                        .ok_or(Pyo3MongoError::Common("GraphML edge without target"))?,
+                    weight,
+                    label: data.get("label").cloned(),
+                    properties,
+                }
+            };
+
+            return Ok(Some(record));
+        }
+    }
+
+    /// collect `<data>` children (attr.name -> text) until the closing tag `end`
+    fn read_data(&mut self, end: &[u8]) -> Pyo3MongoResult<HashMap<String, String>> {
+        let mut data = HashMap::new();
+        let mut key: Option<String> = None;
+
+        loop {
+            self.buf.clear();
+            match self.reader.read_event_into(&mut self.buf)? {
+                Event::Start(e) if e.local_name().as_ref() == b"data" => {
+                    key = attributes(&e)?
+                        .remove("key")
+                        .map(|k| self.keys.get(&k).cloned().unwrap_or(k));
+                }
+                Event::Text(t) => {
+                    if let Some(k) = &key {
+                        data.insert(k.clone(), t.unescape()?.into_owned());
+                    }
+                }
+                Event::End(e) if e.local_name().as_ref() == b"data" => key = None,
+                Event::End(e) if e.local_name().as_ref() == end => return Ok(data),
+                Event::Eof => return Err(Pyo3MongoError::Common("unexpected end of GraphML")),
+                _ => {}
+            }
+        }
+    }
+}
+
+fn attributes(start: &BytesStart) -> Pyo3MongoResult<HashMap<String, String>> {
+    let mut res = HashMap::new();
+    for attr in start.attributes() {
+        let attr = attr.map_err(quick_xml::Error::from)?;
+        let key = String::from_utf8_lossy(attr.key.local_name().as_ref()).into_owned();
+        res.insert(key, attr.unescape_value()?.into_owned());
+    }
+
+    Ok(res)
+}
+
+#[cfg(test)]
+mod test_portable {
+    use mongodb::bson::{doc, oid::ObjectId};
+
+    use super::*;
+
+    fn records() -> Vec<GraphRecord> {
+        vec![
+            GraphRecord::Vertex {
+                id: "n1".to_string(),
+                name: "node <1> & co".to_string(),
+                properties: doc! {"age": 3, "tags": ["a", "b"], "ref": ObjectId::new()},
+            },
+            GraphRecord::Vertex {
+                id: "n2".to_string(),
+                name: "node-2".to_string(),
+                properties: Document::new(),
+            },
+            GraphRecord::Edge {
+                id: Some("e1".to_string()),
+                source: "n1".to_string(),
+                target: "n2".to_string(),
+                weight: Some(1.5),
+                label: Some("test-label".to_string()),
+                properties: doc! {"since": "2022"},
+            },
+            GraphRecord::Edge {
+                id: None,
+                source: "n2".to_string(),
+                target: "n1".to_string(),
+                weight: None,
+                label: None,
+                properties: Document::new(),
+            },
+        ]
+    }
+
+    fn round_trip(format: GraphFormat, records: &[GraphRecord]) -> Vec<GraphRecord> {
+        let mut w = GraphWriter::new(format, Vec::new(), "dev").unwrap();
+        for r in records {
+            w.write_record(r).unwrap();
+        }
+        let bytes = w.finish().unwrap();
+
+        GraphReader::new(format, bytes.as_slice())
+            .collect::<Pyo3MongoResult<Vec<_>>>()
+            .unwrap()
+    }
+
+    #[test]
+    fn test_json_lines_round_trip() {
+        let records = records();
+        assert_eq!(round_trip(GraphFormat::JsonLines, &records), records);
+    }
+
+    #[test]
+    fn test_graphml_round_trip() {
+        let records = records();
+        assert_eq!(round_trip(GraphFormat::GraphML, &records), records);
+    }
+
+    #[test]
+    fn test_graphml_foreign_keys() {
+        // keys named by other tools, and a node without name
+        let xml = r#"<?xml version="1.0"?>
+            <graphml xmlns="http://graphml.graphdrawing.org/xmlns">
+              <key id="d0" for="edge" attr.name="weight" attr.type="double"/>
+              <graph edgedefault="directed">
+                <node id="a"/>
+                <node id="b"/>
+                <edge source="a" target="b"><data key="d0">2</data></edge>
+              </graph>
+            </graphml>"#;
+
+        let res = GraphReader::new(GraphFormat::GraphML, xml.as_bytes())
+            .collect::<Pyo3MongoResult<Vec<_>>>()
+            .unwrap();
+
+        assert_eq!(res.len(), 3);
+        assert_eq!(
+            res[0],
+            GraphRecord::Vertex {
+                id: "a".to_string(),
+                name: "a".to_string(),
+                properties: Document::new(),
+            }
+        );
+        assert!(matches!(&res[2], GraphRecord::Edge { weight: Some(w), .. } if *w == 2.0));
+    }
+
+    #[test]
+    fn test_format_from_path() {
+        assert_eq!(
+            GraphFormat::from_path("dev.jsonl"),
+            Some(GraphFormat::JsonLines)
+        );
+        assert_eq!(
+            GraphFormat::from_path("/tmp/dev.graphml"),
+            Some(GraphFormat::GraphML)
+        );
+        assert_eq!(GraphFormat::from_path("dev"), None);
+    }
+}
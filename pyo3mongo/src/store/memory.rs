@@ -0,0 +1,643 @@
+//! Memory store
+//!
+//! Vertexes & edges kept in maps, ordered by id, i.e. by insertion like a collection scan.
+//! Property filters are evaluated by a small subset of the Mongo query language:
+//! `$eq`, `$ne`, `$gt`, `$gte`, `$lt`, `$lte`, `$in`, `$nin`, `$exists`, `$and` & `$or`.
+
+use std::cmp::Ordering;
+use std::collections::{BTreeMap, HashSet};
+use std::io::Write;
+use std::ops::Bound;
+use std::sync::{PoisonError, RwLock, RwLockReadGuard, RwLockWriteGuard};
+
+use mongodb::bson::oid::ObjectId;
+use mongodb::bson::{Bson, Document};
+
+use super::super::model::{
+    Direction, Edge, FindEdgeByVertexDto, PageQuery, PropertyFilter, SortOrder, Vertex,
+};
+use super::super::portable::{BulkSummary, GraphWriter};
+use super::super::{Pyo3MongoError, Pyo3MongoResult};
+use super::{EntityStream, GraphStore};
+
+#[derive(Default)]
+struct Graph {
+    vertexes: BTreeMap<ObjectId, Vertex>,
+    edges: BTreeMap<ObjectId, Edge>,
+}
+
+impl Graph {
+    fn check_endpoints<'a>(
+        &self,
+        edges: impl IntoIterator<Item = &'a Edge>,
+    ) -> Pyo3MongoResult<()> {
+        for e in edges {
+            for id in [e.source, e.target] {
+                if !self.vertexes.contains_key(&id) {
+                    return Err(Pyo3MongoError::VertexNotFound(id));
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+/// A graph store without database, nothing is persisted.
+///
+/// Locks are never held across an `.await`, a store can be shared between tasks.
+#[derive(Default)]
+pub struct MemoryStore {
+    cat: String,
+    graph: RwLock<Graph>,
+}
+
+impl MemoryStore {
+    pub fn new(cat: &str) -> Self {
+        MemoryStore {
+            cat: cat.to_owned(),
+            graph: RwLock::default(),
+        }
+    }
+
+    // a panic while holding the lock cannot leave the maps half updated
+    fn read(&self) -> RwLockReadGuard<'_, Graph> {
+        self.graph.read().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    fn write(&self) -> RwLockWriteGuard<'_, Graph> {
+        self.graph.write().unwrap_or_else(PoisonError::into_inner)
+    }
+}
+
+impl GraphStore for MemoryStore {
+    fn category(&self) -> &str {
+        &self.cat
+    }
+
+    async fn truncate_all(&self) -> Pyo3MongoResult<()> {
+        let mut graph = self.write();
+        graph.vertexes.clear();
+        graph.edges.clear();
+        Ok(())
+    }
+
+    async fn count_vertexes(&self) -> Pyo3MongoResult<u64> {
+        Ok(self.read().vertexes.len() as u64)
+    }
+
+    async fn count_edges(&self) -> Pyo3MongoResult<u64> {
+        Ok(self.read().edges.len() as u64)
+    }
+
+    async fn insert_vertex(&self, vertex: Vertex) -> Pyo3MongoResult<Vertex> {
+        Ok(self.insert_vertexes(vec![vertex]).await?.remove(0))
+    }
+
+    async fn insert_vertexes(&self, mut vertexes: Vec<Vertex>) -> Pyo3MongoResult<Vec<Vertex>> {
+        let mut graph = self.write();
+        for v in vertexes.iter_mut() {
+            v.id = Some(v.id.unwrap_or_else(ObjectId::new));
+            if graph.vertexes.contains_key(&v.id.unwrap()) {
+                return Err(Pyo3MongoError::Common("duplicate id"));
+            }
+        }
+        for v in vertexes.iter() {
+            graph.vertexes.insert(v.id.unwrap(), v.clone());
+        }
+        Ok(vertexes)
+    }
+
+    async fn get_vertexes(&self, ids: Vec<ObjectId>) -> Pyo3MongoResult<Vec<Vertex>> {
+        let graph = self.read();
+        let ids = ids.into_iter().collect::<HashSet<_>>();
+        let res = graph
+            .vertexes
+            .iter()
+            .filter(|(id, _)| ids.contains(id))
+            .map(|(_, v)| v.clone())
+            .collect();
+        Ok(res)
+    }
+
+    async fn find_vertexes(&self, filter: &PropertyFilter) -> Pyo3MongoResult<Vec<Vertex>> {
+        let query = filter.to_document();
+        let mut res = Vec::new();
+        for v in self.read().vertexes.values() {
+            if matches(&query, &Document::from(v))? {
+                res.push(v.clone());
+            }
+        }
+        Ok(res)
+    }
+
+    async fn vertexes_page(&self, page: &PageQuery) -> Pyo3MongoResult<Vec<Vertex>> {
+        Ok(page_of(&self.read().vertexes, page, |_| true))
+    }
+
+    /// a snapshot of the vertexes at the time of the call
+    async fn stream_vertexes(&self) -> Pyo3MongoResult<EntityStream<Vertex>> {
+        let vertexes = self.read().vertexes.values().cloned().collect::<Vec<_>>();
+        Ok(Box::pin(tokio_stream::iter(vertexes.into_iter().map(Ok))))
+    }
+
+    async fn update_vertex(&self, id: ObjectId, vertex: Vertex) -> Pyo3MongoResult<Vertex> {
+        let mut graph = self.write();
+        let stored = graph
+            .vertexes
+            .get_mut(&id)
+            .ok_or(Pyo3MongoError::VertexNotFound(id))?;
+        let before = stored.clone();
+
+        // same as a `$set` of the serialized vertex, where empty properties are omitted
+        stored.name = vertex.name;
+        if !vertex.properties.is_empty() {
+            stored.properties = vertex.properties;
+        }
+
+        Ok(before)
+    }
+
+    async fn set_vertex_property(
+        &self,
+        key: &str,
+        values: Vec<(ObjectId, Bson)>,
+    ) -> Pyo3MongoResult<u64> {
+        let mut graph = self.write();
+        let mut matched = 0;
+        for (id, value) in values {
+            if let Some(v) = graph.vertexes.get_mut(&id) {
+                v.properties.insert(key, value);
+                matched += 1;
+            }
+        }
+        Ok(matched)
+    }
+
+    async fn delete_vertex(&self, id: ObjectId) -> Pyo3MongoResult<()> {
+        let mut graph = self.write();
+        if graph.vertexes.remove(&id).is_none() {
+            return Err(Pyo3MongoError::VertexNotFound(id));
+        }
+        graph.edges.retain(|_, e| e.source != id && e.target != id);
+        Ok(())
+    }
+
+    async fn insert_edge(&self, edge: Edge) -> Pyo3MongoResult<Edge> {
+        Ok(self.insert_edges(vec![edge]).await?.remove(0))
+    }
+
+    async fn insert_edges(&self, mut edges: Vec<Edge>) -> Pyo3MongoResult<Vec<Edge>> {
+        let mut graph = self.write();
+        graph.check_endpoints(&edges)?;
+
+        for e in edges.iter_mut() {
+            e.id = Some(e.id.unwrap_or_else(ObjectId::new));
+            if graph.edges.contains_key(&e.id.unwrap()) {
+                return Err(Pyo3MongoError::Common("duplicate id"));
+            }
+        }
+        for e in edges.iter() {
+            graph.edges.insert(e.id.unwrap(), e.clone());
+        }
+        Ok(edges)
+    }
+
+    async fn get_edges(&self, ids: Vec<ObjectId>) -> Pyo3MongoResult<Vec<Edge>> {
+        let graph = self.read();
+        let ids = ids.into_iter().collect::<HashSet<_>>();
+        let res = graph
+            .edges
+            .iter()
+            .filter(|(id, _)| ids.contains(id))
+            .map(|(_, e)| e.clone())
+            .collect();
+        Ok(res)
+    }
+
+    async fn find_edges(
+        &self,
+        label: Option<&str>,
+        filter: &PropertyFilter,
+    ) -> Pyo3MongoResult<Vec<Edge>> {
+        let query = filter.to_document();
+        let mut res = Vec::new();
+        for e in self.read().edges.values() {
+            if has_label(e, label) && matches(&query, &Document::from(e))? {
+                res.push(e.clone());
+            }
+        }
+        Ok(res)
+    }
+
+    async fn edges_page(
+        &self,
+        label: Option<&str>,
+        page: &PageQuery,
+    ) -> Pyo3MongoResult<Vec<Edge>> {
+        Ok(page_of(&self.read().edges, page, |e| has_label(e, label)))
+    }
+
+    /// a snapshot of the edges at the time of the call
+    async fn stream_edges(&self, label: Option<&str>) -> Pyo3MongoResult<EntityStream<Edge>> {
+        let edges = self
+            .read()
+            .edges
+            .values()
+            .filter(|e| has_label(e, label))
+            .cloned()
+            .collect::<Vec<_>>();
+        Ok(Box::pin(tokio_stream::iter(edges.into_iter().map(Ok))))
+    }
+
+    async fn update_edge(&self, id: ObjectId, edge: Edge) -> Pyo3MongoResult<Edge> {
+        let mut graph = self.write();
+        graph.check_endpoints([&edge])?;
+
+        let stored = graph
+            .edges
+            .get_mut(&id)
+            .ok_or(Pyo3MongoError::EdgeNotFound(id))?;
+        let before = stored.clone();
+
+        stored.source = edge.source;
+        stored.target = edge.target;
+        stored.weight = edge.weight;
+        stored.label = edge.label;
+        if !edge.properties.is_empty() {
+            stored.properties = edge.properties;
+        }
+
+        Ok(before)
+    }
+
+    async fn delete_edges(&self, ids: Vec<ObjectId>) -> Pyo3MongoResult<u64> {
+        let mut graph = self.write();
+        let deleted = ids
+            .iter()
+            .collect::<HashSet<_>>()
+            .into_iter()
+            .filter(|id| graph.edges.remove(id).is_some())
+            .count();
+        Ok(deleted as u64)
+    }
+
+    async fn edges_by_vertex(&self, find_dto: FindEdgeByVertexDto) -> Pyo3MongoResult<Vec<Edge>> {
+        let graph = self.read();
+        if !graph.vertexes.contains_key(&find_dto.id()) {
+            return Ok(vec![]);
+        }
+
+        let res = graph
+            .edges
+            .values()
+            .filter(|e| match find_dto {
+                FindEdgeByVertexDto::Source(id) => e.source == id,
+                FindEdgeByVertexDto::Target(id) => e.target == id,
+                FindEdgeByVertexDto::Bidirectional(id) => e.source == id || e.target == id,
+            })
+            .cloned()
+            .collect();
+        Ok(res)
+    }
+
+    async fn graph_lookup(
+        &self,
+        vertex_id: ObjectId,
+        label: Option<&str>,
+        depth: Option<i32>,
+    ) -> Pyo3MongoResult<Vec<Edge>> {
+        if depth.is_some_and(|d| d < 0) {
+            return Err(Pyo3MongoError::Common("maxDepth should be non-negative"));
+        }
+
+        let graph = self.read();
+        if !graph.vertexes.contains_key(&vertex_id) {
+            return Ok(vec![]);
+        }
+
+        let mut res = Vec::new();
+        let mut seen_edges = HashSet::new();
+        // a vertex is searched from only once, which ends cycles
+        let mut searched = HashSet::from([vertex_id]);
+        let mut frontier = HashSet::from([vertex_id]);
+        let mut d = 0;
+
+        while !frontier.is_empty() && depth.is_none_or(|m| d <= m) {
+            let mut next = HashSet::new();
+            for e in graph.edges.values() {
+                if frontier.contains(&e.source) && has_label(e, label) && seen_edges.insert(e.id) {
+                    res.push(e.clone());
+                    if searched.insert(e.target) {
+                        next.insert(e.target);
+                    }
+                }
+            }
+            frontier = next;
+            d += 1;
+        }
+
+        Ok(res)
+    }
+
+    async fn adjacent_edges(
+        &self,
+        vertexes: &[ObjectId],
+        direction: Direction,
+        label: Option<&str>,
+    ) -> Pyo3MongoResult<Vec<Edge>> {
+        let vertexes = vertexes.iter().collect::<HashSet<_>>();
+        let res = self
+            .read()
+            .edges
+            .values()
+            .filter(|e| has_label(e, label))
+            .filter(|e| match direction {
+                Direction::Outbound => vertexes.contains(&e.source),
+                Direction::Inbound => vertexes.contains(&e.target),
+                Direction::Both => vertexes.contains(&e.source) || vertexes.contains(&e.target),
+            })
+            .cloned()
+            .collect();
+        Ok(res)
+    }
+
+    async fn write_graph<W: Write + Send>(
+        &self,
+        writer: &mut GraphWriter<W>,
+    ) -> Pyo3MongoResult<BulkSummary> {
+        let graph = self.read();
+        for v in graph.vertexes.values() {
+            writer.write_vertex(v)?;
+        }
+        for e in graph.edges.values() {
+            writer.write_edge(e)?;
+        }
+
+        Ok(BulkSummary {
+            vertexes: graph.vertexes.len(),
+            edges: graph.edges.len(),
+        })
+    }
+}
+
+// ids of a `BTreeMap` are in order, same as the `_id` index
+fn page_of<T: Clone>(
+    items: &BTreeMap<ObjectId, T>,
+    page: &PageQuery,
+    keep: impl Fn(&T) -> bool,
+) -> Vec<T> {
+    let start = page.after.map_or(Bound::Unbounded, Bound::Excluded);
+    let values: Box<dyn Iterator<Item = &T>> = match page.order {
+        SortOrder::Ascending => Box::new(items.range((start, Bound::Unbounded)).map(|(_, v)| v)),
+        SortOrder::Descending => {
+            Box::new(items.range((Bound::Unbounded, start)).rev().map(|(_, v)| v))
+        }
+    };
+
+    values
+        .filter(|v| keep(v))
+        .take(page.limit)
+        .cloned()
+        .collect()
+}
+
+fn has_label(edge: &Edge, label: Option<&str>) -> bool {
+    label.is_none_or(|l| edge.label.as_deref() == Some(l))
+}
+
+// value of a dotted path, e.g. `properties.address.city`
+fn lookup<'a>(doc: &'a Document, path: &str) -> Option<&'a Bson> {
+    let mut parts = path.split('.');
+    let mut cur = doc.get(parts.next()?)?;
+    for p in parts {
+        cur = match cur {
+            Bson::Document(d) => d.get(p)?,
+            _ => return None,
+        };
+    }
+    Some(cur)
+}
+
+// numbers are compared by value whatever their BSON type, other types only to themselves
+fn compare(a: &Bson, b: &Bson) -> Option<Ordering> {
+    let num = |v: &Bson| match v {
+        Bson::Int32(i) => Some(*i as f64),
+        Bson::Int64(i) => Some(*i as f64),
+        Bson::Double(f) => Some(*f),
+        _ => None,
+    };
+    match (a, b) {
+        (Bson::String(x), Bson::String(y)) => Some(x.cmp(y)),
+        (Bson::Boolean(x), Bson::Boolean(y)) => Some(x.cmp(y)),
+        (Bson::DateTime(x), Bson::DateTime(y)) => Some(x.cmp(y)),
+        (Bson::ObjectId(x), Bson::ObjectId(y)) => Some(x.cmp(y)),
+        _ => num(a)?.partial_cmp(&num(b)?),
+    }
+}
+
+fn equals(a: &Bson, b: &Bson) -> bool {
+    compare(a, b).map_or(a == b, |o| o == Ordering::Equal)
+}
+
+// an array field matches if the array itself or any of its elements matches
+fn any_value(value: Option<&Bson>, f: impl Fn(&Bson) -> bool) -> bool {
+    match value {
+        Some(Bson::Array(items)) => f(value.unwrap()) || items.iter().any(f),
+        Some(v) => f(v),
+        None => false,
+    }
+}
+
+fn matches_op(value: Option<&Bson>, op: &str, arg: &Bson) -> Pyo3MongoResult<bool> {
+    let cmp =
+        |accept: fn(Ordering) -> bool| any_value(value, |v| compare(v, arg).is_some_and(accept));
+    let res = match op {
+        "$eq" => any_value(value, |v| equals(v, arg)),
+        "$ne" => !any_value(value, |v| equals(v, arg)),
+        "$gt" => cmp(Ordering::is_gt),
+        "$gte" => cmp(Ordering::is_ge),
+        "$lt" => cmp(Ordering::is_lt),
+        "$lte" => cmp(Ordering::is_le),
+        "$in" | "$nin" => {
+            let candidates = arg
+                .as_array()
+                .ok_or(Pyo3MongoError::Common("$in & $nin need an array"))?;
+            let found = candidates
+                .iter()
+                .any(|c| any_value(value, |v| equals(v, c)));
+            found == (op == "$in")
+        }
+        "$exists" => value.is_some() == arg.as_bool().unwrap_or(true),
+        _ => return Err(Pyo3MongoError::Common("unsupported query operator")),
+    };
+    Ok(res)
+}
+
+// whether `doc` matches the Mongo query `query`
+fn matches(query: &Document, doc: &Document) -> Pyo3MongoResult<bool> {
+    for (key, cond) in query {
+        let ok = match key.as_str() {
+            "$and" | "$or" => {
+                let subs = cond
+                    .as_array()
+                    .ok_or(Pyo3MongoError::Common("$and & $or need an array"))?;
+                let mut results = Vec::with_capacity(subs.len());
+                for s in subs {
+                    let s = s
+                        .as_document()
+                        .ok_or(Pyo3MongoError::Common("$and & $or need documents"))?;
+                    results.push(matches(s, doc)?);
+                }
+                if key == "$and" {
+                    results.iter().all(|r| *r)
+                } else {
+                    results.iter().any(|r| *r)
+                }
+            }
+            k if k.starts_with('$') => {
+                return Err(Pyo3MongoError::Common("unsupported query operator"))
+            }
+            path => {
+                let value = lookup(doc, path);
+                match cond {
+                    // `{"$gt": 1, "$lt": 9}`, otherwise an embedded document to be equal to
+                    Bson::Document(ops)
+                        if ops.keys().next().is_some_and(|k| k.starts_with('$')) =>
+                    {
+                        let mut ok = true;
+                        for (op, arg) in ops {
+                            ok &= matches_op(value, op, arg)?;
+                        }
+                        ok
+                    }
+                    _ => matches_op(value, "$eq", cond)?,
+                }
+            }
+        };
+        if !ok {
+            return Ok(false);
+        }
+    }
+
+    Ok(true)
+}
+
+#[cfg(test)]
+mod test_memory {
+    use mongodb::bson::doc;
+
+    use super::*;
+
+    fn edge(source: ObjectId, target: ObjectId, label: &str) -> Edge {
+        Edge {
+            id: None,
+            source,
+            target,
+            weight: None,
+            label: Some(label.to_owned()),
+            properties: Document::new(),
+        }
+    }
+
+    fn vertex(name: &str) -> Vertex {
+        Vertex {
+            id: None,
+            name: name.to_owned(),
+            properties: Document::new(),
+        }
+    }
+
+    #[test]
+    fn test_matches() {
+        let d = doc! {
+            "properties": {"age": 12, "tags": ["a", "b"], "address": {"city": "Shanghai"}}
+        };
+
+        let ok = |q: Document| matches(&q, &d).unwrap();
+        assert!(ok(doc! {"properties.age": {"$gte": 10.0, "$lt": 18}}));
+        assert!(!ok(doc! {"properties.age": {"$gt": 12}}));
+        assert!(ok(doc! {"properties.tags": "a"}));
+        assert!(ok(
+            doc! {"properties.address.city": {"$in": ["Shanghai", "Beijing"]}}
+        ));
+        assert!(ok(doc! {"properties.name": {"$exists": false}}));
+        assert!(ok(doc! {"properties.name": {"$ne": "x"}}));
+        assert!(ok(
+            doc! {"$or": [{"properties.age": 1}, {"properties.age": 12}]}
+        ));
+        assert!(!ok(doc! {"properties.age": "12"}));
+        assert!(matches(&doc! {"properties.age": {"$regex": "1"}}, &d).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_graph_lookup() {
+        let store = MemoryStore::new("dev");
+        let n = store
+            .insert_vertexes((0..4).map(|i| vertex(&format!("node-{i}"))).collect())
+            .await
+            .unwrap()
+            .into_iter()
+            .map(|v| v.id.unwrap())
+            .collect::<Vec<_>>();
+
+        // n0 -> n1 -> n2 -> n0 (a cycle), n2 -> n3 of another label
+        store
+            .insert_edges(vec![
+                edge(n[0], n[1], "a"),
+                edge(n[1], n[2], "a"),
+                edge(n[2], n[0], "a"),
+                edge(n[2], n[3], "b"),
+            ])
+            .await
+            .unwrap();
+
+        let all = store.graph_lookup(n[0], None, None).await.unwrap();
+        assert_eq!(all.len(), 4);
+
+        let labeled = store.graph_lookup(n[0], Some("a"), None).await.unwrap();
+        assert_eq!(labeled.len(), 3);
+
+        // depth 0: edges of the start vertex only
+        let first = store.graph_lookup(n[0], None, Some(0)).await.unwrap();
+        assert_eq!(first.len(), 1);
+        let second = store.graph_lookup(n[0], None, Some(1)).await.unwrap();
+        assert_eq!(second.len(), 2);
+
+        assert!(store
+            .graph_lookup(ObjectId::new(), None, None)
+            .await
+            .unwrap()
+            .is_empty());
+        assert!(store.graph_lookup(n[0], None, Some(-1)).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_mutations() {
+        let store = MemoryStore::new("dev");
+        let v1 = store.insert_vertex(vertex("node-1")).await.unwrap();
+        let v2 = store.insert_vertex(vertex("node-2")).await.unwrap();
+        let (id1, id2) = (v1.id.unwrap(), v2.id.unwrap());
+
+        let missing = store.insert_edge(edge(id1, ObjectId::new(), "a")).await;
+        assert!(matches!(missing, Err(Pyo3MongoError::VertexNotFound(_))));
+
+        let e = store.insert_edge(edge(id1, id2, "a")).await.unwrap();
+
+        // the vertex before update is returned, and empty properties are kept
+        let mut renamed = vertex("node-3");
+        let before = store.update_vertex(id1, renamed.clone()).await.unwrap();
+        assert_eq!(before, v1);
+        renamed.properties = doc! {"age": 1};
+        store.update_vertex(id1, renamed).await.unwrap();
+        store.update_vertex(id1, vertex("node-4")).await.unwrap();
+        let get = store.get_vertexes(vec![id1]).await.unwrap();
+        assert_eq!(get[0].properties, doc! {"age": 1});
+
+        store.delete_vertex(id2).await.unwrap();
+        let edges = store.get_edges(vec![e.id.unwrap()]).await.unwrap();
+        assert!(edges.is_empty(), "edges are deleted along with a vertex");
+
+        let again = store.delete_vertex(id2).await;
+        assert!(matches!(again, Err(Pyo3MongoError::VertexNotFound(v)) if v == id2));
+    }
+}
@@ -0,0 +1,176 @@
+//! Store
+//!
+//! Persistence of vertexes & edges behind `GraphService`:
+//! 1. `MongoStore`, collections `${cat}_vertex` & `${cat}_edge` of a MongoDB database
+//! 1. `MemoryStore`, plain maps in memory, for unit tests & offline tools
+
+mod memory;
+mod mongo;
+mod watch;
+
+pub use memory::MemoryStore;
+pub use mongo::{EdgeUniqueness, ExplainVerbosity, MongoStore, TransactionMode, Traversal};
+pub use watch::GraphEventStream;
+
+use std::future::Future;
+use std::io::Write;
+use std::pin::Pin;
+
+use mongodb::bson::oid::ObjectId;
+use mongodb::bson::Bson;
+use tokio_stream::Stream;
+
+use super::model::{Direction, Edge, FindEdgeByVertexDto, PageQuery, PropertyFilter, Vertex};
+use super::portable::{BulkSummary, GraphWriter};
+use super::Pyo3MongoResult;
+
+/// vertexes or edges read lazily, e.g. from a Mongo cursor
+pub type EntityStream<T> = Pin<Box<dyn Stream<Item = Pyo3MongoResult<T>> + Send>>;
+
+/// Vertex & edge persistence, plus the lookups that graph traversals are built on.
+///
+/// Every implementation follows the semantics of the Mongo one, which are described
+/// on each method. Futures are `Send`, so that a store can be shared by a Python async runtime.
+pub trait GraphStore: Send + Sync {
+    /// category (graph) name
+    fn category(&self) -> &str;
+
+    /// remove all vertexes & edges, careful to use
+    fn truncate_all(&self) -> impl Future<Output = Pyo3MongoResult<()>> + Send;
+
+    /// number of vertexes, none is loaded
+    fn count_vertexes(&self) -> impl Future<Output = Pyo3MongoResult<u64>> + Send;
+
+    /// number of edges, none is loaded
+    fn count_edges(&self) -> impl Future<Output = Pyo3MongoResult<u64>> + Send;
+
+    /// returns the inserted vertex, along with its new id
+    fn insert_vertex(&self, vertex: Vertex)
+        -> impl Future<Output = Pyo3MongoResult<Vertex>> + Send;
+
+    /// bulk insertion, ids of the returned vertexes are filled in order
+    fn insert_vertexes(
+        &self,
+        vertexes: Vec<Vertex>,
+    ) -> impl Future<Output = Pyo3MongoResult<Vec<Vertex>>> + Send;
+
+    /// missing ids are skipped
+    fn get_vertexes(
+        &self,
+        ids: Vec<ObjectId>,
+    ) -> impl Future<Output = Pyo3MongoResult<Vec<Vertex>>> + Send;
+
+    /// all vertexes if `filter` is empty
+    fn find_vertexes(
+        &self,
+        filter: &PropertyFilter,
+    ) -> impl Future<Output = Pyo3MongoResult<Vec<Vertex>>> + Send;
+
+    /// at most `page.limit` vertexes, ordered by id
+    fn vertexes_page(
+        &self,
+        page: &PageQuery,
+    ) -> impl Future<Output = Pyo3MongoResult<Vec<Vertex>>> + Send;
+
+    /// all vertexes ordered by id, without loading them at once
+    fn stream_vertexes(&self)
+        -> impl Future<Output = Pyo3MongoResult<EntityStream<Vertex>>> + Send;
+
+    /// `$set` name & properties (kept if empty), returns the vertex before the update.
+    /// `VertexNotFound` if missing.
+    fn update_vertex(
+        &self,
+        id: ObjectId,
+        vertex: Vertex,
+    ) -> impl Future<Output = Pyo3MongoResult<Vertex>> + Send;
+
+    /// `$set` `properties.<key>` of each vertex to its value, missing vertexes are skipped.
+    /// Returns the number of matched vertexes.
+    fn set_vertex_property(
+        &self,
+        key: &str,
+        values: Vec<(ObjectId, Bson)>,
+    ) -> impl Future<Output = Pyo3MongoResult<u64>> + Send;
+
+    /// delete all edges of the vertex and then the vertex, `VertexNotFound` if missing
+    fn delete_vertex(&self, id: ObjectId) -> impl Future<Output = Pyo3MongoResult<()>> + Send;
+
+    /// both endpoints must exist, `VertexNotFound` otherwise
+    fn insert_edge(&self, edge: Edge) -> impl Future<Output = Pyo3MongoResult<Edge>> + Send;
+
+    /// bulk insertion, nothing is inserted if an endpoint is missing
+    fn insert_edges(
+        &self,
+        edges: Vec<Edge>,
+    ) -> impl Future<Output = Pyo3MongoResult<Vec<Edge>>> + Send;
+
+    /// missing ids are skipped
+    fn get_edges(
+        &self,
+        ids: Vec<ObjectId>,
+    ) -> impl Future<Output = Pyo3MongoResult<Vec<Edge>>> + Send;
+
+    /// all edges (of a label) if `filter` is empty
+    fn find_edges(
+        &self,
+        label: Option<&str>,
+        filter: &PropertyFilter,
+    ) -> impl Future<Output = Pyo3MongoResult<Vec<Edge>>> + Send;
+
+    /// at most `page.limit` edges (of a label), ordered by id
+    fn edges_page(
+        &self,
+        label: Option<&str>,
+        page: &PageQuery,
+    ) -> impl Future<Output = Pyo3MongoResult<Vec<Edge>>> + Send;
+
+    /// all edges (of a label) ordered by id, without loading them at once
+    fn stream_edges(
+        &self,
+        label: Option<&str>,
+    ) -> impl Future<Output = Pyo3MongoResult<EntityStream<Edge>>> + Send;
+
+    /// endpoints are checked first, then same as `update_vertex`.
+    /// `weight` & `label` are always overwritten.
+    fn update_edge(
+        &self,
+        id: ObjectId,
+        edge: Edge,
+    ) -> impl Future<Output = Pyo3MongoResult<Edge>> + Send;
+
+    /// returns the number of deleted edges
+    fn delete_edges(&self, ids: Vec<ObjectId>)
+        -> impl Future<Output = Pyo3MongoResult<u64>> + Send;
+
+    /// edges of a vertex in one direction, empty if the vertex is missing
+    fn edges_by_vertex(
+        &self,
+        find_dto: FindEdgeByVertexDto,
+    ) -> impl Future<Output = Pyo3MongoResult<Vec<Edge>>> + Send;
+
+    /// `$graphLookup` from a vertex, following `source -> target`:
+    /// - edges of the vertex are at depth 0, and `depth` is the max depth included
+    /// - `label` restricts every edge of the search
+    /// - each edge is returned once, and cycles are only walked through once
+    /// - empty if the vertex is missing
+    fn graph_lookup(
+        &self,
+        vertex_id: ObjectId,
+        label: Option<&str>,
+        depth: Option<i32>,
+    ) -> impl Future<Output = Pyo3MongoResult<Vec<Edge>>> + Send;
+
+    /// edges touching any of `vertexes` in `direction`, optionally of a label
+    fn adjacent_edges(
+        &self,
+        vertexes: &[ObjectId],
+        direction: Direction,
+        label: Option<&str>,
+    ) -> impl Future<Output = Pyo3MongoResult<Vec<Edge>>> + Send;
+
+    /// write all vertexes and then all edges, without loading the whole graph if possible
+    fn write_graph<W: Write + Send>(
+        &self,
+        writer: &mut GraphWriter<W>,
+    ) -> impl Future<Output = Pyo3MongoResult<BulkSummary>> + Send;
+}
@@ -0,0 +1,947 @@
+//! Mongo store
+//!
+//! A graph is stored in two collections:
+//! 1. ${cat}_vertex
+//! 1. ${cat}_edge
+
+use std::collections::HashSet;
+use std::io::Write;
+use std::str::FromStr;
+use std::sync::OnceLock;
+
+use mongodb::bson::oid::ObjectId;
+use mongodb::bson::{doc, Bson, Document};
+use mongodb::change_stream::event::ResumeToken;
+use mongodb::options::{ChangeStreamOptions, FindOptions, FullDocumentType, IndexOptions};
+use mongodb::{ClientSession, Collection, IndexModel};
+use tokio_stream::StreamExt;
+
+use super::super::db::MongoClient;
+use super::super::model::{
+    Direction, Edge, FindEdgeByVertexDto, PageQuery, PropertyFilter, PureId, Vertex,
+};
+use super::super::portable::{BulkSummary, GraphWriter};
+use super::super::{Pyo3MongoError, Pyo3MongoResult};
+use super::{EntityStream, GraphEventStream, GraphStore};
+
+// max number of ids in a single `$in` query, keeps the filter document far below 16MB
+const ID_BATCH_SIZE: usize = 10_000;
+
+/// How mutations touching several documents (`insert_edge`, `insert_edges`, `update_edge`,
+/// `delete_vertex`) are executed.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum TransactionMode {
+    /// use a transaction if the deployment supports it, otherwise fallback to `Disabled`
+    #[default]
+    Auto,
+    /// always use a transaction, fail with `TransactionUnsupported` on a standalone server
+    Required,
+    /// never use a transaction, steps are ordered so that a failure in the middle
+    /// never leaves an edge pointing to a missing vertex
+    Disabled,
+}
+
+/// Uniqueness rule of edges, enforced by a unique index (see `MongoStore::ensure_indexes`)
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum EdgeUniqueness {
+    /// parallel edges are allowed
+    #[default]
+    None,
+    /// at most one edge from a source to a target
+    Endpoints,
+    /// at most one edge of each label from a source to a target
+    EndpointsAndLabel,
+}
+
+impl EdgeUniqueness {
+    // name & keys of the unique index
+    fn index(&self) -> Option<(&'static str, Document)> {
+        match self {
+            EdgeUniqueness::None => None,
+            EdgeUniqueness::Endpoints => {
+                Some(("unique_source_target", doc! {"source": 1, "target": 1}))
+            }
+            EdgeUniqueness::EndpointsAndLabel => Some((
+                "unique_source_target_label",
+                doc! {"source": 1, "target": 1, "label": 1},
+            )),
+        }
+    }
+}
+
+impl FromStr for EdgeUniqueness {
+    type Err = Pyo3MongoError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "none" => Ok(EdgeUniqueness::None),
+            "endpoints" => Ok(EdgeUniqueness::Endpoints),
+            "endpoints_label" | "endpoints-label" => Ok(EdgeUniqueness::EndpointsAndLabel),
+            _ => Err(Pyo3MongoError::Common("unknown edge uniqueness")),
+        }
+    }
+}
+
+/// A query issued by a traversal method, see `MongoStore::explain`
+#[derive(Debug)]
+pub enum Traversal<'a> {
+    /// `get_edges_by_vertex`
+    EdgesByVertex(FindEdgeByVertexDto),
+    /// edges removed by `delete_vertex`
+    DeleteVertex(ObjectId),
+    /// `get_edges_from_vertex_by_label` & `get_graph_from_vertex_by_label`
+    GraphLookup {
+        vertex_id: ObjectId,
+        label: Option<&'a str>,
+        depth: Option<i32>,
+    },
+    /// a single hop of `neighborhood` & `shortest_path`
+    AdjacentEdges {
+        vertexes: &'a [ObjectId],
+        direction: Direction,
+        label: Option<&'a str>,
+    },
+}
+
+/// Amount of information returned by `MongoStore::explain`
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum ExplainVerbosity {
+    /// the winning plan only, nothing is executed
+    #[default]
+    QueryPlanner,
+    /// plus statistics of executing the winning plan
+    ExecutionStats,
+    /// plus statistics of the rejected plans
+    AllPlansExecution,
+}
+
+impl ExplainVerbosity {
+    fn as_str(&self) -> &'static str {
+        match self {
+            ExplainVerbosity::QueryPlanner => "queryPlanner",
+            ExplainVerbosity::ExecutionStats => "executionStats",
+            ExplainVerbosity::AllPlansExecution => "allPlansExecution",
+        }
+    }
+}
+
+impl FromStr for ExplainVerbosity {
+    type Err = Pyo3MongoError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "queryplanner" => Ok(ExplainVerbosity::QueryPlanner),
+            "executionstats" => Ok(ExplainVerbosity::ExecutionStats),
+            "allplansexecution" => Ok(ExplainVerbosity::AllPlansExecution),
+            _ => Err(Pyo3MongoError::Common("unknown explain verbosity")),
+        }
+    }
+}
+
+pub struct MongoStore {
+    client: MongoClient,
+    cat: String,
+    mode: TransactionMode,
+    // cached result of `MongoClient::supports_transaction`
+    txn_support: OnceLock<bool>,
+}
+
+impl MongoStore {
+    pub async fn new(uri: &str, db: &str, cat: &str) -> Pyo3MongoResult<Self> {
+        Ok(MongoStore {
+            client: MongoClient::new(uri, db).await?,
+            cat: cat.to_owned(),
+            mode: TransactionMode::default(),
+            txn_support: OnceLock::new(),
+        })
+    }
+
+    pub fn with_transaction_mode(mut self, mode: TransactionMode) -> Self {
+        self.mode = mode;
+        self
+    }
+
+    pub fn transaction_mode(&self) -> TransactionMode {
+        self.mode
+    }
+
+    pub async fn show_dbs(&self) -> Pyo3MongoResult<Vec<String>> {
+        Ok(self.client.show_dbs().await?)
+    }
+
+    /// validate `properties` of every vertex by a JSON schema, `None` removes the validation.
+    ///
+    /// Insertions & updates violating the schema are rejected by MongoDB
+    /// (`DocumentValidationFailure`, code 121).
+    pub async fn set_vertex_schema(&self, schema: Option<Document>) -> Pyo3MongoResult<()> {
+        self.client
+            .set_validator(
+                &format!("{}_vertex", self.cat),
+                properties_validator(schema),
+            )
+            .await?;
+        Ok(())
+    }
+
+    /// validate `properties` of every edge by a JSON schema, see `set_vertex_schema`
+    pub async fn set_edge_schema(&self, schema: Option<Document>) -> Pyo3MongoResult<()> {
+        self.client
+            .set_validator(&format!("{}_edge", self.cat), properties_validator(schema))
+            .await?;
+        Ok(())
+    }
+
+    /// events of both collections in a single change stream, from now on or right after
+    /// `resume_after` (see `GraphEventStream::resume_token`).
+    ///
+    /// Change streams require a replica set or a sharded cluster.
+    pub async fn watch(
+        &self,
+        resume_after: Option<ResumeToken>,
+    ) -> Pyo3MongoResult<GraphEventStream> {
+        let vertex = format!("{}_vertex", self.cat);
+        let edge = format!("{}_edge", self.cat);
+
+        let pipeline = vec![doc! {"$match": {"ns.coll": {"$in": [&vertex, &edge]}}}];
+        let options = ChangeStreamOptions::builder()
+            .full_document(Some(FullDocumentType::UpdateLookup))
+            .resume_after(resume_after)
+            .build();
+        let stream = self.client.watch(pipeline, options).await?;
+
+        Ok(GraphEventStream::new(stream, vertex))
+    }
+
+    /// create the indexes that traversals rely on, returns the names of all the indexes
+    /// of the edge collection:
+    /// - `{source, label}` serves outbound lookups and `$graphLookup`, which connects
+    ///   `target -> source` and restricts the search by label
+    /// - `{target, label}` serves inbound lookups
+    /// - a unique index according to `uniqueness`, the unique index of another rule is
+    ///   dropped. Creation fails if existing edges violate the rule.
+    ///
+    /// Idempotent, existing indexes are kept. Vertexes are only queried by `_id`.
+    pub async fn ensure_indexes(&self, uniqueness: EdgeUniqueness) -> Pyo3MongoResult<Vec<String>> {
+        let index = |name: &str, keys: Document, unique: bool| {
+            let options = IndexOptions::builder()
+                .name(name.to_owned())
+                .unique(unique.then_some(true))
+                .build();
+            IndexModel::builder().keys(keys).options(options).build()
+        };
+
+        let mut models = vec![
+            index("source_label", doc! {"source": 1, "label": 1}, false),
+            index("target_label", doc! {"target": 1, "label": 1}, false),
+        ];
+        let unique = uniqueness.index();
+        if let Some((name, keys)) = &unique {
+            models.push(index(name, keys.clone(), true));
+        }
+
+        let coll = self.collection_edge();
+        coll.create_indexes(models, None).await?;
+
+        let wanted = unique.map(|(name, _)| name);
+        for rule in [EdgeUniqueness::Endpoints, EdgeUniqueness::EndpointsAndLabel] {
+            if let Some((name, _)) = rule.index().filter(|(n, _)| Some(*n) != wanted) {
+                if coll.list_index_names().await?.iter().any(|n| n == name) {
+                    coll.drop_index(name, None).await?;
+                }
+            }
+        }
+
+        Ok(coll.list_index_names().await?)
+    }
+
+    /// query plan of a traversal, as returned by the `explain` command
+    pub async fn explain(
+        &self,
+        traversal: Traversal<'_>,
+        verbosity: ExplainVerbosity,
+    ) -> Pyo3MongoResult<Document> {
+        let vertex = format!("{}_vertex", self.cat);
+        let edge = format!("{}_edge", self.cat);
+
+        let aggregate = |coll: &str, pipeline: Vec<Document>| {
+            doc! {"aggregate": coll, "pipeline": pipeline, "cursor": {}}
+        };
+        let command = match traversal {
+            Traversal::EdgesByVertex(find_dto) => {
+                aggregate(&vertex, self.edges_by_vertex_pipeline(find_dto))
+            }
+            Traversal::DeleteVertex(id) => doc! {
+                "delete": &edge,
+                "deletes": [{"q": incident_edges_filter(id), "limit": 0}]
+            },
+            Traversal::GraphLookup {
+                vertex_id,
+                label,
+                depth,
+            } => aggregate(&vertex, self.graph_lookup_pipeline(vertex_id, label, depth)),
+            Traversal::AdjacentEdges {
+                vertexes,
+                direction,
+                label,
+            } => doc! {
+                "find": &edge,
+                "filter": adjacent_edges_filter(vertexes, direction, label)
+            },
+        };
+
+        let explain = doc! {"explain": command, "verbosity": verbosity.as_str()};
+        Ok(self.client.run_command(explain).await?)
+    }
+
+    /// collection of vertex
+    fn collection_vertex(&self) -> Collection<Vertex> {
+        self.client
+            .collection::<Vertex>(&format!("{}_vertex", self.cat))
+    }
+
+    /// collection of edge
+    fn collection_edge(&self) -> Collection<Edge> {
+        self.client
+            .collection::<Edge>(&format!("{}_edge", self.cat))
+    }
+
+    /// start a session, and a transaction on it if the mode & the deployment allow.
+    /// The returned flag tells whether a transaction has been started.
+    async fn begin(&self) -> Pyo3MongoResult<(ClientSession, bool)> {
+        let use_txn = match self.mode {
+            TransactionMode::Disabled => false,
+            TransactionMode::Auto | TransactionMode::Required => {
+                let supported = match self.txn_support.get() {
+                    Some(s) => *s,
+                    None => {
+                        let s = self.client.supports_transaction().await?;
+                        *self.txn_support.get_or_init(|| s)
+                    }
+                };
+                if !supported && self.mode == TransactionMode::Required {
+                    return Err(Pyo3MongoError::TransactionUnsupported);
+                }
+                supported
+            }
+        };
+
+        let mut session = self.client.start_session().await?;
+        if use_txn {
+            session.start_transaction(None).await?;
+        }
+
+        Ok((session, use_txn))
+    }
+
+    /// commit on success, abort on failure; do nothing if no transaction was started
+    async fn end<T>(
+        &self,
+        mut session: ClientSession,
+        txn: bool,
+        res: Pyo3MongoResult<T>,
+    ) -> Pyo3MongoResult<T> {
+        if !txn {
+            return res;
+        }
+
+        match res {
+            Ok(v) => {
+                session
+                    .commit_transaction()
+                    .await
+                    .map_err(Pyo3MongoError::TransactionAborted)?;
+                Ok(v)
+            }
+            Err(e) => {
+                // the original error is more meaningful than a failed abort
+                let _ = session.abort_transaction().await;
+                Err(e)
+            }
+        }
+    }
+
+    /// look up all the source & target vertexes whether existed.
+    /// Instead of querying vertex by vertex, distinct ids are looked up in batches,
+    /// and the first missing one is reported.
+    async fn check_edges_legitimacy(
+        &self,
+        edges: &[Edge],
+        session: &mut ClientSession,
+    ) -> Pyo3MongoResult<()> {
+        let ids = edges
+            .iter()
+            .flat_map(|e| [e.source, e.target])
+            .collect::<HashSet<_>>()
+            .into_iter()
+            .collect::<Vec<_>>();
+        let fo = FindOptions::builder()
+            .projection(doc! {"_id": 1i32})
+            .build();
+
+        for chunk in ids.chunks(ID_BATCH_SIZE) {
+            let mut cursor = self
+                .client
+                .collection::<PureId>(&format!("{}_vertex", self.cat))
+                .find_with_session(doc! {"_id": {"$in": chunk}}, fo.clone(), session)
+                .await?;
+
+            let mut found = HashSet::new();
+            while let Some(v) = cursor.next(session).await {
+                found.insert(v?.id);
+            }
+
+            if let Some(missing) = chunk.iter().find(|id| !found.contains(id)) {
+                return Err(Pyo3MongoError::VertexNotFound(*missing));
+            }
+        }
+
+        Ok(())
+    }
+
+    async fn insert_edge_in(
+        &self,
+        mut edge: Edge,
+        session: &mut ClientSession,
+    ) -> Pyo3MongoResult<Edge> {
+        self.check_edges_legitimacy(std::slice::from_ref(&edge), session)
+            .await?;
+
+        let insert = self
+            .collection_edge()
+            .insert_one_with_session(&edge, None, session)
+            .await?;
+        edge.id = insert.inserted_id.as_object_id();
+
+        Ok(edge)
+    }
+
+    async fn insert_edges_in(
+        &self,
+        mut edges: Vec<Edge>,
+        session: &mut ClientSession,
+    ) -> Pyo3MongoResult<Vec<Edge>> {
+        self.check_edges_legitimacy(&edges, session).await?;
+
+        let insert = self
+            .collection_edge()
+            .insert_many_with_session(&edges, None, session)
+            .await?;
+        for (idx, id) in insert.inserted_ids {
+            edges[idx].id = id.as_object_id();
+        }
+
+        Ok(edges)
+    }
+
+    async fn update_edge_in(
+        &self,
+        id: ObjectId,
+        edge: Edge,
+        session: &mut ClientSession,
+    ) -> Pyo3MongoResult<Edge> {
+        self.check_edges_legitimacy(std::slice::from_ref(&edge), session)
+            .await?;
+
+        let filter = doc! {"_id": id};
+        let update = doc! {
+            "$set": Document::from(&edge)
+        };
+
+        self.collection_edge()
+            .find_one_and_update_with_session(filter, update, None, session)
+            .await?
+            .ok_or(Pyo3MongoError::EdgeNotFound(id))
+    }
+
+    async fn delete_vertex_in(
+        &self,
+        id: ObjectId,
+        session: &mut ClientSession,
+    ) -> Pyo3MongoResult<()> {
+        // exit if vertex not found
+        if self
+            .collection_vertex()
+            .find_one_with_session(doc! {"_id": id}, None, session)
+            .await?
+            .is_none()
+        {
+            return Err(Pyo3MongoError::VertexNotFound(id));
+        }
+
+        // edges who link to or from the vertex, removed by a single `delete_many`.
+        // Edges go first: without a transaction, a failure in between leaves an
+        // isolated vertex rather than dangling edges
+        self.collection_edge()
+            .delete_many_with_session(incident_edges_filter(id), None, session)
+            .await?;
+
+        // delete vertex
+        let res = self
+            .collection_vertex()
+            .delete_one_with_session(doc! {"_id": id}, None, session)
+            .await?;
+
+        if res.deleted_count == 0 {
+            return Err(Pyo3MongoError::VertexNotFound(id));
+        }
+
+        Ok(())
+    }
+
+    // pipeline of `edges_by_vertex`, aggregated on the vertex collection
+    fn edges_by_vertex_pipeline(&self, find_dto: FindEdgeByVertexDto) -> Vec<Document> {
+        // match object id in vertex collection
+        let match_id = |id: ObjectId| doc! {"$match": {"_id": id}};
+        // from edge collection
+        let from = format!("{}_edge", self.cat);
+        // lookup related edges, source/target/both
+        let lookup = |field: &str| {
+            doc! {"$lookup": {
+                "from": &from,
+                "localField": "_id",
+                "foreignField": field,
+                "as": "edges"
+            }}
+        };
+        // turn aggregations into a vector of edges document
+        let unwind = doc! {"$unwind": "$edges"};
+        // replaceRoot, discard unnecessary parent fields, and keep a child value only
+        let replace = doc! {"$replaceRoot": {"newRoot": "$edges"}};
+
+        // a pipeline can been seen as a workflow
+        match find_dto {
+            FindEdgeByVertexDto::Source(id) => {
+                vec![match_id(id), lookup("source"), unwind, replace]
+            }
+            FindEdgeByVertexDto::Target(id) => {
+                vec![match_id(id), lookup("target"), unwind, replace]
+            }
+            FindEdgeByVertexDto::Bidirectional(id) => {
+                // lookup both source and target direction's edges
+                // instead of using `localField` & `foreignField` combination, we need a
+                // `pipeline` here to express an advanced matching case -- $or.
+                // A plain query rather than `$expr`, so that the indexes are used
+                let advanced_lookup = doc! {
+                    "$lookup": {
+                        "from": &from,
+                        "pipeline": [{"$match": incident_edges_filter(id)}],
+                        "as": "edges"
+                    }
+                };
+
+                vec![match_id(id), advanced_lookup, unwind, replace]
+            }
+        }
+    }
+
+    // pipeline of `graph_lookup`, aggregated on the vertex collection
+    fn graph_lookup_pipeline(
+        &self,
+        vertex_id: ObjectId,
+        label: Option<&str>,
+        depth: Option<i32>,
+    ) -> Vec<Document> {
+        // optional field
+        let depth = match depth {
+            Some(n) => doc! {"maxDepth": n},
+            None => doc! {},
+        };
+        // optional field
+        let restrict = match label {
+            Some(l) => doc! {"restrictSearchWithMatch": {"label": l}},
+            None => doc! {},
+        };
+        // CORE FEATURE
+        let mut graph_lookup = doc! {
+            "from": format!("{}_edge", self.cat),
+            "startWith": "$_id",
+            "connectFromField": "target",
+            "connectToField": "source",
+            "as": "edges",
+        };
+        graph_lookup.extend(depth);
+        graph_lookup.extend(restrict);
+
+        // a pipeline similar to `$lookup` as shown above
+        vec![
+            doc! {"$match": doc! {"_id": vertex_id}},
+            doc! {"$graphLookup": graph_lookup},
+            doc! {"$unwind": "$edges"},
+            doc! {"$replaceRoot": {"newRoot": "$edges"}},
+        ]
+    }
+}
+
+impl GraphStore for MongoStore {
+    fn category(&self) -> &str {
+        &self.cat
+    }
+
+    async fn truncate_all(&self) -> Pyo3MongoResult<()> {
+        self.collection_vertex().delete_many(doc! {}, None).await?;
+        self.collection_edge().delete_many(doc! {}, None).await?;
+        Ok(())
+    }
+
+    async fn count_vertexes(&self) -> Pyo3MongoResult<u64> {
+        Ok(self
+            .collection_vertex()
+            .count_documents(doc! {}, None)
+            .await?)
+    }
+
+    async fn count_edges(&self) -> Pyo3MongoResult<u64> {
+        Ok(self
+            .collection_edge()
+            .count_documents(doc! {}, None)
+            .await?)
+    }
+
+    async fn insert_vertex(&self, vertex: Vertex) -> Pyo3MongoResult<Vertex> {
+        let insert = self.collection_vertex().insert_one(vertex, None).await?;
+
+        let id = insert.inserted_id.as_object_id().unwrap();
+        self.collection_vertex()
+            .find_one(doc! {"_id": id}, None)
+            .await?
+            .ok_or(Pyo3MongoError::VertexNotFound(id))
+    }
+
+    /// a single `insert_many`, unlike `insert_vertex` no `find_one` is needed,
+    /// ids are taken from the insert result
+    async fn insert_vertexes(&self, mut vertexes: Vec<Vertex>) -> Pyo3MongoResult<Vec<Vertex>> {
+        if vertexes.is_empty() {
+            return Ok(vec![]);
+        }
+
+        let insert = self
+            .collection_vertex()
+            .insert_many(&vertexes, None)
+            .await?;
+
+        // `inserted_ids` is keyed by the index of each inserted document
+        for (idx, id) in insert.inserted_ids {
+            vertexes[idx].id = id.as_object_id();
+        }
+
+        Ok(vertexes)
+    }
+
+    async fn get_vertexes(&self, ids: Vec<ObjectId>) -> Pyo3MongoResult<Vec<Vertex>> {
+        let mut res = Vec::with_capacity(ids.len());
+        for chunk in ids.chunks(ID_BATCH_SIZE) {
+            let mut cursor = self
+                .collection_vertex()
+                .find(doc! {"_id": doc! {"$in": chunk}}, None)
+                .await?;
+            while let Some(doc) = cursor.next().await {
+                res.push(doc?);
+            }
+        }
+
+        Ok(res)
+    }
+
+    async fn find_vertexes(&self, filter: &PropertyFilter) -> Pyo3MongoResult<Vec<Vertex>> {
+        let mut cursor = self
+            .collection_vertex()
+            .find(filter.to_document(), None)
+            .await?;
+
+        let mut res = Vec::new();
+        while let Some(doc) = cursor.next().await {
+            res.push(doc?);
+        }
+
+        Ok(res)
+    }
+
+    async fn vertexes_page(&self, page: &PageQuery) -> Pyo3MongoResult<Vec<Vertex>> {
+        let fo = FindOptions::builder()
+            .sort(page.sort())
+            .limit(page.limit as i64)
+            .build();
+        let mut cursor = self
+            .collection_vertex()
+            .find(page.to_document(), fo)
+            .await?;
+
+        let mut res = Vec::with_capacity(page.limit);
+        while let Some(doc) = cursor.next().await {
+            res.push(doc?);
+        }
+
+        Ok(res)
+    }
+
+    async fn stream_vertexes(&self) -> Pyo3MongoResult<EntityStream<Vertex>> {
+        let fo = FindOptions::builder().sort(doc! {"_id": 1}).build();
+        let cursor = self.collection_vertex().find(None, fo).await?;
+
+        Ok(Box::pin(cursor.map(|v| Ok(v?))))
+    }
+
+    async fn update_vertex(&self, id: ObjectId, vertex: Vertex) -> Pyo3MongoResult<Vertex> {
+        let filter = doc! {"_id": id};
+        let update = doc! {
+            "$set": Document::from(&vertex)
+        };
+
+        self.collection_vertex()
+            .find_one_and_update(filter, update, None)
+            .await?
+            .ok_or(Pyo3MongoError::VertexNotFound(id))
+    }
+
+    /// raw `update` commands of `ID_BATCH_SIZE` statements, a single round trip per batch
+    async fn set_vertex_property(
+        &self,
+        key: &str,
+        values: Vec<(ObjectId, Bson)>,
+    ) -> Pyo3MongoResult<u64> {
+        let field = format!("properties.{key}");
+        let collection = format!("{}_vertex", self.cat);
+        let mut matched = 0;
+        for chunk in values.chunks(ID_BATCH_SIZE) {
+            let updates = chunk
+                .iter()
+                .map(|(id, value)| doc! {"q": {"_id": id}, "u": {"$set": {&field: value}}})
+                .collect::<Vec<_>>();
+            let command = doc! {"update": &collection, "updates": updates, "ordered": false};
+            let res = self.client.run_command(command).await?;
+            if res.get_array("writeErrors").is_ok_and(|e| !e.is_empty()) {
+                return Err(Pyo3MongoError::Common("failed to set vertex property"));
+            }
+            matched += res.get_i32("n").unwrap_or_default() as u64;
+        }
+
+        Ok(matched)
+    }
+
+    /// atomically if a transaction is available (see `TransactionMode`)
+    async fn delete_vertex(&self, id: ObjectId) -> Pyo3MongoResult<()> {
+        let (mut session, txn) = self.begin().await?;
+        let res = self.delete_vertex_in(id, &mut session).await;
+        self.end(session, txn, res).await
+    }
+
+    /// endpoints checking and insertion are done in a single transaction (see `TransactionMode`),
+    /// so that a concurrent `delete_vertex` cannot leave this edge dangling
+    async fn insert_edge(&self, edge: Edge) -> Pyo3MongoResult<Edge> {
+        let (mut session, txn) = self.begin().await?;
+        let res = self.insert_edge_in(edge, &mut session).await;
+        self.end(session, txn, res).await
+    }
+
+    /// a single `insert_many`, endpoints are checked in batches, both in a single transaction
+    /// (see `TransactionMode`)
+    async fn insert_edges(&self, edges: Vec<Edge>) -> Pyo3MongoResult<Vec<Edge>> {
+        if edges.is_empty() {
+            return Ok(vec![]);
+        }
+
+        let (mut session, txn) = self.begin().await?;
+        let res = self.insert_edges_in(edges, &mut session).await;
+        self.end(session, txn, res).await
+    }
+
+    async fn get_edges(&self, ids: Vec<ObjectId>) -> Pyo3MongoResult<Vec<Edge>> {
+        let mut res = Vec::with_capacity(ids.len());
+        for chunk in ids.chunks(ID_BATCH_SIZE) {
+            let mut cursor = self
+                .collection_edge()
+                .find(doc! {"_id": doc! {"$in": chunk}}, None)
+                .await?;
+            while let Some(doc) = cursor.next().await {
+                res.push(doc?);
+            }
+        }
+
+        Ok(res)
+    }
+
+    async fn find_edges(
+        &self,
+        label: Option<&str>,
+        filter: &PropertyFilter,
+    ) -> Pyo3MongoResult<Vec<Edge>> {
+        let mut query = filter.to_document();
+        if let Some(l) = label {
+            query.insert("label", l);
+        }
+
+        let mut cursor = self.collection_edge().find(query, None).await?;
+
+        let mut res = Vec::new();
+        while let Some(doc) = cursor.next().await {
+            res.push(doc?);
+        }
+
+        Ok(res)
+    }
+
+    async fn edges_page(
+        &self,
+        label: Option<&str>,
+        page: &PageQuery,
+    ) -> Pyo3MongoResult<Vec<Edge>> {
+        let mut query = page.to_document();
+        if let Some(l) = label {
+            query.insert("label", l);
+        }
+        let fo = FindOptions::builder()
+            .sort(page.sort())
+            .limit(page.limit as i64)
+            .build();
+        let mut cursor = self.collection_edge().find(query, fo).await?;
+
+        let mut res = Vec::with_capacity(page.limit);
+        while let Some(doc) = cursor.next().await {
+            res.push(doc?);
+        }
+
+        Ok(res)
+    }
+
+    async fn stream_edges(&self, label: Option<&str>) -> Pyo3MongoResult<EntityStream<Edge>> {
+        let query = label.map(|l| doc! {"label": l});
+        let fo = FindOptions::builder().sort(doc! {"_id": 1}).build();
+        let cursor = self.collection_edge().find(query, fo).await?;
+
+        Ok(Box::pin(cursor.map(|e| Ok(e?))))
+    }
+
+    async fn update_edge(&self, id: ObjectId, edge: Edge) -> Pyo3MongoResult<Edge> {
+        let (mut session, txn) = self.begin().await?;
+        let res = self.update_edge_in(id, edge, &mut session).await;
+        self.end(session, txn, res).await
+    }
+
+    async fn delete_edges(&self, ids: Vec<ObjectId>) -> Pyo3MongoResult<u64> {
+        let mut deleted = 0;
+        for chunk in ids.chunks(ID_BATCH_SIZE) {
+            let res = self
+                .collection_edge()
+                .delete_many(doc! {"_id": { "$in": chunk }}, None)
+                .await?;
+            deleted += res.deleted_count;
+        }
+
+        Ok(deleted)
+    }
+
+    async fn edges_by_vertex(&self, find_dto: FindEdgeByVertexDto) -> Pyo3MongoResult<Vec<Edge>> {
+        let pipeline = self.edges_by_vertex_pipeline(find_dto);
+
+        // cursor, streams the result of a query, can be seen as a future's iterator
+        let mut cursor = self.collection_vertex().aggregate(pipeline, None).await?;
+
+        let mut res = Vec::new();
+        while let Some(doc) = cursor.next().await {
+            let edge: Edge = bson::from_document(doc?)?;
+            res.push(edge);
+        }
+
+        Ok(res)
+    }
+
+    // graph-lookup, a powerful query method provided by mongo, used to recursively
+    // find out related graph patter, see README.md for more details
+    async fn graph_lookup(
+        &self,
+        vertex_id: ObjectId,
+        label: Option<&str>,
+        depth: Option<i32>,
+    ) -> Pyo3MongoResult<Vec<Edge>> {
+        let pipeline = self.graph_lookup_pipeline(vertex_id, label, depth);
+
+        let mut cursor = self.collection_vertex().aggregate(pipeline, None).await?;
+
+        let mut res = Vec::new();
+        while let Some(doc) = cursor.next().await {
+            let edge: Edge = bson::from_document(doc?)?;
+            res.push(edge);
+        }
+
+        Ok(res)
+    }
+
+    async fn adjacent_edges(
+        &self,
+        vertexes: &[ObjectId],
+        direction: Direction,
+        label: Option<&str>,
+    ) -> Pyo3MongoResult<Vec<Edge>> {
+        let mut res = Vec::new();
+        for chunk in vertexes.chunks(ID_BATCH_SIZE) {
+            let filter = adjacent_edges_filter(chunk, direction, label);
+            let mut cursor = self.collection_edge().find(filter, None).await?;
+            while let Some(edge) = cursor.next().await {
+                res.push(edge?);
+            }
+        }
+
+        Ok(res)
+    }
+
+    /// both collections are streamed by cursor, so the graph is never fully loaded
+    async fn write_graph<W: Write + Send>(
+        &self,
+        writer: &mut GraphWriter<W>,
+    ) -> Pyo3MongoResult<BulkSummary> {
+        let mut summary = BulkSummary::default();
+
+        let mut cursor = self.collection_vertex().find(None, None).await?;
+        while let Some(vertex) = cursor.next().await {
+            writer.write_vertex(&vertex?)?;
+            summary.vertexes += 1;
+        }
+
+        let mut cursor = self.collection_edge().find(None, None).await?;
+        while let Some(edge) = cursor.next().await {
+            writer.write_edge(&edge?)?;
+            summary.edges += 1;
+        }
+
+        Ok(summary)
+    }
+}
+
+// wrap a JSON schema of `properties` into a collection validator.
+// `properties` is omitted when empty, so it becomes required once the schema requires any field.
+fn properties_validator(schema: Option<Document>) -> Document {
+    let schema = match schema {
+        Some(s) => s,
+        None => return doc! {},
+    };
+
+    let mut json_schema = doc! {"bsonType": "object"};
+    if schema.get_array("required").is_ok_and(|r| !r.is_empty()) {
+        json_schema.insert("required", vec!["properties"]);
+    }
+    json_schema.insert("properties", doc! {"properties": schema});
+
+    doc! {"$jsonSchema": json_schema}
+}
+
+// edges linking to or from a vertex
+fn incident_edges_filter(id: ObjectId) -> Document {
+    doc! {"$or": [{"source": id}, {"target": id}]}
+}
+
+// edges touching any of `ids` in `direction`, optionally of a label
+fn adjacent_edges_filter(ids: &[ObjectId], direction: Direction, label: Option<&str>) -> Document {
+    let mut filter = match direction {
+        Direction::Outbound => doc! {"source": {"$in": ids}},
+        Direction::Inbound => doc! {"target": {"$in": ids}},
+        Direction::Both => doc! {"$or": [
+            {"source": {"$in": ids}},
+            {"target": {"$in": ids}}
+        ]},
+    };
+    if let Some(l) = label {
+        filter.insert("label", l);
+    }
+
+    filter
+}
@@ -0,0 +1,97 @@
+//! Watch
+//!
+//! Typed events over a MongoDB change stream of a category.
+
+use std::pin::Pin;
+use std::task::{ready, Context, Poll};
+
+use mongodb::bson::{self, Document};
+use mongodb::change_stream::event::{ChangeStreamEvent, OperationType, ResumeToken};
+use mongodb::change_stream::ChangeStream;
+use tokio_stream::Stream;
+
+use super::super::model::{GraphEvent, Operation};
+use super::super::Pyo3MongoResult;
+
+/// `Stream` of `GraphEvent`, see `MongoStore::watch`.
+///
+/// Events other than insertions, updates, replacements & deletions (e.g. a dropped
+/// collection) are skipped.
+pub struct GraphEventStream {
+    inner: ChangeStream<ChangeStreamEvent<Document>>,
+    // name of `${cat}_vertex`, events of the other collection are edges
+    vertex_collection: String,
+}
+
+impl GraphEventStream {
+    pub(crate) fn new(
+        inner: ChangeStream<ChangeStreamEvent<Document>>,
+        vertex_collection: String,
+    ) -> Self {
+        GraphEventStream {
+            inner,
+            vertex_collection,
+        }
+    }
+
+    /// token of the last event read, to be given to `watch` in order to restart the
+    /// stream right after it
+    pub fn resume_token(&self) -> Option<ResumeToken> {
+        self.inner.resume_token()
+    }
+
+    fn to_event(&self, change: ChangeStreamEvent<Document>) -> Pyo3MongoResult<Option<GraphEvent>> {
+        let operation = match change.operation_type {
+            OperationType::Insert => Operation::Created,
+            OperationType::Update | OperationType::Replace => Operation::Updated,
+            OperationType::Delete => Operation::Deleted,
+            _ => return Ok(None),
+        };
+        let id = match change
+            .document_key
+            .as_ref()
+            .and_then(|k| k.get_object_id("_id").ok())
+        {
+            Some(id) => id,
+            None => return Ok(None),
+        };
+        let is_vertex = change
+            .ns
+            .as_ref()
+            .and_then(|ns| ns.coll.as_deref())
+            .is_some_and(|c| c == self.vertex_collection);
+
+        let event = if is_vertex {
+            GraphEvent::Vertex {
+                operation,
+                id,
+                vertex: change.full_document.map(bson::from_document).transpose()?,
+            }
+        } else {
+            GraphEvent::Edge {
+                operation,
+                id,
+                edge: change.full_document.map(bson::from_document).transpose()?,
+            }
+        };
+
+        Ok(Some(event))
+    }
+}
+
+impl Stream for GraphEventStream {
+    type Item = Pyo3MongoResult<GraphEvent>;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        loop {
+            let change = match ready!(Pin::new(&mut self.inner).poll_next(cx)) {
+                Some(Ok(c)) => c,
+                Some(Err(e)) => return Poll::Ready(Some(Err(e.into()))),
+                None => return Poll::Ready(None),
+            };
+            if let Some(event) = self.to_event(change).transpose() {
+                return Poll::Ready(Some(event));
+            }
+        }
+    }
+}
@@ -0,0 +1,301 @@
+//! Traversal
+//!
+//! In-memory path finding over a set of edges, usually the result of a `$graphLookup`.
+//! Edges are directed: `source -> target`.
+
+use std::cmp::Ordering;
+use std::collections::hash_map::Entry;
+use std::collections::{BinaryHeap, HashMap, VecDeque};
+
+use mongodb::bson::oid::ObjectId;
+
+use super::model::Edge;
+use super::{Pyo3MongoError, Pyo3MongoResult};
+
+/// an edge without weight costs 1.0
+pub fn edge_weight(edge: &Edge) -> f64 {
+    edge.weight.unwrap_or(1.0)
+}
+
+// source -> outgoing edges
+fn adjacency(edges: &[Edge]) -> HashMap<ObjectId, Vec<&Edge>> {
+    let mut adj: HashMap<ObjectId, Vec<&Edge>> = HashMap::new();
+    for e in edges {
+        adj.entry(e.source).or_default().push(e);
+    }
+    adj
+}
+
+/// fewest edges from `from` to `to`, with no more than `max_hops` edges.
+///
+/// `Some(vec![])` is returned when `from == to`.
+pub fn shortest_path_unweighted(
+    edges: &[Edge],
+    from: ObjectId,
+    to: ObjectId,
+    max_hops: Option<u32>,
+) -> Option<Vec<Edge>> {
+    let adj = adjacency(edges);
+    // vertex -> (hop, the edge reaching it)
+    let mut visited: HashMap<ObjectId, (u32, Option<&Edge>)> = HashMap::new();
+    let mut queue = VecDeque::new();
+
+    visited.insert(from, (0, None));
+    queue.push_back(from);
+
+    while let Some(v) = queue.pop_front() {
+        if v == to {
+            break;
+        }
+        let hop = visited[&v].0;
+        if max_hops.is_some_and(|m| hop >= m) {
+            continue;
+        }
+        for e in adj.get(&v).into_iter().flatten() {
+            if let Entry::Vacant(entry) = visited.entry(e.target) {
+                entry.insert((hop + 1, Some(*e)));
+                queue.push_back(e.target);
+            }
+        }
+    }
+
+    visited.contains_key(&to).then(|| {
+        let mut path = Vec::new();
+        let mut cur = to;
+        while let Some((_, Some(e))) = visited.get(&cur) {
+            path.push((*e).clone());
+            cur = e.source;
+        }
+        path.reverse();
+        path
+    })
+}
+
+// min-heap entry of Dijkstra
+struct State {
+    cost: f64,
+    vertex: ObjectId,
+}
+
+impl PartialEq for State {
+    fn eq(&self, other: &Self) -> bool {
+        self.cost.total_cmp(&other.cost) == Ordering::Equal
+    }
+}
+
+impl Eq for State {}
+
+impl PartialOrd for State {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for State {
+    // reversed, `BinaryHeap` is a max-heap
+    fn cmp(&self, other: &Self) -> Ordering {
+        other.cost.total_cmp(&self.cost)
+    }
+}
+
+/// lowest total weight from `from` to `to`, see `edge_weight`.
+///
+/// Without `max_hops` this is Dijkstra; with it, a Bellman-Ford limited to `max_hops`
+/// rounds, since the cheapest path may not be the one with the fewest edges.
+/// Negative weights are rejected.
+pub fn shortest_path_weighted(
+    edges: &[Edge],
+    from: ObjectId,
+    to: ObjectId,
+    max_hops: Option<u32>,
+) -> Pyo3MongoResult<Option<(Vec<Edge>, f64)>> {
+    if edges.iter().any(|e| edge_weight(e) < 0.0) {
+        return Err(Pyo3MongoError::Common("negative edge weight"));
+    }
+
+    let res = match max_hops {
+        None => dijkstra(edges, from, to),
+        Some(m) => bellman_ford(edges, from, to, m),
+    };
+
+    Ok(res)
+}
+
+fn dijkstra(edges: &[Edge], from: ObjectId, to: ObjectId) -> Option<(Vec<Edge>, f64)> {
+    let adj = adjacency(edges);
+    // vertex -> (cost, the edge reaching it)
+    let mut best: HashMap<ObjectId, (f64, Option<&Edge>)> = HashMap::new();
+    let mut heap = BinaryHeap::new();
+
+    best.insert(from, (0.0, None));
+    heap.push(State {
+        cost: 0.0,
+        vertex: from,
+    });
+
+    while let Some(State { cost, vertex }) = heap.pop() {
+        if vertex == to {
+            break;
+        }
+        // outdated entry
+        if cost > best[&vertex].0 {
+            continue;
+        }
+        for e in adj.get(&vertex).into_iter().flatten() {
+            let next = cost + edge_weight(e);
+            if best.get(&e.target).is_none_or(|(c, _)| next < *c) {
+                best.insert(e.target, (next, Some(*e)));
+                heap.push(State {
+                    cost: next,
+                    vertex: e.target,
+                });
+            }
+        }
+    }
+
+    let cost = best.get(&to)?.0;
+    let mut path = Vec::new();
+    let mut cur = to;
+    while let Some((_, Some(e))) = best.get(&cur) {
+        path.push((*e).clone());
+        cur = e.source;
+    }
+    path.reverse();
+
+    Some((path, cost))
+}
+
+fn bellman_ford(
+    edges: &[Edge],
+    from: ObjectId,
+    to: ObjectId,
+    max_hops: u32,
+) -> Option<(Vec<Edge>, f64)> {
+    // rounds[k]: vertex -> (cost, index of the edge reaching it in round k).
+    // A vertex only appears in round k if its cost has been improved in round k,
+    // otherwise its cost is the one from a previous round.
+    let mut rounds: Vec<HashMap<ObjectId, (f64, usize)>> = Vec::new();
+    let mut best: HashMap<ObjectId, f64> = HashMap::from([(from, 0.0)]);
+
+    for _ in 0..max_hops {
+        let mut improved: HashMap<ObjectId, (f64, usize)> = HashMap::new();
+        for (idx, e) in edges.iter().enumerate() {
+            // relax from costs of the previous round only, so that a path never
+            // uses more edges than rounds
+            let Some(base) = best.get(&e.source) else {
+                continue;
+            };
+            let next = base + edge_weight(e);
+            let current = improved
+                .get(&e.target)
+                .map(|(c, _)| *c)
+                .or_else(|| best.get(&e.target).copied());
+            if current.is_none_or(|c| next < c) {
+                improved.insert(e.target, (next, idx));
+            }
+        }
+        if improved.is_empty() {
+            break;
+        }
+        for (v, (c, _)) in improved.iter() {
+            best.insert(*v, *c);
+        }
+        rounds.push(improved);
+    }
+
+    let cost = *best.get(&to)?;
+
+    // walk back: find the latest round improving the current vertex, take its edge
+    // and continue with the source of that edge in earlier rounds
+    // (`from` costs 0 and weights are non-negative, so it is never improved)
+    let mut path = Vec::new();
+    let mut cur = to;
+    let mut round = rounds.len();
+    while cur != from {
+        let k = (0..round).rev().find(|k| rounds[*k].contains_key(&cur))?;
+        let e = &edges[rounds[k][&cur].1];
+        path.push(e.clone());
+        cur = e.source;
+        round = k;
+    }
+    path.reverse();
+
+    Some((path, cost))
+}
+
+#[cfg(test)]
+mod test_traversal {
+    use super::*;
+
+    fn edge(source: ObjectId, target: ObjectId, weight: Option<f64>) -> Edge {
+        Edge {
+            id: Some(ObjectId::new()),
+            source,
+            target,
+            weight,
+            label: None,
+            properties: Default::default(),
+        }
+    }
+
+    // n0 -> n1 -> n2 -> n3 (weights 1, 1, 1)
+    // n0 -> n3 (weight 10)
+    fn graph() -> (Vec<ObjectId>, Vec<Edge>) {
+        let n = (0..4).map(|_| ObjectId::new()).collect::<Vec<_>>();
+        let edges = vec![
+            edge(n[0], n[1], Some(1.0)),
+            edge(n[1], n[2], None),
+            edge(n[2], n[3], Some(1.0)),
+            edge(n[0], n[3], Some(10.0)),
+        ];
+        (n, edges)
+    }
+
+    #[test]
+    fn test_unweighted() {
+        let (n, edges) = graph();
+
+        let path = shortest_path_unweighted(&edges, n[0], n[3], None).unwrap();
+        assert_eq!(path, vec![edges[3].clone()]);
+
+        let path = shortest_path_unweighted(&edges, n[0], n[0], None).unwrap();
+        assert!(path.is_empty());
+
+        // reversed direction is unreachable
+        assert!(shortest_path_unweighted(&edges, n[3], n[0], None).is_none());
+        // n2 needs two hops
+        assert!(shortest_path_unweighted(&edges, n[0], n[2], Some(1)).is_none());
+    }
+
+    #[test]
+    fn test_weighted() {
+        let (n, edges) = graph();
+
+        let (path, cost) = shortest_path_weighted(&edges, n[0], n[3], None)
+            .unwrap()
+            .unwrap();
+        assert_eq!(path, edges[0..3].to_vec());
+        assert_eq!(cost, 3.0);
+
+        // only 2 hops allowed, the expensive shortcut is the only choice
+        let (path, cost) = shortest_path_weighted(&edges, n[0], n[3], Some(2))
+            .unwrap()
+            .unwrap();
+        assert_eq!(path, vec![edges[3].clone()]);
+        assert_eq!(cost, 10.0);
+
+        let (path, cost) = shortest_path_weighted(&edges, n[0], n[3], Some(3))
+            .unwrap()
+            .unwrap();
+        assert_eq!(path.len(), 3);
+        assert_eq!(cost, 3.0);
+    }
+
+    #[test]
+    fn test_negative_weight() {
+        let (n, mut edges) = graph();
+        edges.push(edge(n[3], n[0], Some(-1.0)));
+
+        assert!(shortest_path_weighted(&edges, n[0], n[3], None).is_err());
+    }
+}
@@ -0,0 +1,348 @@
+//! Avro
+//!
+//! Datagrid in Avro files of many blocks, read and written block by block.
+//!
+//! A reader schema may differ from the schema the file was written with, resolved by name as
+//! the Avro specification does:
+//! - fields only in the file are skipped
+//! - fields only in the reader schema are null, they must be nullable
+//! - numbers are promoted, `int` to `long`, `float` or `double`, `long` to `float` or `double`,
+//!   `float` to `double`, and `string` and `bytes` are interchangeable
+
+use std::io::{Read, Write};
+
+use anyhow::{anyhow, Error, Result};
+use arrow2::array::{new_null_array, Array};
+use arrow2::compute::cast::{cast, CastOptions};
+use arrow2::datatypes::{DataType, Field, Schema};
+use arrow2::io::avro::avro_schema;
+use arrow2::io::avro::avro_schema::file::{Block, CompressedBlock, Compression};
+use arrow2::io::avro::read as avro_read;
+use arrow2::io::avro::write as avro_write;
+
+use crate::datagrid::Datagrid;
+
+/// rows of a block by default
+pub const DEFAULT_BLOCK_SIZE: usize = 10_000;
+
+/// Writer of Avro blocks, `block_size` rows each.
+///
+/// The header is written on creation, so a file without any grid is still valid.
+pub struct AvroWriter<W: Write> {
+    writer: W,
+    schema: Schema,
+    record: avro_schema::schema::Record,
+    compression: Option<Compression>,
+    block_size: usize,
+}
+
+impl<W: Write> AvroWriter<W> {
+    /// blocks are compressed by `Deflate` or `Snappy` if given, the only codecs of arrow2,
+    /// `zstandard`, `bzip2` and `xz` of the Avro specification cannot be written
+    pub fn try_new(
+        mut writer: W,
+        schema: Schema,
+        compression: Option<Compression>,
+        block_size: usize,
+    ) -> Result<Self> {
+        if block_size == 0 {
+            return Err(anyhow!("block_size must be positive"));
+        }
+
+        let record = avro_write::to_record(&schema)?;
+        avro_schema::write::write_metadata(&mut writer, record.clone(), compression)
+            .map_err(Error::msg)?;
+
+        Ok(Self {
+            writer,
+            schema,
+            record,
+            compression,
+            block_size,
+        })
+    }
+
+    pub fn schema(&self) -> &Schema {
+        &self.schema
+    }
+
+    pub fn write(&mut self, datagrid: &Datagrid) -> Result<()> {
+        let arrays = datagrid.arrays();
+        let (fl, al) = (self.schema.fields.len(), arrays.len());
+        if fl != al {
+            return Err(anyhow!(
+                "length does not match: fields.len {fl} & arrays.len {al}"
+            ));
+        }
+        // serializers are made for the types of the schema
+        if let Some((f, a)) = self
+            .schema
+            .fields
+            .iter()
+            .zip(arrays.iter())
+            .find(|(f, a)| f.data_type() != a.data_type())
+        {
+            return Err(anyhow!(
+                "column {}: array of {:?} for a field of {:?}",
+                f.name,
+                a.data_type(),
+                f.data_type()
+            ));
+        }
+
+        let mut block = Block::new(0, vec![]);
+        let mut compressed_block = CompressedBlock::default();
+
+        for offset in (0..datagrid.len()).step_by(self.block_size) {
+            let length = self.block_size.min(datagrid.len() - offset);
+            let sliced = arrays
+                .iter()
+                .map(|a| a.sliced(offset, length))
+                .collect::<Vec<_>>();
+
+            let mut serializers = sliced
+                .iter()
+                .zip(self.record.fields.iter())
+                .map(|(array, field)| avro_write::new_serializer(array.as_ref(), &field.schema))
+                .collect::<Vec<_>>();
+            block.number_of_rows = length;
+            avro_write::serialize(&mut serializers, &mut block);
+
+            avro_schema::write::compress(&mut block, &mut compressed_block, self.compression)
+                .map_err(Error::msg)?;
+            avro_schema::write::write_block(&mut self.writer, &compressed_block)
+                .map_err(Error::msg)?;
+        }
+
+        Ok(())
+    }
+
+    pub fn into_inner(self) -> W {
+        self.writer
+    }
+}
+
+// how a field of the reader schema is made
+enum Resolution {
+    // index of the projected writer field, cast if needed
+    Field(usize, Option<DataType>),
+    Null(DataType),
+}
+
+fn promotable(from: &DataType, to: &DataType) -> bool {
+    use DataType::*;
+
+    matches!(
+        (from, to),
+        (Int32, Int64 | Float32 | Float64)
+            | (Int64, Float32 | Float64)
+            | (Float32, Float64)
+            | (Utf8, Binary)
+            | (Binary, Utf8)
+    )
+}
+
+/// Reader of Avro blocks, an iterator of `Datagrid`, one per block.
+pub struct AvroReader<R: Read> {
+    blocks: avro_read::Reader<R>,
+    schema: Schema,
+    resolutions: Option<Vec<Resolution>>,
+}
+
+impl<R: Read> AvroReader<R> {
+    /// read with the schema of the file
+    pub fn try_new(reader: R) -> Result<Self> {
+        Self::try_new_with_schema(reader, None)
+    }
+
+    /// read with `reader_schema` if given, resolved against the schema of the file
+    pub fn try_new_with_schema(mut reader: R, reader_schema: Option<&Schema>) -> Result<Self> {
+        let metadata = avro_schema::read::read_metadata(&mut reader).map_err(Error::msg)?;
+        let writer_schema = avro_read::infer_schema(&metadata.record)?;
+
+        let (schema, projection, resolutions) = match reader_schema {
+            None => (writer_schema.clone(), None, None),
+            Some(rs) => {
+                let (projection, resolutions) = resolve(&writer_schema.fields, &rs.fields)?;
+                (rs.clone(), Some(projection), Some(resolutions))
+            }
+        };
+
+        let blocks = avro_read::Reader::new(reader, metadata, writer_schema.fields, projection);
+
+        Ok(Self {
+            blocks,
+            schema,
+            resolutions,
+        })
+    }
+
+    /// schema of the grids read
+    pub fn schema(&self) -> &Schema {
+        &self.schema
+    }
+
+    fn resolve(&self, arrays: Vec<Box<dyn Array>>) -> Result<Datagrid> {
+        let resolutions = match &self.resolutions {
+            Some(r) => r,
+            None => return Datagrid::try_new(self.schema.clone(), arrays),
+        };
+
+        let len = arrays.first().map(|a| a.len()).unwrap_or_default();
+        let arrays = resolutions
+            .iter()
+            .map(|r| match r {
+                Resolution::Field(i, None) => Ok(arrays[*i].clone()),
+                Resolution::Field(i, Some(dt)) => {
+                    Ok(cast(arrays[*i].as_ref(), dt, CastOptions::default())?)
+                }
+                Resolution::Null(dt) => Ok(new_null_array(dt.clone(), len)),
+            })
+            .collect::<Result<Vec<_>>>()?;
+
+        Datagrid::try_new(self.schema.clone(), arrays)
+    }
+}
+
+// projection of the writer fields, and how to make each reader field
+fn resolve(writer: &[Field], reader: &[Field]) -> Result<(Vec<bool>, Vec<Resolution>)> {
+    let projection = writer
+        .iter()
+        .map(|w| reader.iter().any(|r| r.name == w.name))
+        .collect::<Vec<_>>();
+    // index of each writer field among the projected ones
+    let projected = writer
+        .iter()
+        .filter(|w| reader.iter().any(|r| r.name == w.name))
+        .collect::<Vec<_>>();
+    // the rows of a block are only known from its arrays
+    if projected.is_empty() {
+        return Err(anyhow!("no field of the reader schema is in the file"));
+    }
+
+    let resolutions = reader
+        .iter()
+        .map(|r| match projected.iter().position(|w| w.name == r.name) {
+            Some(i) => {
+                let from = projected[i].data_type();
+                if from == r.data_type() {
+                    Ok(Resolution::Field(i, None))
+                } else if promotable(from, r.data_type()) {
+                    Ok(Resolution::Field(i, Some(r.data_type().clone())))
+                } else {
+                    Err(anyhow!(
+                        "field {}: cannot resolve {from:?} to {:?}",
+                        r.name,
+                        r.data_type()
+                    ))
+                }
+            }
+            None if r.is_nullable => Ok(Resolution::Null(r.data_type().clone())),
+            None => Err(anyhow!(
+                "field {}: not in the file and not nullable",
+                r.name
+            )),
+        })
+        .collect::<Result<Vec<_>>>()?;
+
+    Ok((projection, resolutions))
+}
+
+impl<R: Read> Iterator for AvroReader<R> {
+    type Item = Result<Datagrid>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let chunk = self.blocks.next()?;
+        Some(
+            chunk
+                .map_err(Error::msg)
+                .and_then(|c| self.resolve(c.into_arrays())),
+        )
+    }
+}
+
+#[cfg(test)]
+mod test_avro {
+    use std::io::Cursor;
+
+    use arrow2::array::*;
+
+    use super::*;
+
+    fn datagrid() -> (Datagrid, Schema) {
+        let a = Int32Array::from((0..25).map(Some).collect::<Vec<_>>()).boxed();
+        let b = Utf8Array::<i32>::from((0..25).map(|i| Some(i.to_string())).collect::<Vec<_>>())
+            .boxed();
+        let datagrid = Datagrid::try_from_names(&["a", "b"], vec![a, b]).unwrap();
+        let schema = datagrid.schema().clone();
+        (datagrid, schema)
+    }
+
+    #[test]
+    fn blocks_success() {
+        let (datagrid, schema) = datagrid();
+
+        for compression in [None, Some(Compression::Deflate), Some(Compression::Snappy)] {
+            let mut writer = AvroWriter::try_new(vec![], schema.clone(), compression, 10).unwrap();
+            writer.write(&datagrid).unwrap();
+            writer.write(&Datagrid::empty()).unwrap_err();
+            let bytes = writer.into_inner();
+
+            let reader = AvroReader::try_new(Cursor::new(bytes)).unwrap();
+            assert_eq!(reader.schema(), &schema);
+            let lens = reader.map(|d| d.unwrap().len()).collect::<Vec<_>>();
+            assert_eq!(lens, vec![10, 10, 5]);
+        }
+    }
+
+    #[test]
+    fn mismatch_failure() {
+        let (_, schema) = datagrid();
+        let a = Int64Array::from_slice([1, 2]).boxed();
+        let b = Utf8Array::<i32>::from_slice(["1", "2"]).boxed();
+        let datagrid = Datagrid::try_from_names(&["a", "b"], vec![a, b]).unwrap();
+
+        let mut writer = AvroWriter::try_new(vec![], schema, None, 10).unwrap();
+        let err = writer.write(&datagrid).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "column a: array of Int64 for a field of Int32"
+        );
+    }
+
+    #[test]
+    fn empty_success() {
+        let (_, schema) = datagrid();
+        let empty = Datagrid::new_empty(schema.clone());
+
+        let mut writer = AvroWriter::try_new(vec![], schema, None, 10).unwrap();
+        writer.write(&empty).unwrap();
+        let bytes = writer.into_inner();
+
+        let reader = AvroReader::try_new(Cursor::new(bytes)).unwrap();
+        assert_eq!(reader.count(), 0);
+    }
+
+    #[test]
+    fn evolution_success() {
+        let (datagrid, schema) = datagrid();
+        let mut writer = AvroWriter::try_new(vec![], schema, None, 100).unwrap();
+        writer.write(&datagrid).unwrap();
+        let bytes = writer.into_inner();
+
+        let reader_schema = Schema::from(vec![
+            Field::new("c", DataType::Boolean, true),
+            Field::new("a", DataType::Float64, false),
+        ]);
+        let mut reader =
+            AvroReader::try_new_with_schema(Cursor::new(bytes.clone()), Some(&reader_schema))
+                .unwrap();
+        let grid = reader.next().unwrap().unwrap();
+        assert_eq!(grid.arrays()[0].null_count(), 25);
+        assert_eq!(grid.arrays()[1].data_type(), &DataType::Float64);
+        assert!(reader.next().is_none());
+
+        let invalid = Schema::from(vec![Field::new("c", DataType::Boolean, false)]);
+        assert!(AvroReader::try_new_with_schema(Cursor::new(bytes), Some(&invalid)).is_err());
+    }
+}
@@ -0,0 +1,471 @@
+//! Datagrid file CLI
+//!
+//! ```sh
+//! datagrid convert data.csv data.parquet
+//! datagrid convert data.avro data.out --to ndjson
+//! datagrid --delimiter ';' schema data.csv
+//! datagrid head data.arrow -n 5
+//! datagrid count data.jsonl
+//! datagrid stats data.parquet
+//! ```
+
+use std::fs::File;
+use std::io::{BufReader, BufWriter, Write};
+use std::path::Path;
+
+use anyhow::{anyhow, Result};
+use arrow2::array::{get_display, Array, UInt64Array, Utf8Array};
+use arrow2::chunk::Chunk;
+use arrow2::compute::sort::{sort_to_indices, SortOptions};
+use arrow2::datatypes::Schema;
+use arrow2::io::parquet::write::CompressionOptions;
+use arrow2::io::print;
+use clap::{ArgEnum, Parser, Subcommand};
+use ipc_rs::read_ipc::read_chunks;
+use ipc_rs::write_ipc::write_batches;
+use sqlx_arrow2::avro::{AvroReader, AvroWriter, DEFAULT_BLOCK_SIZE};
+use sqlx_arrow2::csv::{CsvReadOptions, CsvReader, CsvWriteOptions, CsvWriter};
+use sqlx_arrow2::datagrid::Datagrid;
+use sqlx_arrow2::ndjson::{NdjsonReader, NdjsonWriter};
+use sqlx_arrow2::parquet::{ParquetReadOptions, ParquetReader, ParquetWriteOptions, ParquetWriter};
+
+#[derive(Parser, Debug)]
+#[clap(about, version, author)]
+struct Args {
+    /// delimiter of CSV files, read and written
+    #[clap(short, long, default_value = ",", parse(try_from_str = parse_delimiter))]
+    delimiter: u8,
+
+    #[clap(subcommand)]
+    command: Command,
+}
+
+#[derive(Subcommand, Debug)]
+enum Command {
+    /// convert a file to another format
+    Convert {
+        input: String,
+
+        output: String,
+
+        /// guessed by the extension of `input` if absent
+        #[clap(long, arg_enum)]
+        from: Option<Format>,
+
+        /// guessed by the extension of `output` if absent
+        #[clap(long, arg_enum)]
+        to: Option<Format>,
+    },
+
+    /// names, types and nullability of the columns
+    Schema {
+        input: String,
+
+        #[clap(long, arg_enum)]
+        from: Option<Format>,
+    },
+
+    /// first rows as a table
+    Head {
+        input: String,
+
+        #[clap(short = 'n', long, default_value = "10")]
+        rows: usize,
+
+        #[clap(long, arg_enum)]
+        from: Option<Format>,
+    },
+
+    /// number of rows
+    Count {
+        input: String,
+
+        #[clap(long, arg_enum)]
+        from: Option<Format>,
+    },
+
+    /// nulls, min & max of each column
+    Stats {
+        input: String,
+
+        #[clap(long, arg_enum)]
+        from: Option<Format>,
+    },
+}
+
+/// file formats, see `Format::from_path` for their extensions
+#[derive(ArgEnum, Clone, Copy, Debug, PartialEq, Eq)]
+enum Format {
+    Avro,
+    Parquet,
+    /// Arrow IPC file
+    Ipc,
+    Csv,
+    /// newline delimited JSON
+    Ndjson,
+}
+
+impl Format {
+    fn from_path(path: &str) -> Option<Self> {
+        let extension = Path::new(path).extension()?.to_str()?.to_lowercase();
+        match extension.as_str() {
+            "avro" => Some(Format::Avro),
+            "parquet" | "pq" => Some(Format::Parquet),
+            "ipc" | "arrow" | "feather" => Some(Format::Ipc),
+            "csv" => Some(Format::Csv),
+            "ndjson" | "jsonl" => Some(Format::Ndjson),
+            _ => None,
+        }
+    }
+}
+
+fn parse_delimiter(s: &str) -> Result<u8, String> {
+    match s.as_bytes() {
+        [b] if b.is_ascii() => Ok(*b),
+        _ => Err("delimiter should be a single ASCII character".to_owned()),
+    }
+}
+
+fn format(path: &str, format: Option<Format>) -> Result<Format> {
+    format
+        .or_else(|| Format::from_path(path))
+        .ok_or_else(|| anyhow!("unknown format of {path}, see --from & --to"))
+}
+
+type Grids = Box<dyn Iterator<Item = Result<Datagrid>>>;
+
+// grids of a file read one after another, at most `limit` rows of Parquet
+fn grids(
+    path: &str,
+    format: Format,
+    delimiter: u8,
+    limit: Option<usize>,
+) -> Result<(Schema, Grids)> {
+    let file = || -> Result<BufReader<File>> { Ok(BufReader::new(File::open(path)?)) };
+
+    Ok(match format {
+        Format::Avro => {
+            let reader = AvroReader::try_new(file()?)?;
+            (reader.schema().clone(), Box::new(reader))
+        }
+        Format::Parquet => {
+            let options = ParquetReadOptions {
+                limit,
+                ..Default::default()
+            };
+            let reader = ParquetReader::try_new(file()?, options)?;
+            (reader.schema().clone(), Box::new(reader))
+        }
+        // `ipc_rs` reads all the batches of a file at once
+        Format::Ipc => {
+            let (schema, chunks) = read_chunks(path)?;
+            let s = schema.clone();
+            let grids = chunks
+                .into_iter()
+                .map(move |c| Datagrid::try_new(s.clone(), c.into_arrays()));
+            (schema, Box::new(grids))
+        }
+        Format::Csv => {
+            let options = CsvReadOptions {
+                delimiter,
+                ..Default::default()
+            };
+            let reader = CsvReader::try_new(file()?, options)?;
+            (reader.schema().clone(), Box::new(reader))
+        }
+        Format::Ndjson => {
+            let reader = NdjsonReader::try_new(file()?, Default::default())?;
+            (reader.schema().clone(), Box::new(reader))
+        }
+    })
+}
+
+fn concat(schema: Schema, grids: &[Datagrid]) -> Result<Datagrid> {
+    match grids.len() {
+        0 => Ok(Datagrid::new_empty(schema)),
+        _ => Datagrid::concat(grids),
+    }
+}
+
+fn read(path: &str, format: Format, delimiter: u8) -> Result<Datagrid> {
+    let (schema, grids) = grids(path, format, delimiter, None)?;
+
+    concat(schema, &grids.collect::<Result<Vec<_>>>()?)
+}
+
+// first `rows` rows, the grids after them are not read
+fn head(path: &str, format: Format, delimiter: u8, rows: usize) -> Result<Datagrid> {
+    let (schema, grids) = grids(path, format, delimiter, Some(rows))?;
+
+    let mut head = vec![];
+    let mut len = 0;
+    for grid in grids {
+        if len >= rows {
+            break;
+        }
+        let grid = grid?.slice(0, rows - len)?;
+        len += grid.len();
+        head.push(grid);
+    }
+
+    concat(schema, &head)
+}
+
+// rows counted grid by grid
+fn count(path: &str, format: Format, delimiter: u8) -> Result<usize> {
+    let (_, grids) = grids(path, format, delimiter, None)?;
+
+    grids.map(|g| g.map(|g| g.len())).sum()
+}
+
+// buffered writes to a file created at `path`
+fn write_file<F, T>(path: &str, f: F) -> Result<T>
+where
+    F: FnOnce(&mut BufWriter<File>) -> Result<T>,
+{
+    let mut writer = BufWriter::new(File::create(path)?);
+    let res = f(&mut writer)?;
+    writer.flush()?;
+
+    Ok(res)
+}
+
+// `f` called on each grid, the rows of all grids are returned
+fn for_each_grid<F>(grids: Grids, mut f: F) -> Result<usize>
+where
+    F: FnMut(&Datagrid) -> Result<()>,
+{
+    let mut rows = 0;
+    for grid in grids {
+        let grid = grid?;
+        f(&grid)?;
+        rows += grid.len();
+    }
+
+    Ok(rows)
+}
+
+// grids written as they are read, the rows written are returned
+fn write(schema: Schema, grids: Grids, path: &str, format: Format, delimiter: u8) -> Result<usize> {
+    match format {
+        Format::Avro => write_file(path, |w| {
+            let mut writer = AvroWriter::try_new(w, schema, None, DEFAULT_BLOCK_SIZE)?;
+            for_each_grid(grids, |g| writer.write(g))
+        }),
+        Format::Parquet => write_file(path, |w| {
+            let options = ParquetWriteOptions {
+                compression: CompressionOptions::Snappy,
+                ..Default::default()
+            };
+            let mut writer = ParquetWriter::try_new(w, &schema, options)?;
+            let rows = for_each_grid(grids, |g| writer.write(g))?;
+            writer.finish()?;
+            Ok(rows)
+        }),
+        // `ipc_rs` writes all the batches of a file at once
+        Format::Ipc => {
+            let mut chunks = vec![];
+            let rows = for_each_grid(grids, |g| {
+                chunks.push(Chunk::new(g.arrays().to_vec()));
+                Ok(())
+            })?;
+            write_batches(path, schema, &chunks)?;
+            Ok(rows)
+        }
+        Format::Csv => write_file(path, |w| {
+            let options = CsvWriteOptions {
+                delimiter,
+                ..Default::default()
+            };
+            let mut writer = CsvWriter::try_new(w, schema, options)?;
+            for_each_grid(grids, |g| writer.write(g))
+        }),
+        Format::Ndjson => write_file(path, |w| {
+            let mut writer = NdjsonWriter::new(w, schema);
+            for_each_grid(grids, |g| writer.write(g))
+        }),
+    }
+}
+
+fn table(datagrid: &Datagrid) -> String {
+    print::write(&[Chunk::new(datagrid.arrays().to_vec())], &datagrid.names())
+}
+
+// smallest value if ascending, largest if descending, empty if the type can't be sorted
+fn extreme(array: &dyn Array, descending: bool) -> String {
+    let options = SortOptions {
+        descending,
+        nulls_first: false,
+    };
+
+    let mut value = String::new();
+    if let Ok(indices) = sort_to_indices::<u64>(array, &options, Some(1)) {
+        if let Some(i) = indices.iter().flatten().next() {
+            let _ = get_display(array, "null")(&mut value, *i as usize);
+        }
+    }
+    value
+}
+
+fn stats(datagrid: &Datagrid) -> Result<Datagrid> {
+    let fields = &datagrid.schema().fields;
+    let names = fields.iter().map(|f| Some(f.name.clone()));
+    let types = fields.iter().map(|f| Some(format!("{:?}", f.data_type())));
+    let nulls = datagrid
+        .arrays()
+        .iter()
+        .map(|a| Some(a.null_count() as u64));
+    let mins = datagrid
+        .arrays()
+        .iter()
+        .map(|a| Some(extreme(a.as_ref(), false)));
+    let maxs = datagrid
+        .arrays()
+        .iter()
+        .map(|a| Some(extreme(a.as_ref(), true)));
+
+    Datagrid::try_from_names(
+        &["column", "type", "nulls", "min", "max"],
+        vec![
+            Utf8Array::<i32>::from_iter(names).boxed(),
+            Utf8Array::<i32>::from_iter(types).boxed(),
+            UInt64Array::from_iter(nulls).boxed(),
+            Utf8Array::<i32>::from_iter(mins).boxed(),
+            Utf8Array::<i32>::from_iter(maxs).boxed(),
+        ],
+    )
+}
+
+fn main() -> Result<()> {
+    let args = Args::parse();
+    let delimiter = args.delimiter;
+
+    match args.command {
+        Command::Convert {
+            input,
+            output,
+            from,
+            to,
+        } => {
+            let (from, to) = (format(&input, from)?, format(&output, to)?);
+            let (schema, grids) = grids(&input, from, delimiter, None)?;
+            let rows = write(schema, grids, &output, to, delimiter)?;
+            println!("converted {rows} rows from {from:?} to {to:?}");
+        }
+        // only the schema of the reader, no grid is read
+        Command::Schema { input, from } => {
+            let (schema, _) = grids(&input, format(&input, from)?, delimiter, None)?;
+            for f in &schema.fields {
+                let nullable = if f.is_nullable {
+                    "nullable"
+                } else {
+                    "not null"
+                };
+                println!("{}: {:?}, {nullable}", f.name, f.data_type());
+            }
+        }
+        Command::Head { input, rows, from } => {
+            let datagrid = head(&input, format(&input, from)?, delimiter, rows)?;
+            println!("{}", table(&datagrid));
+        }
+        Command::Count { input, from } => {
+            println!("{}", count(&input, format(&input, from)?, delimiter)?);
+        }
+        Command::Stats { input, from } => {
+            let datagrid = read(&input, format(&input, from)?, delimiter)?;
+            println!("rows: {}", datagrid.len());
+            println!("{}", table(&stats(&datagrid)?));
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod test_cli {
+    use arrow2::array::Int32Array;
+    use clap::CommandFactory;
+
+    use super::*;
+
+    #[test]
+    fn test_args() {
+        Args::command().debug_assert();
+
+        let parse = |cmd: &[&str]| Args::try_parse_from([&["datagrid"], cmd].concat());
+        assert!(parse(&["convert", "a.csv", "b.parquet"]).is_ok());
+        assert!(parse(&["convert", "a.csv"]).is_err());
+        assert!(parse(&["convert", "a", "b", "--from", "csv", "--to", "xlsx"]).is_err());
+        assert!(parse(&["-d", ";", "schema", "a.csv"]).is_ok());
+        assert!(parse(&["-d", ";;", "schema", "a.csv"]).is_err());
+        assert!(parse(&["head", "a.arrow", "-n", "5"]).is_ok());
+        assert!(parse(&["count", "a.jsonl", "--from", "ndjson"]).is_ok());
+        assert!(parse(&["stats"]).is_err());
+    }
+
+    #[test]
+    fn test_format() {
+        assert_eq!(Format::from_path("a/b.PARQUET"), Some(Format::Parquet));
+        assert_eq!(Format::from_path("b.feather"), Some(Format::Ipc));
+        assert_eq!(Format::from_path("b.jsonl"), Some(Format::Ndjson));
+        assert_eq!(Format::from_path("b"), None);
+        assert!(format("b.txt", None).is_err());
+        assert_eq!(format("b.txt", Some(Format::Csv)).unwrap(), Format::Csv);
+    }
+
+    #[test]
+    fn test_head_count() {
+        let path = "./cache/test_cli.avro";
+        let a = Int32Array::from((0..25_000).map(Some).collect::<Vec<_>>()).boxed();
+        let datagrid = Datagrid::try_from_names(&["a"], vec![a]).unwrap();
+        write_file(path, |w| datagrid.write_avro(w, None)).unwrap();
+
+        assert_eq!(count(path, Format::Avro, b',').unwrap(), 25_000);
+        assert_eq!(
+            head(path, Format::Avro, b',', 15_000).unwrap().len(),
+            15_000
+        );
+        let empty = head(path, Format::Avro, b',', 0).unwrap();
+        assert_eq!((empty.len(), empty.names()), (0, vec!["a"]));
+    }
+
+    #[test]
+    fn test_convert() {
+        let (avro, csv) = (
+            "./cache/test_cli_convert.avro",
+            "./cache/test_cli_convert.csv",
+        );
+        let a = Int32Array::from((0..25_000).map(Some).collect::<Vec<_>>()).boxed();
+        let datagrid = Datagrid::try_from_names(&["a"], vec![a]).unwrap();
+        write_file(avro, |w| datagrid.write_avro(w, None)).unwrap();
+
+        let (schema, grids) = grids(avro, Format::Avro, b',', None).unwrap();
+        assert_eq!(
+            write(schema, grids, csv, Format::Csv, b';').unwrap(),
+            25_000
+        );
+        assert_eq!(count(csv, Format::Csv, b';').unwrap(), 25_000);
+    }
+
+    #[test]
+    fn test_stats() {
+        let a = Int32Array::from([Some(3), None, Some(1)]).boxed();
+        let b = Utf8Array::<i32>::from([Some("b"), Some("c"), None]).boxed();
+        let datagrid = Datagrid::try_from_names(&["a", "b"], vec![a, b]).unwrap();
+
+        let stats = stats(&datagrid).unwrap();
+        assert_eq!(stats.len(), 2);
+        let column = |name| {
+            stats
+                .column(name)
+                .unwrap()
+                .as_any()
+                .downcast_ref::<Utf8Array<i32>>()
+                .unwrap()
+                .clone()
+        };
+        assert_eq!(column("min").get(0), Some("1"));
+        assert_eq!(column("max").get(0), Some("3"));
+        assert_eq!(column("max").get(1), Some("c"));
+    }
+}
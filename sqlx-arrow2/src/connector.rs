@@ -5,10 +5,15 @@ use std::str::FromStr;
 
 use anyhow::{anyhow, Error, Result};
 use futures::future::BoxFuture;
+use futures::{StreamExt, TryStreamExt};
 use sqlx::mssql::{MssqlPool, MssqlPoolOptions};
 use sqlx::mysql::{MySqlPool, MySqlPoolOptions};
 use sqlx::postgres::{PgPool, PgPoolOptions};
-use sqlx::{Database, FromRow, Mssql, MySql, Pool, Postgres};
+use sqlx::{Database, Executor, FromRow, Mssql, MySql, Pool, Postgres};
+
+use crate::datagrid::Datagrid;
+use crate::insert::{ArrowEncode, InsertMode, InsertPlan};
+use crate::rows::{column_specs, gen_schema, rows_to_datagrid, DatagridStream};
 
 pub enum DB {
     MsSql,
@@ -117,6 +122,30 @@ impl<T: SqlMeta> Connector<T> {
             None => Err(anyhow!(CONN_N_ERR)),
         }
     }
+
+    pub async fn query_datagrid<'a>(
+        &'a self,
+        sql: &'a str,
+        batch_size: usize,
+    ) -> Result<DatagridStream<'a>> {
+        match self.pool_options.as_ref() {
+            Some(p) => p.query_datagrid(sql, batch_size).await,
+            None => Err(anyhow!(CONN_N_ERR)),
+        }
+    }
+
+    pub async fn insert_datagrid<'a>(
+        &'a self,
+        table: &'a str,
+        datagrid: &'a Datagrid,
+        mode: InsertMode,
+        create_table: bool,
+    ) -> Result<u64> {
+        match self.pool_options.as_ref() {
+            Some(p) => p.insert_datagrid(table, datagrid, mode, create_table).await,
+            None => Err(anyhow!(CONN_N_ERR)),
+        }
+    }
 }
 
 pub trait SqlMeta: Sized {
@@ -175,6 +204,22 @@ pub trait SqlMeta: Sized {
         &'a self,
         sql: &'a str,
     ) -> BoxFuture<'a, Result<<Self::DB as Database>::QueryResult>>;
+
+    // query as a stream of `Datagrid`, `batch_size` rows each
+    fn query_datagrid<'a>(
+        &'a self,
+        sql: &'a str,
+        batch_size: usize,
+    ) -> BoxFuture<'a, Result<DatagridStream<'a>>>;
+
+    // insert in a transaction, columns named by the schema of `datagrid`, optionally creating the table
+    fn insert_datagrid<'a>(
+        &'a self,
+        table: &'a str,
+        datagrid: &'a Datagrid,
+        mode: InsertMode,
+        create_table: bool,
+    ) -> BoxFuture<'a, Result<u64>>;
 }
 
 macro_rules! impl_sql_meta {
@@ -270,6 +315,62 @@ macro_rules! impl_sql_meta {
                 let q = async move { sqlx::query(sql).execute(self).await.map_err(Error::msg) };
                 Box::pin(q)
             }
+
+            fn query_datagrid<'a>(
+                &'a self,
+                sql: &'a str,
+                batch_size: usize,
+            ) -> BoxFuture<'a, Result<DatagridStream<'a>>> {
+                let q = async move {
+                    if batch_size == 0 {
+                        return Err(anyhow!("batch_size must be positive"));
+                    }
+
+                    // types are known before the first row, even if there is none
+                    let describe = self.describe(sql).await.map_err(Error::msg)?;
+                    let columns =
+                        column_specs::<$db>(describe.columns(), |i| describe.nullable(i))?;
+                    let schema = gen_schema(&columns);
+
+                    let batches = sqlx::query(sql)
+                        .fetch(self)
+                        .map_err(Error::msg)
+                        .try_chunks(batch_size)
+                        .map(move |rows| {
+                            let rows = rows.map_err(|e| e.1)?;
+                            rows_to_datagrid::<$db>(&columns, &rows)
+                        })
+                        .boxed();
+
+                    Ok(DatagridStream { schema, batches })
+                };
+                Box::pin(q)
+            }
+
+            fn insert_datagrid<'a>(
+                &'a self,
+                table: &'a str,
+                datagrid: &'a Datagrid,
+                mode: InsertMode,
+                create_table: bool,
+            ) -> BoxFuture<'a, Result<u64>> {
+                let q = async move {
+                    let plan = InsertPlan::new::<$db>(table, datagrid.schema(), mode)?;
+
+                    let mut tx = self.begin().await.map_err(Error::msg)?;
+                    for sql in plan.prelude::<$db>(create_table) {
+                        sqlx::query(&sql)
+                            .execute(&mut tx)
+                            .await
+                            .map_err(Error::msg)?;
+                    }
+                    let rows = <$db as ArrowEncode>::insert(&mut tx, &plan, datagrid).await?;
+                    tx.commit().await.map_err(Error::msg)?;
+
+                    Ok(rows)
+                };
+                Box::pin(q)
+            }
         }
     };
 }
@@ -352,4 +453,42 @@ mod test_connector {
 
         assert!(res.is_ok());
     }
+
+    #[tokio::test]
+    async fn query_datagrid_success() {
+        let mut ct = Connector::<PgPool>::new(URL);
+        ct.connect().await.expect("Connection success");
+
+        let sql = "SELECT * FROM users";
+
+        let res = ct.query_datagrid(sql, 2).await.expect("Query success");
+
+        println!("{:?}", res.schema);
+
+        let grids = res.batches.try_collect::<Vec<_>>().await;
+
+        assert!(grids.is_ok());
+    }
+
+    #[tokio::test]
+    async fn insert_datagrid_success() {
+        use arrow2::array::{Int64Array, Utf8Array};
+
+        let mut ct = Connector::<PgPool>::new(URL);
+        ct.connect().await.expect("Connection success");
+
+        let a = Int64Array::from([Some(1), Some(2), Some(3)]).boxed();
+        let b = Utf8Array::<i32>::from([Some("a"), None, Some("c")]).boxed();
+        let datagrid = Datagrid::try_from_names(&["id", "name"], vec![a, b]).unwrap();
+
+        let res = ct
+            .insert_datagrid("datagrid", &datagrid, InsertMode::Replace, true)
+            .await;
+        assert_eq!(res.unwrap(), 3);
+
+        let mode = InsertMode::Upsert(vec!["id".to_owned()]);
+        let res = ct.insert_datagrid("datagrid", &datagrid, mode, false).await;
+
+        assert!(res.is_ok());
+    }
 }
@@ -0,0 +1,417 @@
+//! CSV
+//!
+//! Datagrid in CSV files, read chunk by chunk and written grid by grid.
+//!
+//! Types are inferred from the first rows unless a schema is given: booleans, integers,
+//! floats, dates, times and timestamps, strings otherwise. An empty cell is null, even of strings.
+
+use std::io::{Read, Seek, Write};
+
+use anyhow::{anyhow, Error, Result};
+use arrow2::array::{Array, Utf8Array};
+use arrow2::bitmap::Bitmap;
+use arrow2::chunk::Chunk;
+use arrow2::datatypes::Schema;
+use arrow2::io::csv::read as csv_read;
+use arrow2::io::csv::write as csv_write;
+use arrow2::offset::Offset;
+
+use crate::datagrid::Datagrid;
+
+/// rows read to infer the types by default
+pub const DEFAULT_INFER_ROWS: usize = 1_000;
+
+/// rows of a grid by default
+pub const DEFAULT_CHUNK_SIZE: usize = 10_000;
+
+// ------------------------------------------------------------------------------
+// read
+
+/// options of `CsvReader`
+#[derive(Debug, Clone)]
+pub struct CsvReadOptions {
+    /// the first row names the columns, else they are `column_1`, `column_2`...
+    pub has_header: bool,
+    pub delimiter: u8,
+    pub quote: u8,
+    /// rows read to infer the types, all of them if `None`
+    pub infer_rows: Option<usize>,
+    /// most rows of a grid
+    pub chunk_size: usize,
+}
+
+impl Default for CsvReadOptions {
+    fn default() -> Self {
+        Self {
+            has_header: true,
+            delimiter: b',',
+            quote: b'"',
+            infer_rows: Some(DEFAULT_INFER_ROWS),
+            chunk_size: DEFAULT_CHUNK_SIZE,
+        }
+    }
+}
+
+/// Reader of CSV rows, an iterator of `Datagrid`, `chunk_size` rows each.
+pub struct CsvReader<R: Read> {
+    reader: csv_read::Reader<R>,
+    schema: Schema,
+    // column of the file of each field
+    projection: Vec<usize>,
+    rows: Vec<csv_read::ByteRecord>,
+    // rows read so far, for error messages
+    line: usize,
+}
+
+impl<R: Read + Seek> CsvReader<R> {
+    /// read with the types inferred from the first `infer_rows` rows, all fields nullable
+    pub fn try_new(reader: R, options: CsvReadOptions) -> Result<Self> {
+        let mut reader = reader_builder(&options).from_reader(reader);
+        let start = reader.position().clone();
+        let (fields, _) = csv_read::infer_schema(
+            &mut reader,
+            options.infer_rows,
+            options.has_header,
+            &csv_read::infer,
+        )?;
+        // without a header the first row is data, but `infer_schema` seeks after it
+        if !options.has_header {
+            reader.seek(start)?;
+        }
+        let projection = (0..fields.len()).collect();
+
+        Self::try_new_with_projection(reader, Schema::from(fields), projection, &options)
+    }
+}
+
+impl<R: Read> CsvReader<R> {
+    /// read with `schema`, its fields found by name in the header, else by position
+    pub fn try_new_with_schema(reader: R, options: CsvReadOptions, schema: Schema) -> Result<Self> {
+        let mut reader = reader_builder(&options).from_reader(reader);
+
+        let projection = if options.has_header {
+            let header = reader.headers().map_err(Error::msg)?;
+            schema
+                .fields
+                .iter()
+                .map(|f| {
+                    header
+                        .iter()
+                        .position(|h| h == f.name)
+                        .ok_or_else(|| anyhow!("column {} is not in the header", f.name))
+                })
+                .collect::<Result<Vec<_>>>()?
+        } else {
+            (0..schema.fields.len()).collect()
+        };
+
+        Self::try_new_with_projection(reader, schema, projection, &options)
+    }
+
+    fn try_new_with_projection(
+        reader: csv_read::Reader<R>,
+        schema: Schema,
+        projection: Vec<usize>,
+        options: &CsvReadOptions,
+    ) -> Result<Self> {
+        if options.chunk_size == 0 {
+            return Err(anyhow!("chunk_size must be positive"));
+        }
+
+        Ok(Self {
+            reader,
+            schema,
+            projection,
+            rows: vec![csv_read::ByteRecord::default(); options.chunk_size],
+            line: 0,
+        })
+    }
+
+    /// schema of the grids read
+    pub fn schema(&self) -> &Schema {
+        &self.schema
+    }
+
+    fn read_chunk(&mut self) -> Result<Option<Datagrid>> {
+        let len = csv_read::read_rows(&mut self.reader, 0, &mut self.rows)?;
+        if len == 0 {
+            return Ok(None);
+        }
+
+        let rows = &self.rows[..len];
+        let arrays = self
+            .schema
+            .fields
+            .iter()
+            .zip(self.projection.iter())
+            .map(|(f, c)| {
+                let array =
+                    csv_read::deserialize_column(rows, *c, f.data_type().clone(), self.line)?;
+                Ok(null_empty(array))
+            })
+            .collect::<Result<Vec<_>>>()?;
+        self.line += len;
+
+        Datagrid::try_new(self.schema.clone(), arrays).map(Some)
+    }
+}
+
+// an empty cell of strings is read as an empty string, it is null as in other types
+fn null_empty(array: Box<dyn Array>) -> Box<dyn Array> {
+    fn validity<O: Offset>(array: &Utf8Array<O>) -> Bitmap {
+        array
+            .iter()
+            .map(|v| v.is_some_and(|s| !s.is_empty()))
+            .collect()
+    }
+
+    let any = array.as_any();
+    if let Some(a) = any.downcast_ref::<Utf8Array<i32>>() {
+        a.clone().with_validity(Some(validity(a))).boxed()
+    } else if let Some(a) = any.downcast_ref::<Utf8Array<i64>>() {
+        a.clone().with_validity(Some(validity(a))).boxed()
+    } else {
+        array
+    }
+}
+
+fn reader_builder(options: &CsvReadOptions) -> csv_read::ReaderBuilder {
+    let mut builder = csv_read::ReaderBuilder::new();
+    builder
+        .has_headers(options.has_header)
+        .delimiter(options.delimiter)
+        .quote(options.quote);
+    builder
+}
+
+impl<R: Read> Iterator for CsvReader<R> {
+    type Item = Result<Datagrid>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.read_chunk().transpose()
+    }
+}
+
+// ------------------------------------------------------------------------------
+// write
+
+/// options of `CsvWriter`
+#[derive(Debug, Clone)]
+pub struct CsvWriteOptions {
+    /// write the column names first
+    pub has_header: bool,
+    pub delimiter: u8,
+    pub quote: u8,
+}
+
+impl Default for CsvWriteOptions {
+    fn default() -> Self {
+        Self {
+            has_header: true,
+            delimiter: b',',
+            quote: b'"',
+        }
+    }
+}
+
+/// Writer of CSV rows, dates and times formatted as `chrono` does.
+///
+/// The header is written on creation, so a file without any grid is still valid.
+pub struct CsvWriter<W: Write> {
+    writer: W,
+    schema: Schema,
+    options: csv_write::SerializeOptions,
+}
+
+impl<W: Write> CsvWriter<W> {
+    pub fn try_new(mut writer: W, schema: Schema, options: CsvWriteOptions) -> Result<Self> {
+        let has_header = options.has_header;
+        let options = csv_write::SerializeOptions {
+            delimiter: options.delimiter,
+            quote: options.quote,
+            ..Default::default()
+        };
+        if has_header {
+            write_header(&mut writer, &schema, &options)?;
+        }
+
+        Ok(Self {
+            writer,
+            schema,
+            options,
+        })
+    }
+
+    pub fn schema(&self) -> &Schema {
+        &self.schema
+    }
+
+    pub fn write(&mut self, datagrid: &Datagrid) -> Result<()> {
+        let (fl, al) = (self.schema.fields.len(), datagrid.arrays().len());
+        if fl != al {
+            return Err(anyhow!(
+                "length does not match: fields.len {fl} & arrays.len {al}"
+            ));
+        }
+        if let Some((f, a)) = self
+            .schema
+            .fields
+            .iter()
+            .zip(datagrid.arrays().iter())
+            .find(|(f, a)| f.data_type() != a.data_type())
+        {
+            return Err(anyhow!(
+                "column {}: array of {:?} for a field of {:?}",
+                f.name,
+                a.data_type(),
+                f.data_type()
+            ));
+        }
+
+        let chunk = Chunk::new(datagrid.arrays().to_vec());
+        csv_write::write_chunk(&mut self.writer, &chunk, &self.options)?;
+
+        Ok(())
+    }
+
+    pub fn into_inner(self) -> W {
+        self.writer
+    }
+}
+
+// names quoted as the values are, `csv_write::write_header` does not
+fn write_header<W: Write>(
+    writer: &mut W,
+    schema: &Schema,
+    options: &csv_write::SerializeOptions,
+) -> Result<()> {
+    let (delimiter, quote) = (options.delimiter as char, options.quote as char);
+    let names = schema
+        .fields
+        .iter()
+        .map(|f| {
+            let name = &f.name;
+            if name.contains([delimiter, quote, '\n', '\r']) {
+                let escaped = name.replace(quote, &format!("{quote}{quote}"));
+                format!("{quote}{escaped}{quote}")
+            } else {
+                name.to_owned()
+            }
+        })
+        .collect::<Vec<_>>();
+
+    writeln!(writer, "{}", names.join(&delimiter.to_string()))?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod test_csv {
+    use std::io::Cursor;
+
+    use arrow2::array::*;
+    use arrow2::datatypes::{DataType, Field};
+
+    use super::*;
+
+    fn datagrid() -> Datagrid {
+        let a = Int64Array::from((0..25).map(Some).collect::<Vec<_>>()).boxed();
+        let b = Utf8Array::<i32>::from(
+            (0..25)
+                .map(|i| match i % 5 {
+                    0 => None,
+                    _ => Some(format!("v;\"{i}\"")),
+                })
+                .collect::<Vec<_>>(),
+        )
+        .boxed();
+        Datagrid::try_from_names(&["a", "b;c"], vec![a, b]).unwrap()
+    }
+
+    #[test]
+    fn chunks_success() {
+        let datagrid = datagrid();
+        let options = CsvWriteOptions {
+            delimiter: b';',
+            ..Default::default()
+        };
+
+        let mut writer = CsvWriter::try_new(vec![], datagrid.schema().clone(), options).unwrap();
+        writer.write(&datagrid).unwrap();
+        writer.write(&Datagrid::empty()).unwrap_err();
+        let swapped = Datagrid::try_from_names(
+            &["a", "b;c"],
+            vec![datagrid.arrays()[1].clone(), datagrid.arrays()[0].clone()],
+        )
+        .unwrap();
+        writer.write(&swapped).unwrap_err();
+        let bytes = writer.into_inner();
+
+        let options = CsvReadOptions {
+            delimiter: b';',
+            chunk_size: 10,
+            ..Default::default()
+        };
+        let reader = CsvReader::try_new(Cursor::new(bytes), options).unwrap();
+        assert_eq!(reader.schema().fields[1].name, "b;c");
+        assert_eq!(reader.schema().fields[1].data_type(), &DataType::Utf8);
+
+        let grids = reader.map(|d| d.unwrap()).collect::<Vec<_>>();
+        assert_eq!(
+            grids.iter().map(|g| g.len()).collect::<Vec<_>>(),
+            vec![10, 10, 5]
+        );
+
+        let b = grids[0]
+            .column("b;c")
+            .unwrap()
+            .as_any()
+            .downcast_ref::<Utf8Array<i32>>()
+            .unwrap();
+        assert_eq!(b.get(0), None);
+        assert_eq!(b.get(1), Some("v;\"1\""));
+    }
+
+    #[test]
+    fn header_success() {
+        let datagrid = datagrid();
+        let options = CsvWriteOptions {
+            has_header: false,
+            ..Default::default()
+        };
+
+        let mut writer = CsvWriter::try_new(vec![], datagrid.schema().clone(), options).unwrap();
+        writer.write(&datagrid).unwrap();
+        let bytes = writer.into_inner();
+
+        let options = CsvReadOptions {
+            has_header: false,
+            ..Default::default()
+        };
+        let reader = CsvReader::try_new(Cursor::new(bytes), options).unwrap();
+        assert_eq!(reader.schema().fields[0].name, "column_1");
+        assert_eq!(reader.map(|d| d.unwrap().len()).sum::<usize>(), 25);
+    }
+
+    #[test]
+    fn schema_success() {
+        let bytes = b"a,b,c\n1,x,2020-01-02\n,y,2020-01-03\n".to_vec();
+
+        let schema = Schema::from(vec![
+            Field::new("c", DataType::Date32, false),
+            Field::new("a", DataType::Float64, true),
+        ]);
+        let mut reader =
+            CsvReader::try_new_with_schema(Cursor::new(bytes.clone()), Default::default(), schema)
+                .unwrap();
+        let grid = reader.next().unwrap().unwrap();
+        assert_eq!(grid.names(), vec!["c", "a"]);
+        assert_eq!(grid.column("a").unwrap().null_count(), 1);
+        assert!(reader.next().is_none());
+
+        let invalid = Schema::from(vec![Field::new("d", DataType::Utf8, true)]);
+        assert!(
+            CsvReader::try_new_with_schema(Cursor::new(bytes), Default::default(), invalid)
+                .is_err()
+        );
+    }
+}
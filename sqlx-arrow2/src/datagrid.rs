@@ -1,183 +1,384 @@
 //! Datagrid
+//!
+//! Arrays with their schema, a lightweight table.
 
-use std::io::{Read, Seek, Write};
+use std::io::{BufRead, Read, Seek, Write};
 
 use anyhow::{anyhow, Error, Result};
 use arrow2::array::*;
 use arrow2::chunk::Chunk;
+use arrow2::compute::concatenate::concatenate;
+use arrow2::compute::filter::filter_chunk;
+use arrow2::compute::sort::{lexsort_to_indices, SortColumn, SortOptions};
+use arrow2::compute::take::take;
 use arrow2::datatypes::{Field, Schema};
 use arrow2::io::avro::avro_schema;
-use arrow2::io::avro::read as avro_read;
-use arrow2::io::avro::write as avro_write;
-use arrow2::io::parquet::read as parquet_read;
 use arrow2::io::parquet::write as parquet_write;
 
-pub struct Datagrid(Chunk<Box<dyn Array>>);
+use crate::avro::{AvroReader, AvroWriter, DEFAULT_BLOCK_SIZE};
+use crate::csv::{CsvReadOptions, CsvReader, CsvWriteOptions, CsvWriter};
+use crate::ndjson::{NdjsonReader, NdjsonWriter};
+use crate::parquet::{ParquetReader, ParquetWriteOptions, ParquetWriter};
+
+#[derive(Debug, Clone)]
+pub struct Datagrid {
+    schema: Schema,
+    chunk: Chunk<Box<dyn Array>>,
+}
 
 impl Datagrid {
     pub fn empty() -> Self {
-        Datagrid(Chunk::new(vec![]))
+        Datagrid::new_empty(Schema::default())
     }
 
-    pub fn new(arrays: Vec<Box<dyn Array>>) -> Self {
-        Datagrid(Chunk::new(arrays))
+    /// no row, columns of `schema`
+    pub fn new_empty(schema: Schema) -> Self {
+        let arrays = schema
+            .fields
+            .iter()
+            .map(|f| new_empty_array(f.data_type().clone()))
+            .collect();
+        Datagrid {
+            schema,
+            chunk: Chunk::new(arrays),
+        }
     }
 
-    pub fn try_new(arrays: Vec<Box<dyn Array>>) -> Result<Self> {
+    /// arrays must be of the data types of `schema`, and of the same length
+    pub fn try_new(schema: Schema, arrays: Vec<Box<dyn Array>>) -> Result<Self> {
+        let (fl, al) = (schema.fields.len(), arrays.len());
+        if fl != al {
+            return Err(anyhow!(
+                "length does not match: fields.len {fl} & arrays.len {al}"
+            ));
+        }
+        if let Some((f, a)) = schema
+            .fields
+            .iter()
+            .zip(arrays.iter())
+            .find(|(f, a)| f.data_type() != a.data_type())
+        {
+            return Err(anyhow!(
+                "column {}: array of {:?} for a field of {:?}",
+                f.name,
+                a.data_type(),
+                f.data_type()
+            ));
+        }
+
         let chunk = Chunk::try_new(arrays).map_err(Error::msg)?;
-        Ok(Datagrid(chunk))
+        Ok(Datagrid { schema, chunk })
     }
 
-    pub fn gen_schema(&self, names: &[&str]) -> Result<Schema> {
-        let arrays = self.0.arrays();
-        let al = arrays.len();
-        let nl = names.len();
-        if al != nl {
+    /// fields named by `names`, all nullable as a grid does not tell whether later ones have
+    /// nulls, see `try_new` for a schema of non-nullable fields
+    pub fn try_from_names(names: &[&str], arrays: Vec<Box<dyn Array>>) -> Result<Self> {
+        let (nl, al) = (names.len(), arrays.len());
+        if nl != al {
             return Err(anyhow!(
-                "length does not match: names.len ${nl} & arrays.len ${al}"
+                "length does not match: names.len {nl} & arrays.len {al}"
             ));
         }
 
         let fld = names
             .iter()
-            .zip(arrays)
-            .map(|(n, a)| Field::new(*n, a.data_type().clone(), a.null_count() > 0))
+            .zip(arrays.iter())
+            .map(|(n, a)| Field::new(*n, a.data_type().clone(), true))
             .collect::<Vec<_>>();
 
-        Ok(Schema::from(fld))
+        Datagrid::try_new(Schema::from(fld), arrays)
     }
 
-    pub fn write_avro<W: Write>(
-        &self,
-        writer: &mut W,
-        schema: &Schema,
-        compression: Option<avro_schema::file::Compression>,
-    ) -> Result<()> {
-        let record = avro_write::to_record(schema)?;
-        let arrays = self.0.arrays();
+    pub fn schema(&self) -> &Schema {
+        &self.schema
+    }
+
+    pub fn arrays(&self) -> &[Box<dyn Array>] {
+        self.chunk.arrays()
+    }
+
+    pub fn into_arrays(self) -> Vec<Box<dyn Array>> {
+        self.chunk.into_arrays()
+    }
+
+    /// number of rows
+    pub fn len(&self) -> usize {
+        self.chunk.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.chunk.is_empty()
+    }
+
+    pub fn names(&self) -> Vec<&str> {
+        self.schema.fields.iter().map(|f| f.name.as_str()).collect()
+    }
 
-        let mut serializers = arrays
+    pub fn column_index(&self, name: &str) -> Result<usize> {
+        self.schema
+            .fields
             .iter()
-            .zip(record.fields.iter())
-            .map(|(array, field)| avro_write::new_serializer(array.as_ref(), &field.schema))
-            .collect::<Vec<_>>();
-        let mut block = avro_schema::file::Block::new(arrays[0].as_ref().len(), vec![]);
+            .position(|f| f.name == name)
+            .ok_or_else(|| anyhow!("column {name} not found"))
+    }
 
-        avro_write::serialize(&mut serializers, &mut block);
+    pub fn column(&self, name: &str) -> Result<&dyn Array> {
+        let i = self.column_index(name)?;
+        Ok(self.chunk.arrays()[i].as_ref())
+    }
 
-        let mut compressed_block = avro_schema::file::CompressedBlock::default();
+    /// columns of `names`, in that order
+    pub fn select(&self, names: &[&str]) -> Result<Self> {
+        let indices = names
+            .iter()
+            .map(|n| self.column_index(n))
+            .collect::<Result<Vec<_>>>()?;
 
-        let _was_compressed =
-            avro_schema::write::compress(&mut block, &mut compressed_block, compression)
-                .map_err(Error::msg)?;
+        let fields = indices
+            .iter()
+            .map(|i| self.schema.fields[*i].clone())
+            .collect::<Vec<_>>();
+        let arrays = indices
+            .iter()
+            .map(|i| self.chunk.arrays()[*i].clone())
+            .collect();
 
-        avro_schema::write::write_metadata(writer, record, compression).map_err(Error::msg)?;
+        let schema = Schema::from(fields).with_metadata(self.schema.metadata.clone());
+        Datagrid::try_new(schema, arrays)
+    }
 
-        avro_schema::write::write_block(writer, &compressed_block).map_err(Error::msg)?;
+    pub fn rename(&mut self, from: &str, to: &str) -> Result<()> {
+        let i = self.column_index(from)?;
+        if from != to && self.column_index(to).is_ok() {
+            return Err(anyhow!("column {to} already exists"));
+        }
 
+        self.schema.fields[i].name = to.to_owned();
         Ok(())
     }
 
-    pub fn read_avro<R: Read>(&mut self, reader: &mut R) -> Result<()> {
-        let metadata = avro_schema::read::read_metadata(reader).map_err(Error::msg)?;
+    /// rows from `offset`, `length` at most
+    pub fn slice(&self, offset: usize, length: usize) -> Result<Self> {
+        if offset > self.len() {
+            return Err(anyhow!("offset {offset} out of bounds, len {}", self.len()));
+        }
+        let length = length.min(self.len() - offset);
 
-        let schema = avro_read::infer_schema(&metadata.record)?;
+        let arrays = self
+            .chunk
+            .arrays()
+            .iter()
+            .map(|a| a.sliced(offset, length))
+            .collect();
+        Datagrid::try_new(self.schema.clone(), arrays)
+    }
 
-        let mut blocks = avro_read::Reader::new(reader, metadata, schema.fields, None);
+    /// rows of `grids` one after the other, columns of the same names and types.
+    /// A field is nullable if it is in any grid
+    pub fn concat(grids: &[Datagrid]) -> Result<Self> {
+        let first = grids
+            .first()
+            .ok_or_else(|| anyhow!("no datagrid to concatenate"))?;
+        let mut fields = first.schema.fields.clone();
+
+        for grid in &grids[1..] {
+            let (fl, gl) = (fields.len(), grid.schema.fields.len());
+            if fl != gl {
+                return Err(anyhow!(
+                    "length does not match: fields.len {fl} & fields.len {gl}"
+                ));
+            }
+            for (f, g) in fields.iter_mut().zip(grid.schema.fields.iter()) {
+                if f.name != g.name || f.data_type() != g.data_type() {
+                    return Err(anyhow!(
+                        "schema does not match: {} of {:?} & {} of {:?}",
+                        f.name,
+                        f.data_type(),
+                        g.name,
+                        g.data_type()
+                    ));
+                }
+                f.is_nullable |= g.is_nullable;
+            }
+        }
 
-        if let Some(Ok(c)) = blocks.next() {
-            self.0 = c;
+        let arrays = (0..fields.len())
+            .map(|i| match grids.len() {
+                1 => Ok(first.arrays()[i].clone()),
+                _ => {
+                    let arrays = grids
+                        .iter()
+                        .map(|g| g.arrays()[i].as_ref())
+                        .collect::<Vec<_>>();
+                    concatenate(&arrays).map_err(Error::msg)
+                }
+            })
+            .collect::<Result<Vec<_>>>()?;
+
+        let schema = Schema::from(fields).with_metadata(first.schema.metadata.clone());
+        Datagrid::try_new(schema, arrays)
+    }
+
+    /// rows where `mask` is true, a null is false
+    pub fn filter(&self, mask: &BooleanArray) -> Result<Self> {
+        let (ml, l) = (mask.len(), self.len());
+        if ml != l {
+            return Err(anyhow!("length does not match: mask.len {ml} & len {l}"));
         }
 
+        let chunk = filter_chunk(&self.chunk, mask)?;
+        Datagrid::try_new(self.schema.clone(), chunk.into_arrays())
+    }
+
+    /// rows ordered by columns, the first one first
+    pub fn sort(&self, by: &[(&str, SortOptions)]) -> Result<Self> {
+        let columns = by
+            .iter()
+            .map(|(name, options)| {
+                Ok(SortColumn {
+                    values: self.column(name)?,
+                    options: Some(*options),
+                })
+            })
+            .collect::<Result<Vec<_>>>()?;
+
+        let indices = lexsort_to_indices::<u64>(&columns, None)?;
+        let arrays = self
+            .chunk
+            .arrays()
+            .iter()
+            .map(|a| take(a.as_ref(), &indices))
+            .collect::<arrow2::error::Result<Vec<_>>>()?;
+
+        Datagrid::try_new(self.schema.clone(), arrays)
+    }
+
+    /// write in blocks of `avro::DEFAULT_BLOCK_SIZE` rows, see `AvroWriter` for more
+    pub fn write_avro<W: Write>(
+        &self,
+        writer: &mut W,
+        compression: Option<avro_schema::file::Compression>,
+    ) -> Result<()> {
+        let mut writer =
+            AvroWriter::try_new(writer, self.schema.clone(), compression, DEFAULT_BLOCK_SIZE)?;
+
+        writer.write(self)
+    }
+
+    /// read all blocks, see `AvroReader` to read them one by one
+    pub fn read_avro<R: Read>(&mut self, reader: &mut R) -> Result<()> {
+        let blocks = AvroReader::try_new(reader)?;
+        let schema = blocks.schema().clone();
+
+        *self = Datagrid::try_from_grids(schema, blocks)?;
+
         Ok(())
     }
 
+    /// write with a header if `options.has_header`, see `CsvWriter` for more
+    pub fn write_csv<W: Write>(&self, writer: &mut W, options: CsvWriteOptions) -> Result<()> {
+        let mut writer = CsvWriter::try_new(writer, self.schema.clone(), options)?;
+
+        writer.write(self)
+    }
+
+    /// read all rows, types inferred, see `CsvReader` to read them chunk by chunk
+    pub fn read_csv<R: Read + Seek>(
+        &mut self,
+        reader: &mut R,
+        options: CsvReadOptions,
+    ) -> Result<()> {
+        let chunks = CsvReader::try_new(reader, options)?;
+        let schema = chunks.schema().clone();
+
+        *self = Datagrid::try_from_grids(schema, chunks)?;
+
+        Ok(())
+    }
+
+    /// write in row groups of `parquet::DEFAULT_ROW_GROUP_SIZE` rows, see `ParquetWriter` for more
     pub fn write_parquet<W: Write>(
         &self,
         writer: &mut W,
-        schema: &Schema,
         compression: parquet_write::CompressionOptions,
     ) -> Result<()> {
-        let options = parquet_write::WriteOptions {
-            write_statistics: true,
+        let options = ParquetWriteOptions {
             compression,
-            version: parquet_write::Version::V2,
-            data_pagesize_limit: None,
+            ..Default::default()
         };
+        let mut writer = ParquetWriter::try_new(writer, &self.schema, options)?;
 
-        let iter = vec![Ok(self.0.clone())];
+        writer.write(self)?;
+        writer.finish()?;
 
-        let encodings = schema
-            .fields
-            .iter()
-            .map(|f| parquet_write::transverse(f.data_type(), |_| parquet_write::Encoding::Plain))
-            .collect();
-
-        let row_groups =
-            parquet_write::RowGroupIterator::try_new(iter.into_iter(), schema, options, encodings)?;
-
-        let mut fw = parquet_write::FileWriter::try_new(writer, schema.clone(), options)?;
+        Ok(())
+    }
 
-        for group in row_groups {
-            fw.write(group?)?;
-        }
+    /// read all row groups, see `ParquetReader` to read them chunk by chunk
+    pub fn read_parquet<R: Read + Seek>(&mut self, reader: &mut R) -> Result<()> {
+        let chunks = ParquetReader::try_new(reader, Default::default())?;
+        let schema = chunks.schema().clone();
 
-        let _size = fw.end(None)?;
+        *self = Datagrid::try_from_grids(schema, chunks)?;
 
         Ok(())
     }
 
-    pub fn read_parquet<R: Read + Seek>(&mut self, reader: &mut R) -> Result<()> {
-        let metadata = parquet_read::read_metadata(reader)?;
+    /// write as lines of objects, see `NdjsonWriter`
+    pub fn write_ndjson<W: Write>(&self, writer: &mut W) -> Result<()> {
+        let mut writer = NdjsonWriter::new(writer, self.schema.clone());
 
-        let schema = parquet_read::infer_schema(&metadata)?;
+        writer.write(self)
+    }
 
-        for field in &schema.fields {
-            let _statistics = parquet_read::statistics::deserialize(field, &metadata.row_groups)?;
-        }
+    /// read all rows, types inferred, see `NdjsonReader` to read them chunk by chunk
+    pub fn read_ndjson<R: BufRead + Seek>(&mut self, reader: &mut R) -> Result<()> {
+        let chunks = NdjsonReader::try_new(reader, Default::default())?;
+        let schema = chunks.schema().clone();
 
-        let row_groups = metadata.row_groups;
+        *self = Datagrid::try_from_grids(schema, chunks)?;
 
-        let chunks = parquet_read::FileReader::new(
-            reader,
-            row_groups,
-            schema,
-            Some(1024 * 8 * 8),
-            None,
-            None,
-        );
+        Ok(())
+    }
 
-        for maybe_chunk in chunks {
-            let chunk = maybe_chunk?;
-            self.0 = chunk;
-        }
+    // grids of a reader in one, empty of `schema` if none
+    fn try_from_grids<I: Iterator<Item = Result<Datagrid>>>(
+        schema: Schema,
+        grids: I,
+    ) -> Result<Self> {
+        let grids = grids.collect::<Result<Vec<_>>>()?;
 
-        Ok(())
+        match grids.len() {
+            0 => Ok(Datagrid::new_empty(schema)),
+            _ => Datagrid::concat(&grids),
+        }
     }
 }
 
 #[cfg(test)]
 mod test_datagrid {
+    use arrow2::datatypes::DataType;
 
     use super::*;
 
     const FILE_AVRO: &str = "./cache/test.avro";
     const FILE_PARQUET: &str = "./cache/test.parquet";
 
-    #[test]
-    fn avro_write_success() {
+    fn datagrid() -> Datagrid {
         let a = Int32Array::from([Some(1), None, Some(3)]).boxed();
         let b = Float32Array::from([Some(2.1), None, Some(6.2)]).boxed();
         let c = Utf8Array::<i32>::from([Some("a"), Some("b"), Some("c")]).boxed();
 
-        let datagrid = Datagrid::new(vec![a, b, c]);
-        let schema = datagrid.gen_schema(&["c1", "c2", "c3"]).unwrap();
+        Datagrid::try_from_names(&["c1", "c2", "c3"], vec![a, b, c]).unwrap()
+    }
+
+    #[test]
+    fn avro_write_success() {
+        let datagrid = datagrid();
 
         let mut file = std::fs::File::create(FILE_AVRO).unwrap();
 
-        datagrid
-            .write_avro(&mut file, &schema, None)
-            .expect("write success")
+        datagrid.write_avro(&mut file, None).expect("write success")
     }
 
     #[test]
@@ -189,7 +390,6 @@ mod test_datagrid {
         datagrid.read_avro(&mut file).unwrap();
 
         let data_types = datagrid
-            .0
             .arrays()
             .iter()
             .map(|a| a.data_type())
@@ -199,21 +399,12 @@ mod test_datagrid {
 
     #[test]
     fn parquet_write_success() {
-        let a = Int32Array::from([Some(1), None, Some(3)]).boxed();
-        let b = Float32Array::from([Some(2.1), None, Some(6.2)]).boxed();
-        let c = Utf8Array::<i32>::from([Some("a"), Some("b"), Some("c")]).boxed();
-
-        let datagrid = Datagrid::new(vec![a, b, c]);
-        let schema = datagrid.gen_schema(&["c1", "c2", "c3"]).unwrap();
+        let datagrid = datagrid();
 
         let mut file = std::fs::File::create(FILE_PARQUET).unwrap();
 
         datagrid
-            .write_parquet(
-                &mut file,
-                &schema,
-                parquet_write::CompressionOptions::Uncompressed,
-            )
+            .write_parquet(&mut file, parquet_write::CompressionOptions::Uncompressed)
             .expect("write success");
     }
 
@@ -226,11 +417,113 @@ mod test_datagrid {
         datagrid.read_parquet(&mut file).unwrap();
 
         let data_types = datagrid
-            .0
             .arrays()
             .iter()
             .map(|a| a.data_type())
             .collect::<Vec<_>>();
         println!("{:?}", data_types);
     }
+
+    #[test]
+    fn csv_success() {
+        let datagrid = datagrid();
+
+        let mut bytes = vec![];
+        datagrid.write_csv(&mut bytes, Default::default()).unwrap();
+
+        let mut read = Datagrid::empty();
+        let mut cursor = std::io::Cursor::new(bytes);
+        read.read_csv(&mut cursor, Default::default()).unwrap();
+
+        assert_eq!(read.names(), vec!["c1", "c2", "c3"]);
+        assert_eq!(read.len(), 3);
+        assert_eq!(read.arrays()[0].data_type(), &DataType::Int64);
+        assert_eq!(read.arrays()[1].null_count(), 1);
+    }
+
+    #[test]
+    fn ndjson_success() {
+        let datagrid = datagrid();
+
+        let mut bytes = vec![];
+        datagrid.write_ndjson(&mut bytes).unwrap();
+
+        let mut read = Datagrid::empty();
+        let mut cursor = std::io::Cursor::new(bytes);
+        read.read_ndjson(&mut cursor).unwrap();
+
+        assert_eq!(read.names(), vec!["c1", "c2", "c3"]);
+        assert_eq!(read.len(), 3);
+        assert_eq!(read.arrays()[0].data_type(), &DataType::Int64);
+        assert_eq!(read.arrays()[1].null_count(), 1);
+    }
+
+    #[test]
+    fn try_new_fail() {
+        let a = Int32Array::from([Some(1)]).boxed();
+        let schema = datagrid().schema().clone();
+        assert!(Datagrid::try_new(schema.clone(), vec![a.clone()]).is_err());
+
+        let b = Int32Array::from([Some(1)]).boxed();
+        let c = Int32Array::from([Some(1)]).boxed();
+        assert!(Datagrid::try_new(schema, vec![a, b, c]).is_err());
+
+        assert!(Datagrid::try_from_names(&["a"], vec![]).is_err());
+    }
+
+    #[test]
+    fn columns_success() {
+        let mut datagrid = datagrid();
+        assert!(datagrid.schema().fields.iter().all(|f| f.is_nullable));
+
+        assert_eq!(datagrid.column("c2").unwrap().null_count(), 1);
+        assert!(datagrid.column("c4").is_err());
+
+        let selected = datagrid.select(&["c3", "c1"]).unwrap();
+        assert_eq!(selected.names(), vec!["c3", "c1"]);
+        assert_eq!(
+            selected.arrays()[0].data_type(),
+            &arrow2::datatypes::DataType::Utf8
+        );
+
+        datagrid.rename("c1", "id").unwrap();
+        assert_eq!(datagrid.names(), vec!["id", "c2", "c3"]);
+        assert!(datagrid.rename("c2", "c3").is_err());
+    }
+
+    #[test]
+    fn rows_success() {
+        let datagrid = datagrid();
+
+        let sliced = datagrid.slice(1, 5).unwrap();
+        assert_eq!(sliced.len(), 2);
+        assert!(datagrid.slice(4, 1).is_err());
+
+        let concat = Datagrid::concat(&[datagrid.clone(), sliced]).unwrap();
+        assert_eq!(concat.len(), 5);
+        assert!(Datagrid::concat(&[datagrid.clone(), datagrid.select(&["c1"]).unwrap()]).is_err());
+
+        let mask = BooleanArray::from([Some(true), None, Some(false)]);
+        let filtered = datagrid.filter(&mask).unwrap();
+        assert_eq!(filtered.len(), 1);
+        assert!(datagrid.filter(&BooleanArray::from([Some(true)])).is_err());
+
+        let descending = SortOptions {
+            descending: true,
+            nulls_first: false,
+        };
+        let sorted = concat
+            .sort(&[("c3", descending), ("c1", SortOptions::default())])
+            .unwrap();
+        let c1 = sorted
+            .column("c1")
+            .unwrap()
+            .as_any()
+            .downcast_ref::<Int32Array>()
+            .unwrap();
+        assert_eq!(
+            c1,
+            &Int32Array::from([Some(3), Some(3), None, None, Some(1)])
+        );
+    }
 }
@@ -0,0 +1,827 @@
+//! Insert
+//!
+//! Datagrid into SQL tables, see `Connector::insert_datagrid`.
+//!
+//! Rows are written in a transaction, by multi-row `INSERT` statements whose size is bound
+//! by the number of parameters a database accepts. Postgres appends by `COPY` instead.
+//!
+//! MySQL commits implicitly on `CREATE`/`DROP TABLE`, so a failed insert keeps the table
+//! created by it.
+
+use std::fmt::Write as _;
+
+use anyhow::{anyhow, Result};
+use arrow2::array::*;
+use arrow2::datatypes::{DataType, Schema, TimeUnit};
+use arrow2::temporal_conversions as tc;
+use arrow2::types::NativeType;
+use futures::future::BoxFuture;
+use sqlx::database::HasArguments;
+use sqlx::postgres::PgConnection;
+use sqlx::query::Query;
+use sqlx::types::chrono::{DateTime, NaiveDate, NaiveDateTime, NaiveTime, Utc};
+use sqlx::{Database, Executor, IntoArguments, Mssql, MySql, Postgres};
+
+use crate::datagrid::Datagrid;
+
+type Args<'q, DB> = <DB as HasArguments<'q>>::Arguments;
+
+/// bytes of `COPY` data sent at once
+const COPY_BUFFER_SIZE: usize = 1 << 20;
+
+/// what to do with the rows already in the table
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum InsertMode {
+    /// keep them
+    Append,
+    /// delete them, or drop the table when it is created
+    Replace,
+    /// update the rows of the same key columns, the primary key of a created table.
+    /// MySQL matches on any unique key of the table
+    Upsert(Vec<String>),
+}
+
+/// a cell of a `Datagrid`, typed even if null since some databases check parameter types
+#[derive(Debug, Clone, PartialEq)]
+pub enum Value {
+    Bool(Option<bool>),
+    I8(Option<i8>),
+    I16(Option<i16>),
+    I32(Option<i32>),
+    I64(Option<i64>),
+    U8(Option<u8>),
+    U16(Option<u16>),
+    U32(Option<u32>),
+    U64(Option<u64>),
+    F32(Option<f32>),
+    F64(Option<f64>),
+    Text(Option<String>),
+    Bytes(Option<Vec<u8>>),
+    Date(Option<NaiveDate>),
+    Time(Option<NaiveTime>),
+    Timestamp(Option<NaiveDateTime>),
+    TimestampTz(Option<DateTime<Utc>>),
+}
+
+fn downcast<A: 'static>(array: &dyn Array) -> &A {
+    array
+        .as_any()
+        .downcast_ref::<A>()
+        .expect("array of its data type")
+}
+
+fn primitive<T: NativeType>(array: &dyn Array, row: usize) -> Option<T> {
+    downcast::<PrimitiveArray<T>>(array).get(row)
+}
+
+fn out_of_range<T, O>(value: Option<T>, f: impl Fn(T) -> Option<O>) -> Result<Option<O>> {
+    value
+        .map(|v| f(v).ok_or_else(|| anyhow!("temporal value out of range")))
+        .transpose()
+}
+
+impl Value {
+    /// value at `row` of `array`
+    pub fn from_array(array: &dyn Array, row: usize) -> Result<Self> {
+        let value = match array.data_type().to_logical_type() {
+            DataType::Boolean => Value::Bool(downcast::<BooleanArray>(array).get(row)),
+            DataType::Int8 => Value::I8(primitive(array, row)),
+            DataType::Int16 => Value::I16(primitive(array, row)),
+            DataType::Int32 => Value::I32(primitive(array, row)),
+            DataType::Int64 => Value::I64(primitive(array, row)),
+            DataType::UInt8 => Value::U8(primitive(array, row)),
+            DataType::UInt16 => Value::U16(primitive(array, row)),
+            DataType::UInt32 => Value::U32(primitive(array, row)),
+            DataType::UInt64 => Value::U64(primitive(array, row)),
+            DataType::Float32 => Value::F32(primitive(array, row)),
+            DataType::Float64 => Value::F64(primitive(array, row)),
+            DataType::Utf8 => Value::Text(
+                downcast::<Utf8Array<i32>>(array)
+                    .get(row)
+                    .map(str::to_owned),
+            ),
+            DataType::LargeUtf8 => Value::Text(
+                downcast::<Utf8Array<i64>>(array)
+                    .get(row)
+                    .map(str::to_owned),
+            ),
+            DataType::Binary => Value::Bytes(
+                downcast::<BinaryArray<i32>>(array)
+                    .get(row)
+                    .map(<[u8]>::to_vec),
+            ),
+            DataType::LargeBinary => Value::Bytes(
+                downcast::<BinaryArray<i64>>(array)
+                    .get(row)
+                    .map(<[u8]>::to_vec),
+            ),
+            DataType::Date32 => Value::Date(out_of_range(
+                primitive::<i32>(array, row),
+                tc::date32_to_date_opt,
+            )?),
+            DataType::Time32(TimeUnit::Second) => {
+                Value::Time(primitive::<i32>(array, row).map(tc::time32s_to_time))
+            }
+            DataType::Time32(_) => {
+                Value::Time(primitive::<i32>(array, row).map(tc::time32ms_to_time))
+            }
+            DataType::Time64(TimeUnit::Nanosecond) => Value::Time(out_of_range(
+                primitive::<i64>(array, row),
+                tc::time64ns_to_time_opt,
+            )?),
+            DataType::Time64(_) => Value::Time(out_of_range(
+                primitive::<i64>(array, row),
+                tc::time64us_to_time_opt,
+            )?),
+            DataType::Timestamp(unit, tz) => {
+                let datetime = out_of_range(primitive::<i64>(array, row), |v| match unit {
+                    TimeUnit::Second => tc::timestamp_s_to_datetime_opt(v),
+                    TimeUnit::Millisecond => tc::timestamp_ms_to_datetime_opt(v),
+                    TimeUnit::Microsecond => tc::timestamp_us_to_datetime_opt(v),
+                    TimeUnit::Nanosecond => tc::timestamp_ns_to_datetime_opt(v),
+                })?;
+                // values of any time zone are UTC instants
+                match tz {
+                    Some(_) => Value::TimestampTz(datetime.map(|d| d.and_utc())),
+                    None => Value::Timestamp(datetime),
+                }
+            }
+            dt => return Err(anyhow!("unsupported data type {dt:?}")),
+        };
+        Ok(value)
+    }
+
+    /// text format of Postgres `COPY`
+    pub fn write_pg_copy(&self, buf: &mut String) {
+        fn write<T: std::fmt::Display>(buf: &mut String, value: &Option<T>) {
+            match value {
+                Some(v) => write!(buf, "{v}").unwrap(),
+                None => buf.push_str("\\N"),
+            }
+        }
+
+        fn float<T: std::fmt::Display + Into<f64> + Copy>(v: T) -> String {
+            match v.into() {
+                f if f.is_nan() => "NaN".to_owned(),
+                f64::INFINITY => "Infinity".to_owned(),
+                f64::NEG_INFINITY => "-Infinity".to_owned(),
+                _ => v.to_string(),
+            }
+        }
+
+        match self {
+            Value::Bool(v) => write(buf, &v.map(|b| if b { "t" } else { "f" })),
+            Value::I8(v) => write(buf, v),
+            Value::I16(v) => write(buf, v),
+            Value::I32(v) => write(buf, v),
+            Value::I64(v) => write(buf, v),
+            Value::U8(v) => write(buf, v),
+            Value::U16(v) => write(buf, v),
+            Value::U32(v) => write(buf, v),
+            Value::U64(v) => write(buf, v),
+            Value::F32(v) => write(buf, &v.map(float)),
+            Value::F64(v) => write(buf, &v.map(float)),
+            Value::Text(Some(s)) => {
+                for c in s.chars() {
+                    match c {
+                        '\\' => buf.push_str("\\\\"),
+                        '\t' => buf.push_str("\\t"),
+                        '\n' => buf.push_str("\\n"),
+                        '\r' => buf.push_str("\\r"),
+                        c => buf.push(c),
+                    }
+                }
+            }
+            Value::Bytes(Some(b)) => {
+                // `\x` escaped once more
+                buf.push_str("\\\\x");
+                b.iter().for_each(|b| write!(buf, "{b:02x}").unwrap());
+            }
+            Value::Text(None) | Value::Bytes(None) => buf.push_str("\\N"),
+            Value::Date(v) => write(buf, &v.map(|d| d.format("%Y-%m-%d"))),
+            Value::Time(v) => write(buf, &v.map(|t| t.format("%H:%M:%S%.6f"))),
+            Value::Timestamp(v) => write(buf, &v.map(|d| d.format("%Y-%m-%d %H:%M:%S%.6f"))),
+            Value::TimestampTz(v) => write(buf, &v.map(|d| d.format("%Y-%m-%d %H:%M:%S%.6f+00"))),
+        }
+    }
+}
+
+/// statements of an insert, with identifiers quoted
+#[derive(Debug, Clone)]
+pub struct InsertPlan {
+    pub table: String,
+    pub columns: Vec<String>,
+    pub mode: InsertMode,
+    // column definitions of `CREATE TABLE`
+    definitions: Vec<String>,
+    keys: Vec<String>,
+}
+
+impl InsertPlan {
+    pub fn new<DB: ArrowEncode>(table: &str, schema: &Schema, mode: InsertMode) -> Result<Self> {
+        if schema.fields.is_empty() {
+            return Err(anyhow!("no column to insert"));
+        }
+
+        let keys = match &mode {
+            InsertMode::Upsert(keys) => {
+                if keys.is_empty() {
+                    return Err(anyhow!("upsert needs key columns"));
+                }
+                if let Some(k) = keys
+                    .iter()
+                    .find(|k| !schema.fields.iter().any(|f| &f.name == *k))
+                {
+                    return Err(anyhow!("key column {k} is not in the schema"));
+                }
+                keys.clone()
+            }
+            _ => vec![],
+        };
+
+        let definitions = schema
+            .fields
+            .iter()
+            .map(|f| {
+                let key = keys.contains(&f.name);
+                let sql_type = DB::sql_type(f.data_type(), key)
+                    .map_err(|e| anyhow!("column {}: {e}", f.name))?;
+                let null = if f.is_nullable && !key {
+                    ""
+                } else {
+                    " NOT NULL"
+                };
+                Ok(format!("{} {sql_type}{null}", DB::quote(&f.name)))
+            })
+            .collect::<Result<Vec<_>>>()?;
+
+        Ok(InsertPlan {
+            table: quote_table::<DB>(table),
+            columns: schema.fields.iter().map(|f| DB::quote(&f.name)).collect(),
+            mode,
+            definitions,
+            keys: keys.iter().map(|k| DB::quote(k)).collect(),
+        })
+    }
+
+    /// statements run before the rows are written
+    pub fn prelude<DB: ArrowEncode>(&self, create_table: bool) -> Vec<String> {
+        let mut body = self.definitions.clone();
+        if !self.keys.is_empty() {
+            body.push(format!("PRIMARY KEY ({})", self.keys.join(", ")));
+        }
+        let body = body.join(", ");
+
+        match (&self.mode, create_table) {
+            (InsertMode::Replace, true) => vec![
+                format!("DROP TABLE IF EXISTS {}", self.table),
+                DB::create_table(&self.table, &body, false),
+            ],
+            (InsertMode::Replace, false) => vec![format!("DELETE FROM {}", self.table)],
+            (_, true) => vec![DB::create_table(&self.table, &body, true)],
+            (_, false) => vec![],
+        }
+    }
+
+    /// rows of a statement, the whole statement must fit the parameters of `DB`
+    pub fn batch_size<DB: ArrowEncode>(&self) -> usize {
+        (DB::MAX_PARAMS / self.columns.len()).clamp(1, DB::MAX_ROWS)
+    }
+
+    /// multi-row statement of `rows` rows, with placeholders
+    pub fn insert_sql<DB: ArrowEncode>(&self, rows: usize) -> String {
+        let width = self.columns.len();
+        let values = (0..rows)
+            .map(|r| {
+                let row = (0..width)
+                    .map(|c| DB::placeholder(r * width + c))
+                    .collect::<Vec<_>>();
+                format!("({})", row.join(", "))
+            })
+            .collect::<Vec<_>>()
+            .join(", ");
+
+        match &self.mode {
+            InsertMode::Upsert(_) => DB::upsert(&self.table, &self.columns, &self.keys, &values),
+            _ => format!(
+                "INSERT INTO {} ({}) VALUES {values}",
+                self.table,
+                self.columns.join(", ")
+            ),
+        }
+    }
+
+    // columns updated by an upsert
+    fn updated<'a>(columns: &'a [String], keys: &'a [String]) -> impl Iterator<Item = &'a String> {
+        columns.iter().filter(move |c| !keys.contains(c))
+    }
+}
+
+/// quote each part of a qualified name, e.g. `dbo.users`
+pub fn quote_table<DB: ArrowEncode>(table: &str) -> String {
+    table
+        .split('.')
+        .map(DB::quote)
+        .collect::<Vec<_>>()
+        .join(".")
+}
+
+/// SQL dialect and parameter binding of a database.
+pub trait ArrowEncode: Database {
+    /// most parameters of a statement
+    const MAX_PARAMS: usize;
+    /// most rows of a `VALUES` list
+    const MAX_ROWS: usize;
+
+    fn quote(ident: &str) -> String;
+
+    /// placeholder of the `idx`th parameter, from 0
+    fn placeholder(idx: usize) -> String;
+
+    /// column type of `CREATE TABLE`, `key` columns must be indexable
+    fn sql_type(data_type: &DataType, key: bool) -> Result<String>;
+
+    fn create_table(table: &str, body: &str, if_not_exists: bool) -> String;
+
+    /// `values` is the list of rows after `VALUES`
+    fn upsert(table: &str, columns: &[String], keys: &[String], values: &str) -> String;
+
+    fn bind<'q>(
+        query: Query<'q, Self, Args<'q, Self>>,
+        value: Value,
+    ) -> Result<Query<'q, Self, Args<'q, Self>>>;
+
+    /// write the rows of `datagrid`, returns their count
+    fn insert<'a>(
+        conn: &'a mut Self::Connection,
+        plan: &'a InsertPlan,
+        datagrid: &'a Datagrid,
+    ) -> BoxFuture<'a, Result<u64>>;
+}
+
+/// write by multi-row `INSERT` statements
+pub async fn insert_values<DB>(
+    conn: &mut DB::Connection,
+    plan: &InsertPlan,
+    datagrid: &Datagrid,
+) -> Result<u64>
+where
+    DB: ArrowEncode,
+    for<'c> &'c mut DB::Connection: Executor<'c, Database = DB>,
+    for<'q> Args<'q, DB>: IntoArguments<'q, DB>,
+{
+    let (len, batch_size) = (datagrid.len(), plan.batch_size::<DB>());
+    let mut sql = plan.insert_sql::<DB>(batch_size);
+
+    for start in (0..len).step_by(batch_size) {
+        let end = (start + batch_size).min(len);
+        if end - start < batch_size {
+            sql = plan.insert_sql::<DB>(end - start);
+        }
+
+        let mut query = sqlx::query::<DB>(&sql);
+        for row in start..end {
+            for array in datagrid.arrays() {
+                query = DB::bind(query, Value::from_array(array.as_ref(), row)?)?;
+            }
+        }
+        query.execute(&mut *conn).await?;
+    }
+
+    Ok(len as u64)
+}
+
+// write by `COPY`, in text format
+async fn copy_in(conn: &mut PgConnection, plan: &InsertPlan, datagrid: &Datagrid) -> Result<u64> {
+    let sql = format!(
+        "COPY {} ({}) FROM STDIN",
+        plan.table,
+        plan.columns.join(", ")
+    );
+    // aborted if dropped before `finish`
+    let mut copy = conn.copy_in_raw(&sql).await?;
+
+    let mut buf = String::new();
+    for row in 0..datagrid.len() {
+        for (idx, array) in datagrid.arrays().iter().enumerate() {
+            if idx > 0 {
+                buf.push('\t');
+            }
+            Value::from_array(array.as_ref(), row)?.write_pg_copy(&mut buf);
+        }
+        buf.push('\n');
+
+        if buf.len() >= COPY_BUFFER_SIZE {
+            copy.send(buf.as_bytes()).await?;
+            buf.clear();
+        }
+    }
+    if !buf.is_empty() {
+        copy.send(buf.as_bytes()).await?;
+    }
+
+    Ok(copy.finish().await?)
+}
+
+fn unsupported<T>(data_type: &DataType) -> Result<T> {
+    Err(anyhow!("unsupported data type {data_type:?}"))
+}
+
+fn to_i64(value: Option<u64>) -> Result<Option<i64>> {
+    value
+        .map(|v| i64::try_from(v).map_err(|_| anyhow!("{v} is out of range of BIGINT")))
+        .transpose()
+}
+
+impl ArrowEncode for MySql {
+    const MAX_PARAMS: usize = u16::MAX as usize;
+    const MAX_ROWS: usize = usize::MAX;
+
+    fn quote(ident: &str) -> String {
+        format!("`{}`", ident.replace('`', "``"))
+    }
+
+    fn placeholder(_idx: usize) -> String {
+        "?".to_owned()
+    }
+
+    fn sql_type(data_type: &DataType, key: bool) -> Result<String> {
+        let t = match data_type.to_logical_type() {
+            DataType::Boolean => "BOOLEAN",
+            DataType::Int8 => "TINYINT",
+            DataType::Int16 => "SMALLINT",
+            DataType::Int32 => "INT",
+            DataType::Int64 => "BIGINT",
+            DataType::UInt8 => "TINYINT UNSIGNED",
+            DataType::UInt16 => "SMALLINT UNSIGNED",
+            DataType::UInt32 => "INT UNSIGNED",
+            DataType::UInt64 => "BIGINT UNSIGNED",
+            DataType::Float32 => "FLOAT",
+            DataType::Float64 => "DOUBLE",
+            // `TEXT` and `BLOB` can't be keys without a prefix length
+            DataType::Utf8 | DataType::LargeUtf8 if key => "VARCHAR(255)",
+            DataType::Utf8 | DataType::LargeUtf8 => "LONGTEXT",
+            DataType::Binary | DataType::LargeBinary if key => "VARBINARY(255)",
+            DataType::Binary | DataType::LargeBinary => "LONGBLOB",
+            DataType::Date32 => "DATE",
+            DataType::Time32(_) | DataType::Time64(_) => "TIME(6)",
+            DataType::Timestamp(_, None) => "DATETIME(6)",
+            DataType::Timestamp(_, Some(_)) => "TIMESTAMP(6)",
+            dt => return unsupported(dt),
+        };
+        Ok(t.to_owned())
+    }
+
+    fn create_table(table: &str, body: &str, if_not_exists: bool) -> String {
+        let ine = if if_not_exists { "IF NOT EXISTS " } else { "" };
+        format!("CREATE TABLE {ine}{table} ({body})")
+    }
+
+    fn upsert(table: &str, columns: &[String], keys: &[String], values: &str) -> String {
+        let mut updates = InsertPlan::updated(columns, keys)
+            .map(|c| format!("{c} = VALUES({c})"))
+            .collect::<Vec<_>>();
+        if updates.is_empty() {
+            // nothing but keys, keep the row
+            updates.push(format!("{0} = {0}", keys[0]));
+        }
+        format!(
+            "INSERT INTO {table} ({}) VALUES {values} ON DUPLICATE KEY UPDATE {}",
+            columns.join(", "),
+            updates.join(", ")
+        )
+    }
+
+    fn bind<'q>(
+        query: Query<'q, Self, Args<'q, Self>>,
+        value: Value,
+    ) -> Result<Query<'q, Self, Args<'q, Self>>> {
+        let query = match value {
+            Value::Bool(v) => query.bind(v),
+            Value::I8(v) => query.bind(v),
+            Value::I16(v) => query.bind(v),
+            Value::I32(v) => query.bind(v),
+            Value::I64(v) => query.bind(v),
+            Value::U8(v) => query.bind(v),
+            Value::U16(v) => query.bind(v),
+            Value::U32(v) => query.bind(v),
+            Value::U64(v) => query.bind(v),
+            Value::F32(v) => query.bind(v),
+            Value::F64(v) => query.bind(v),
+            Value::Text(v) => query.bind(v),
+            Value::Bytes(v) => query.bind(v),
+            Value::Date(v) => query.bind(v),
+            Value::Time(v) => query.bind(v),
+            Value::Timestamp(v) => query.bind(v),
+            Value::TimestampTz(v) => query.bind(v),
+        };
+        Ok(query)
+    }
+
+    fn insert<'a>(
+        conn: &'a mut Self::Connection,
+        plan: &'a InsertPlan,
+        datagrid: &'a Datagrid,
+    ) -> BoxFuture<'a, Result<u64>> {
+        Box::pin(insert_values::<MySql>(conn, plan, datagrid))
+    }
+}
+
+impl ArrowEncode for Postgres {
+    const MAX_PARAMS: usize = u16::MAX as usize;
+    const MAX_ROWS: usize = usize::MAX;
+
+    fn quote(ident: &str) -> String {
+        format!("\"{}\"", ident.replace('"', "\"\""))
+    }
+
+    fn placeholder(idx: usize) -> String {
+        format!("${}", idx + 1)
+    }
+
+    fn sql_type(data_type: &DataType, _key: bool) -> Result<String> {
+        // unsigned integers widen, as they are bound
+        let t = match data_type.to_logical_type() {
+            DataType::Boolean => "BOOLEAN",
+            DataType::Int8 | DataType::Int16 | DataType::UInt8 => "SMALLINT",
+            DataType::Int32 | DataType::UInt16 => "INTEGER",
+            DataType::Int64 | DataType::UInt32 | DataType::UInt64 => "BIGINT",
+            DataType::Float32 => "REAL",
+            DataType::Float64 => "DOUBLE PRECISION",
+            DataType::Utf8 | DataType::LargeUtf8 => "TEXT",
+            DataType::Binary | DataType::LargeBinary => "BYTEA",
+            DataType::Date32 => "DATE",
+            DataType::Time32(_) | DataType::Time64(_) => "TIME",
+            DataType::Timestamp(_, None) => "TIMESTAMP",
+            DataType::Timestamp(_, Some(_)) => "TIMESTAMPTZ",
+            dt => return unsupported(dt),
+        };
+        Ok(t.to_owned())
+    }
+
+    fn create_table(table: &str, body: &str, if_not_exists: bool) -> String {
+        let ine = if if_not_exists { "IF NOT EXISTS " } else { "" };
+        format!("CREATE TABLE {ine}{table} ({body})")
+    }
+
+    fn upsert(table: &str, columns: &[String], keys: &[String], values: &str) -> String {
+        let updates = InsertPlan::updated(columns, keys)
+            .map(|c| format!("{c} = EXCLUDED.{c}"))
+            .collect::<Vec<_>>();
+        let action = if updates.is_empty() {
+            "NOTHING".to_owned()
+        } else {
+            format!("UPDATE SET {}", updates.join(", "))
+        };
+        format!(
+            "INSERT INTO {table} ({}) VALUES {values} ON CONFLICT ({}) DO {action}",
+            columns.join(", "),
+            keys.join(", ")
+        )
+    }
+
+    fn bind<'q>(
+        query: Query<'q, Self, Args<'q, Self>>,
+        value: Value,
+    ) -> Result<Query<'q, Self, Args<'q, Self>>> {
+        // `i8` is `"CHAR"` in Postgres
+        let query = match value {
+            Value::Bool(v) => query.bind(v),
+            Value::I8(v) => query.bind(v.map(i16::from)),
+            Value::I16(v) => query.bind(v),
+            Value::I32(v) => query.bind(v),
+            Value::I64(v) => query.bind(v),
+            Value::U8(v) => query.bind(v.map(i16::from)),
+            Value::U16(v) => query.bind(v.map(i32::from)),
+            Value::U32(v) => query.bind(v.map(i64::from)),
+            Value::U64(v) => query.bind(to_i64(v)?),
+            Value::F32(v) => query.bind(v),
+            Value::F64(v) => query.bind(v),
+            Value::Text(v) => query.bind(v),
+            Value::Bytes(v) => query.bind(v),
+            Value::Date(v) => query.bind(v),
+            Value::Time(v) => query.bind(v),
+            Value::Timestamp(v) => query.bind(v),
+            Value::TimestampTz(v) => query.bind(v),
+        };
+        Ok(query)
+    }
+
+    fn insert<'a>(
+        conn: &'a mut Self::Connection,
+        plan: &'a InsertPlan,
+        datagrid: &'a Datagrid,
+    ) -> BoxFuture<'a, Result<u64>> {
+        match plan.mode {
+            // `COPY` can't resolve conflicts
+            InsertMode::Upsert(_) => Box::pin(insert_values::<Postgres>(conn, plan, datagrid)),
+            _ => Box::pin(copy_in(conn, plan, datagrid)),
+        }
+    }
+}
+
+impl ArrowEncode for Mssql {
+    const MAX_PARAMS: usize = 2_100 - 1;
+    const MAX_ROWS: usize = 1_000;
+
+    fn quote(ident: &str) -> String {
+        format!("[{}]", ident.replace(']', "]]"))
+    }
+
+    fn placeholder(idx: usize) -> String {
+        format!("@p{}", idx + 1)
+    }
+
+    fn sql_type(data_type: &DataType, key: bool) -> Result<String> {
+        // `TINYINT` is unsigned, temporal values are bound as text
+        let t = match data_type.to_logical_type() {
+            DataType::Boolean => "BIT",
+            DataType::Int8 | DataType::Int16 => "SMALLINT",
+            DataType::Int32 | DataType::UInt16 => "INT",
+            DataType::Int64 | DataType::UInt32 | DataType::UInt64 => "BIGINT",
+            DataType::UInt8 => "TINYINT",
+            DataType::Float32 => "REAL",
+            DataType::Float64 => "FLOAT",
+            // most bytes of an index key
+            DataType::Utf8 | DataType::LargeUtf8 if key => "NVARCHAR(450)",
+            DataType::Utf8 | DataType::LargeUtf8 => "NVARCHAR(MAX)",
+            DataType::Date32 => "DATE",
+            DataType::Time32(_) | DataType::Time64(_) => "TIME(6)",
+            DataType::Timestamp(_, None) => "DATETIME2(6)",
+            DataType::Timestamp(_, Some(_)) => "DATETIMEOFFSET(6)",
+            dt => return unsupported(dt),
+        };
+        Ok(t.to_owned())
+    }
+
+    fn create_table(table: &str, body: &str, if_not_exists: bool) -> String {
+        let create = format!("CREATE TABLE {table} ({body})");
+        if if_not_exists {
+            format!(
+                "IF OBJECT_ID(N'{}', N'U') IS NULL {create}",
+                table.replace('\'', "''")
+            )
+        } else {
+            create
+        }
+    }
+
+    fn upsert(table: &str, columns: &[String], keys: &[String], values: &str) -> String {
+        let on = keys
+            .iter()
+            .map(|k| format!("t.{k} = s.{k}"))
+            .collect::<Vec<_>>()
+            .join(" AND ");
+        let updates = InsertPlan::updated(columns, keys)
+            .map(|c| format!("{c} = s.{c}"))
+            .collect::<Vec<_>>();
+        let matched = if updates.is_empty() {
+            String::new()
+        } else {
+            format!(" WHEN MATCHED THEN UPDATE SET {}", updates.join(", "))
+        };
+        let sources = columns
+            .iter()
+            .map(|c| format!("s.{c}"))
+            .collect::<Vec<_>>()
+            .join(", ");
+        let columns = columns.join(", ");
+        format!(
+            "MERGE INTO {table} WITH (HOLDLOCK) AS t USING (VALUES {values}) AS s ({columns}) \
+             ON {on}{matched} WHEN NOT MATCHED THEN INSERT ({columns}) VALUES ({sources});"
+        )
+    }
+
+    fn bind<'q>(
+        query: Query<'q, Self, Args<'q, Self>>,
+        value: Value,
+    ) -> Result<Query<'q, Self, Args<'q, Self>>> {
+        let query = match value {
+            Value::Bool(v) => query.bind(v),
+            Value::I8(v) => query.bind(v.map(i16::from)),
+            Value::I16(v) => query.bind(v),
+            Value::I32(v) => query.bind(v),
+            Value::I64(v) => query.bind(v),
+            Value::U8(v) => query.bind(v),
+            Value::U16(v) => query.bind(v.map(i32::from)),
+            Value::U32(v) => query.bind(v.map(i64::from)),
+            Value::U64(v) => query.bind(to_i64(v)?),
+            Value::F32(v) => query.bind(v),
+            Value::F64(v) => query.bind(v),
+            Value::Text(v) => query.bind(v),
+            Value::Bytes(_) => return Err(anyhow!("binary values are not supported by MSSQL")),
+            Value::Date(v) => query.bind(v.map(|d| d.format("%Y-%m-%d").to_string())),
+            Value::Time(v) => query.bind(v.map(|t| t.format("%H:%M:%S%.6f").to_string())),
+            Value::Timestamp(v) => {
+                query.bind(v.map(|d| d.format("%Y-%m-%dT%H:%M:%S%.6f").to_string()))
+            }
+            Value::TimestampTz(v) => {
+                query.bind(v.map(|d| d.format("%Y-%m-%dT%H:%M:%S%.6f+00:00").to_string()))
+            }
+        };
+        Ok(query)
+    }
+
+    fn insert<'a>(
+        conn: &'a mut Self::Connection,
+        plan: &'a InsertPlan,
+        datagrid: &'a Datagrid,
+    ) -> BoxFuture<'a, Result<u64>> {
+        Box::pin(insert_values::<Mssql>(conn, plan, datagrid))
+    }
+}
+
+#[cfg(test)]
+mod test_insert {
+    use arrow2::datatypes::Field;
+
+    use super::*;
+
+    fn schema() -> Schema {
+        Schema::from(vec![
+            Field::new("id", DataType::Int64, false),
+            Field::new("name", DataType::Utf8, true),
+        ])
+    }
+
+    #[test]
+    fn plan_success() {
+        let plan =
+            InsertPlan::new::<Postgres>("public.users", &schema(), InsertMode::Append).unwrap();
+        assert_eq!(
+            plan.insert_sql::<Postgres>(2),
+            r#"INSERT INTO "public"."users" ("id", "name") VALUES ($1, $2), ($3, $4)"#
+        );
+        assert_eq!(
+            plan.prelude::<Postgres>(true),
+            vec![
+                r#"CREATE TABLE IF NOT EXISTS "public"."users" ("id" BIGINT NOT NULL, "name" TEXT)"#
+            ]
+        );
+        assert_eq!(plan.batch_size::<Postgres>(), 32767);
+        assert_eq!(plan.batch_size::<Mssql>(), 1000);
+
+        let plan = InsertPlan::new::<MySql>("users", &schema(), InsertMode::Replace).unwrap();
+        assert_eq!(plan.prelude::<MySql>(false), vec!["DELETE FROM `users`"]);
+        assert_eq!(
+            plan.prelude::<MySql>(true)[1],
+            "CREATE TABLE `users` (`id` BIGINT NOT NULL, `name` LONGTEXT)"
+        );
+    }
+
+    #[test]
+    fn upsert_success() {
+        let mode = InsertMode::Upsert(vec!["name".to_owned()]);
+
+        let plan = InsertPlan::new::<Postgres>("users", &schema(), mode.clone()).unwrap();
+        assert_eq!(
+            plan.insert_sql::<Postgres>(1),
+            r#"INSERT INTO "users" ("id", "name") VALUES ($1, $2) ON CONFLICT ("name") DO UPDATE SET "id" = EXCLUDED."id""#
+        );
+
+        let plan = InsertPlan::new::<MySql>("users", &schema(), mode.clone()).unwrap();
+        assert_eq!(
+            plan.prelude::<MySql>(true),
+            vec!["CREATE TABLE IF NOT EXISTS `users` (`id` BIGINT NOT NULL, `name` VARCHAR(255) NOT NULL, PRIMARY KEY (`name`))"]
+        );
+        assert_eq!(
+            plan.insert_sql::<MySql>(1),
+            "INSERT INTO `users` (`id`, `name`) VALUES (?, ?) ON DUPLICATE KEY UPDATE `id` = VALUES(`id`)"
+        );
+
+        let plan = InsertPlan::new::<Mssql>("dbo.users", &schema(), mode).unwrap();
+        assert_eq!(
+            plan.insert_sql::<Mssql>(1),
+            "MERGE INTO [dbo].[users] WITH (HOLDLOCK) AS t USING (VALUES (@p1, @p2)) AS s ([id], [name]) \
+             ON t.[name] = s.[name] WHEN MATCHED THEN UPDATE SET [id] = s.[id] \
+             WHEN NOT MATCHED THEN INSERT ([id], [name]) VALUES (s.[id], s.[name]);"
+        );
+
+        let missing = InsertMode::Upsert(vec!["email".to_owned()]);
+        assert!(InsertPlan::new::<Postgres>("users", &schema(), missing).is_err());
+    }
+
+    #[test]
+    fn pg_copy_success() {
+        let a = Int32Array::from([Some(1), None]).boxed();
+        let b = Utf8Array::<i32>::from([Some("a\tb\\"), None]).boxed();
+        let c = BinaryArray::<i32>::from([Some(&[0u8, 255][..]), None]).boxed();
+        let d = Int64Array::from([Some(86_400_000_001), None])
+            .to(DataType::Timestamp(TimeUnit::Microsecond, None))
+            .boxed();
+
+        let mut buf = String::new();
+        for row in 0..2 {
+            for array in [&a, &b, &c, &d] {
+                Value::from_array(array.as_ref(), row)
+                    .unwrap()
+                    .write_pg_copy(&mut buf);
+                buf.push('|');
+            }
+        }
+        assert_eq!(
+            buf,
+            "1|a\\tb\\\\|\\\\x00ff|1970-01-02 00:00:00.000001|\\N|\\N|\\N|\\N|"
+        );
+    }
+}
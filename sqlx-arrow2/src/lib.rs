@@ -5,5 +5,11 @@
 #![feature(type_alias_impl_trait)]
 #![feature(impl_trait_in_assoc_type)]
 
+pub mod avro;
 pub mod connector;
+pub mod csv;
 pub mod datagrid;
+pub mod insert;
+pub mod ndjson;
+pub mod parquet;
+pub mod rows;
@@ -0,0 +1,208 @@
+//! NDJSON
+//!
+//! Datagrid in newline delimited JSON, a row an object, read chunk by chunk and written grid
+//! by grid.
+//!
+//! Types are inferred from the first rows unless a schema is given, keys missing in a row are
+//! null.
+
+use std::io::{BufRead, Seek, SeekFrom, Write};
+
+use anyhow::{anyhow, Result};
+use arrow2::array::StructArray;
+use arrow2::datatypes::{DataType, Schema};
+use arrow2::io::ndjson::read as ndjson_read;
+use arrow2::io::ndjson::read::FallibleStreamingIterator;
+use arrow2::io::ndjson::write as ndjson_write;
+
+use crate::datagrid::Datagrid;
+
+/// rows read to infer the types by default
+pub const DEFAULT_INFER_ROWS: usize = 1_000;
+
+/// rows of a grid by default
+pub const DEFAULT_CHUNK_SIZE: usize = 10_000;
+
+/// options of `NdjsonReader`
+#[derive(Debug, Clone)]
+pub struct NdjsonReadOptions {
+    /// rows read to infer the types, all of them if `None`
+    pub infer_rows: Option<usize>,
+    /// most rows of a grid
+    pub chunk_size: usize,
+}
+
+impl Default for NdjsonReadOptions {
+    fn default() -> Self {
+        Self {
+            infer_rows: Some(DEFAULT_INFER_ROWS),
+            chunk_size: DEFAULT_CHUNK_SIZE,
+        }
+    }
+}
+
+/// Reader of NDJSON rows, an iterator of `Datagrid`, `chunk_size` rows each.
+pub struct NdjsonReader<R: BufRead> {
+    rows: ndjson_read::FileReader<R>,
+    schema: Schema,
+}
+
+impl<R: BufRead + Seek> NdjsonReader<R> {
+    /// read with the types inferred from the first `infer_rows` rows, at least one row
+    pub fn try_new(mut reader: R, options: NdjsonReadOptions) -> Result<Self> {
+        let start = reader.stream_position()?;
+        let data_type = ndjson_read::infer(&mut reader, options.infer_rows)?;
+        reader.seek(SeekFrom::Start(start))?;
+
+        let fields = match data_type {
+            DataType::Struct(fields) => fields,
+            dt => return Err(anyhow!("rows are not objects but {dt:?}")),
+        };
+
+        Self::try_new_with_schema(reader, options, Schema::from(fields))
+    }
+}
+
+impl<R: BufRead> NdjsonReader<R> {
+    /// read with `schema`, its fields found by key
+    pub fn try_new_with_schema(
+        reader: R,
+        options: NdjsonReadOptions,
+        schema: Schema,
+    ) -> Result<Self> {
+        if options.chunk_size == 0 {
+            return Err(anyhow!("chunk_size must be positive"));
+        }
+
+        let rows = vec![String::new(); options.chunk_size];
+        Ok(Self {
+            rows: ndjson_read::FileReader::new(reader, rows, None),
+            schema,
+        })
+    }
+
+    /// schema of the grids read
+    pub fn schema(&self) -> &Schema {
+        &self.schema
+    }
+
+    fn read_chunk(&mut self) -> Result<Option<Datagrid>> {
+        let rows = match self.rows.next()? {
+            Some(rows) => rows,
+            None => return Ok(None),
+        };
+
+        let data_type = DataType::Struct(self.schema.fields.clone());
+        let array = ndjson_read::deserialize(rows, data_type)?;
+        let arrays = array
+            .as_any()
+            .downcast_ref::<StructArray>()
+            .ok_or_else(|| anyhow!("rows are not deserialized as objects"))?
+            .values()
+            .to_vec();
+
+        Datagrid::try_new(self.schema.clone(), arrays).map(Some)
+    }
+}
+
+impl<R: BufRead> Iterator for NdjsonReader<R> {
+    type Item = Result<Datagrid>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.read_chunk().transpose()
+    }
+}
+
+/// Writer of NDJSON rows, null values written as `null`.
+pub struct NdjsonWriter<W: Write> {
+    writer: W,
+    schema: Schema,
+}
+
+impl<W: Write> NdjsonWriter<W> {
+    pub fn new(writer: W, schema: Schema) -> Self {
+        Self { writer, schema }
+    }
+
+    pub fn schema(&self) -> &Schema {
+        &self.schema
+    }
+
+    pub fn write(&mut self, datagrid: &Datagrid) -> Result<()> {
+        let data_type = DataType::Struct(self.schema.fields.clone());
+        let array = StructArray::try_new(data_type, datagrid.arrays().to_vec(), None)?;
+
+        let arrays = std::iter::once(Ok::<_, arrow2::error::Error>(array.boxed()));
+        let serializer = ndjson_write::Serializer::new(arrays, vec![]);
+        for res in ndjson_write::FileWriter::new(&mut self.writer, serializer) {
+            res?;
+        }
+
+        Ok(())
+    }
+
+    pub fn into_inner(self) -> W {
+        self.writer
+    }
+}
+
+#[cfg(test)]
+mod test_ndjson {
+    use std::io::Cursor;
+
+    use arrow2::array::*;
+    use arrow2::datatypes::Field;
+
+    use super::*;
+
+    #[test]
+    fn chunks_success() {
+        let a = Int64Array::from((0..25).map(Some).collect::<Vec<_>>()).boxed();
+        let b = Utf8Array::<i32>::from(
+            (0..25)
+                .map(|i| (i % 5 != 0).then(|| format!("v\"{i}\"")))
+                .collect::<Vec<_>>(),
+        )
+        .boxed();
+        let datagrid = Datagrid::try_from_names(&["a", "b"], vec![a, b]).unwrap();
+
+        let mut writer = NdjsonWriter::new(vec![], datagrid.schema().clone());
+        writer.write(&datagrid).unwrap();
+        writer.write(&Datagrid::empty()).unwrap_err();
+        let bytes = writer.into_inner();
+
+        let options = NdjsonReadOptions {
+            chunk_size: 10,
+            ..Default::default()
+        };
+        let reader = NdjsonReader::try_new(Cursor::new(bytes), options).unwrap();
+        assert_eq!(reader.schema().fields[1].data_type(), &DataType::Utf8);
+
+        let grids = reader.map(|d| d.unwrap()).collect::<Vec<_>>();
+        assert_eq!(
+            grids.iter().map(|g| g.len()).collect::<Vec<_>>(),
+            vec![10, 10, 5]
+        );
+        assert_eq!(grids[0].column("b").unwrap().null_count(), 2);
+    }
+
+    #[test]
+    fn schema_success() {
+        let bytes = b"{\"a\": 1, \"b\": \"x\"}\n\n{\"b\": \"y\"}\n".to_vec();
+
+        let schema = Schema::from(vec![
+            Field::new("b", DataType::Utf8, true),
+            Field::new("a", DataType::Int32, true),
+        ]);
+        let mut reader =
+            NdjsonReader::try_new_with_schema(Cursor::new(bytes), Default::default(), schema)
+                .unwrap();
+        let grid = reader.next().unwrap().unwrap();
+        assert_eq!(grid.len(), 2);
+        assert_eq!(grid.column("a").unwrap().null_count(), 1);
+        assert!(reader.next().is_none());
+
+        let invalid = Cursor::new(b"[1, 2]\n".to_vec());
+        assert!(NdjsonReader::try_new(invalid, Default::default()).is_err());
+    }
+}
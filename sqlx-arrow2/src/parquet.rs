@@ -0,0 +1,571 @@
+//! Parquet
+//!
+//! Datagrid in Parquet files of many row groups, read chunk by chunk and written grid by grid.
+//!
+//! Reading skips the columns not asked for, and the row groups whose min/max statistics can't
+//! match a `Predicate`. Pruning is by row group only, rows of a kept row group are not filtered.
+
+use std::collections::HashMap;
+use std::io::{Read, Seek, Write};
+
+use anyhow::{anyhow, Result};
+use arrow2::chunk::Chunk;
+use arrow2::compute::cast::{cast, CastOptions};
+use arrow2::compute::comparison;
+use arrow2::compute::concatenate::concatenate;
+use arrow2::datatypes::{DataType, Field, IntegerType, Schema};
+use arrow2::io::parquet::read as parquet_read;
+use arrow2::io::parquet::read::statistics::Statistics;
+use arrow2::io::parquet::write as parquet_write;
+use arrow2::scalar::Scalar;
+
+use crate::datagrid::Datagrid;
+
+/// rows of a row group by default
+pub const DEFAULT_ROW_GROUP_SIZE: usize = 64 * 1024;
+
+// ------------------------------------------------------------------------------
+// read
+
+/// Condition on the values of columns, to skip row groups by their statistics.
+#[derive(Debug)]
+pub enum Predicate {
+    Eq(String, Box<dyn Scalar>),
+    Lt(String, Box<dyn Scalar>),
+    LtEq(String, Box<dyn Scalar>),
+    Gt(String, Box<dyn Scalar>),
+    GtEq(String, Box<dyn Scalar>),
+    And(Vec<Predicate>),
+    Or(Vec<Predicate>),
+}
+
+impl Predicate {
+    // columns must exist and be of the type of their value
+    fn check(&self, schema: &Schema) -> Result<()> {
+        match self {
+            Predicate::And(ps) | Predicate::Or(ps) => ps.iter().try_for_each(|p| p.check(schema)),
+            Predicate::Eq(c, v)
+            | Predicate::Lt(c, v)
+            | Predicate::LtEq(c, v)
+            | Predicate::Gt(c, v)
+            | Predicate::GtEq(c, v) => {
+                let field = schema
+                    .fields
+                    .iter()
+                    .find(|f| &f.name == c)
+                    .ok_or_else(|| anyhow!("predicate column {c} is not in the file"))?;
+                if field.data_type() != v.data_type() {
+                    return Err(anyhow!(
+                        "predicate column {c}: {:?} compared to {:?}",
+                        field.data_type(),
+                        v.data_type()
+                    ));
+                }
+                Ok(())
+            }
+        }
+    }
+
+    fn columns<'a>(&'a self, columns: &mut Vec<&'a str>) {
+        match self {
+            Predicate::And(ps) | Predicate::Or(ps) => ps.iter().for_each(|p| p.columns(columns)),
+            Predicate::Eq(c, _)
+            | Predicate::Lt(c, _)
+            | Predicate::LtEq(c, _)
+            | Predicate::Gt(c, _)
+            | Predicate::GtEq(c, _) => columns.push(c),
+        }
+    }
+
+    /// `false` only if no row of the row group of `statistics` can match
+    pub fn may_match(&self, statistics: &HashMap<&str, Statistics>) -> bool {
+        type Test = fn(&Statistics, &dyn Scalar) -> Option<bool>;
+
+        // unknown statistics can't prune
+        let test = |c: &str, v: &dyn Scalar, f: Test| {
+            statistics
+                .get(c)
+                .filter(|s| s.min_value.data_type() == v.data_type())
+                .and_then(|s| f(s, v))
+                .unwrap_or(true)
+        };
+
+        match self {
+            Predicate::And(ps) => ps.iter().all(|p| p.may_match(statistics)),
+            Predicate::Or(ps) => ps.iter().any(|p| p.may_match(statistics)),
+            Predicate::Eq(c, v) => test(c, v.as_ref(), |s, v| {
+                let above = comparison::gt_scalar(s.min_value.as_ref(), v).get(0)?;
+                let below = comparison::lt_scalar(s.max_value.as_ref(), v).get(0)?;
+                Some(!above && !below)
+            }),
+            Predicate::Lt(c, v) => test(c, v.as_ref(), |s, v| {
+                comparison::lt_scalar(s.min_value.as_ref(), v).get(0)
+            }),
+            Predicate::LtEq(c, v) => test(c, v.as_ref(), |s, v| {
+                comparison::lt_eq_scalar(s.min_value.as_ref(), v).get(0)
+            }),
+            Predicate::Gt(c, v) => test(c, v.as_ref(), |s, v| {
+                comparison::gt_scalar(s.max_value.as_ref(), v).get(0)
+            }),
+            Predicate::GtEq(c, v) => test(c, v.as_ref(), |s, v| {
+                comparison::gt_eq_scalar(s.max_value.as_ref(), v).get(0)
+            }),
+        }
+    }
+}
+
+/// options of `ParquetReader`
+#[derive(Debug, Default)]
+pub struct ParquetReadOptions {
+    /// columns to read, in the order of the file, all of them if `None`
+    pub columns: Option<Vec<String>>,
+    /// skip the row groups which can't match
+    pub predicate: Option<Predicate>,
+    /// most rows of a grid, a row group at most
+    pub chunk_size: Option<usize>,
+    /// most rows read
+    pub limit: Option<usize>,
+}
+
+/// Reader of Parquet row groups, an iterator of `Datagrid`.
+pub struct ParquetReader<R: Read + Seek> {
+    chunks: parquet_read::FileReader<R>,
+    row_groups: usize,
+}
+
+impl<R: Read + Seek> ParquetReader<R> {
+    pub fn try_new(mut reader: R, options: ParquetReadOptions) -> Result<Self> {
+        let metadata = parquet_read::read_metadata(&mut reader)?;
+        let schema = parquet_read::infer_schema(&metadata)?;
+
+        let row_groups = match &options.predicate {
+            None => metadata.row_groups,
+            Some(predicate) => {
+                predicate.check(&schema)?;
+                prune(&schema, metadata.row_groups, predicate)?
+            }
+        };
+
+        let schema = match &options.columns {
+            None => schema,
+            Some(columns) => {
+                if let Some(c) = columns
+                    .iter()
+                    .find(|c| !schema.fields.iter().any(|f| &f.name == *c))
+                {
+                    return Err(anyhow!("column {c} is not in the file"));
+                }
+                schema.filter(|_, f| columns.contains(&f.name))
+            }
+        };
+
+        let row_groups_len = row_groups.len();
+        let chunks = parquet_read::FileReader::new(
+            reader,
+            row_groups,
+            schema,
+            options.chunk_size,
+            options.limit,
+            None,
+        );
+
+        Ok(Self {
+            chunks,
+            row_groups: row_groups_len,
+        })
+    }
+
+    /// schema of the grids read
+    pub fn schema(&self) -> &Schema {
+        self.chunks.schema()
+    }
+
+    /// row groups left after pruning
+    pub fn row_groups(&self) -> usize {
+        self.row_groups
+    }
+}
+
+// row groups which may match `predicate`
+fn prune(
+    schema: &Schema,
+    row_groups: Vec<parquet_read::RowGroupMetaData>,
+    predicate: &Predicate,
+) -> Result<Vec<parquet_read::RowGroupMetaData>> {
+    let mut columns = vec![];
+    predicate.columns(&mut columns);
+    let fields = schema
+        .fields
+        .iter()
+        .filter(|f| columns.contains(&f.name.as_str()))
+        .collect::<Vec<_>>();
+
+    let mut kept = vec![];
+    for row_group in row_groups {
+        let statistics = fields
+            .iter()
+            .map(|f| {
+                let s = parquet_read::statistics::deserialize(f, std::slice::from_ref(&row_group))?;
+                Ok((f.name.as_str(), s))
+            })
+            .collect::<Result<HashMap<_, _>>>()?;
+
+        if predicate.may_match(&statistics) {
+            kept.push(row_group);
+        }
+    }
+
+    Ok(kept)
+}
+
+impl<R: Read + Seek> Iterator for ParquetReader<R> {
+    type Item = Result<Datagrid>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let chunk = self.chunks.next()?;
+        let schema = self.chunks.schema().clone();
+        Some(
+            chunk
+                .map_err(anyhow::Error::from)
+                .and_then(|c| Datagrid::try_new(schema, c.into_arrays())),
+        )
+    }
+}
+
+// ------------------------------------------------------------------------------
+// write
+
+/// encoding of a column
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ColumnEncoding {
+    Plain,
+    /// values in a dictionary page, keys RLE/bit-packed
+    Dictionary,
+    /// integers and temporal types
+    DeltaBinaryPacked,
+    /// strings and binaries
+    DeltaLengthByteArray,
+}
+
+/// options of `ParquetWriter`
+#[derive(Debug, Clone)]
+pub struct ParquetWriteOptions {
+    pub compression: parquet_write::CompressionOptions,
+    /// rows of a row group, the last one may have less
+    pub row_group_size: usize,
+    /// encodings by column name, `ColumnEncoding::Plain` if missing
+    pub encodings: HashMap<String, ColumnEncoding>,
+}
+
+impl Default for ParquetWriteOptions {
+    fn default() -> Self {
+        Self {
+            compression: parquet_write::CompressionOptions::Uncompressed,
+            row_group_size: DEFAULT_ROW_GROUP_SIZE,
+            encodings: HashMap::new(),
+        }
+    }
+}
+
+/// Writer of Parquet row groups, grids are buffered until a row group is full.
+///
+/// The footer is written by `finish`, a file not finished is not valid.
+pub struct ParquetWriter<W: Write> {
+    writer: parquet_write::FileWriter<W>,
+    // schema of the grids written
+    schema: Schema,
+    // types as written, dictionaries of `ColumnEncoding::Dictionary`
+    data_types: Vec<DataType>,
+    encodings: Vec<Vec<parquet_write::Encoding>>,
+    parquet_fields: Vec<parquet_write::ParquetType>,
+    row_group_size: usize,
+    buffer: Vec<Datagrid>,
+    buffered: usize,
+}
+
+impl<W: Write> ParquetWriter<W> {
+    pub fn try_new(writer: W, schema: &Schema, options: ParquetWriteOptions) -> Result<Self> {
+        if options.row_group_size == 0 {
+            return Err(anyhow!("row_group_size must be positive"));
+        }
+        if let Some(c) = options
+            .encodings
+            .keys()
+            .find(|c| !schema.fields.iter().any(|f| &f.name == *c))
+        {
+            return Err(anyhow!("encoding of column {c} which is not in the schema"));
+        }
+
+        let mut fields = vec![];
+        let mut encodings = vec![];
+        for field in &schema.fields {
+            let encoding = options
+                .encodings
+                .get(&field.name)
+                .copied()
+                .unwrap_or(ColumnEncoding::Plain);
+            let (field, encoding) = encode(field, encoding)?;
+            encodings.push(parquet_write::transverse(field.data_type(), |_| encoding));
+            fields.push(field);
+        }
+        let logical = schema.clone();
+        let schema = Schema::from(fields);
+
+        let write_options = parquet_write::WriteOptions {
+            write_statistics: true,
+            compression: options.compression,
+            version: parquet_write::Version::V2,
+            data_pagesize_limit: None,
+        };
+        let parquet_fields = parquet_write::to_parquet_schema(&schema)?.fields().to_vec();
+        let data_types = schema
+            .fields
+            .iter()
+            .map(|f| f.data_type().clone())
+            .collect();
+        let writer = parquet_write::FileWriter::try_new(writer, schema, write_options)?;
+
+        Ok(Self {
+            writer,
+            schema: logical,
+            data_types,
+            encodings,
+            parquet_fields,
+            row_group_size: options.row_group_size,
+            buffer: vec![],
+            buffered: 0,
+        })
+    }
+
+    pub fn write(&mut self, datagrid: &Datagrid) -> Result<()> {
+        let (fl, al) = (self.schema.fields.len(), datagrid.arrays().len());
+        if fl != al {
+            return Err(anyhow!(
+                "length does not match: fields.len {fl} & arrays.len {al}"
+            ));
+        }
+        if let Some((f, a)) = self
+            .schema
+            .fields
+            .iter()
+            .zip(datagrid.arrays().iter())
+            .find(|(f, a)| f.data_type() != a.data_type())
+        {
+            return Err(anyhow!(
+                "column {}: array of {:?} for a field of {:?}",
+                f.name,
+                a.data_type(),
+                f.data_type()
+            ));
+        }
+
+        let mut offset = 0;
+        while offset < datagrid.len() {
+            let length = (self.row_group_size - self.buffered).min(datagrid.len() - offset);
+            self.buffer.push(datagrid.slice(offset, length)?);
+            self.buffered += length;
+            offset += length;
+
+            if self.buffered == self.row_group_size {
+                self.flush()?;
+            }
+        }
+
+        Ok(())
+    }
+
+    // write the buffered grids as one row group
+    fn flush(&mut self) -> Result<()> {
+        if self.buffered == 0 {
+            return Ok(());
+        }
+
+        let arrays = (0..self.data_types.len())
+            .map(|i| {
+                let arrays = self
+                    .buffer
+                    .iter()
+                    .map(|g| g.arrays()[i].as_ref())
+                    .collect::<Vec<_>>();
+                let array = concatenate(&arrays)?;
+                // types are checked by `write`, only a dictionary to encode differs
+                match &self.data_types[i] {
+                    dt @ DataType::Dictionary(..) if array.data_type() != dt => {
+                        Ok(cast(array.as_ref(), dt, CastOptions::default())?)
+                    }
+                    _ => Ok(array),
+                }
+            })
+            .collect::<Result<Vec<_>>>()?;
+        self.buffer.clear();
+        self.buffered = 0;
+
+        let row_group = parquet_write::row_group_iter(
+            Chunk::try_new(arrays)?,
+            self.encodings.clone(),
+            self.parquet_fields.clone(),
+            self.writer.options(),
+        );
+        self.writer.write(row_group)?;
+
+        Ok(())
+    }
+
+    /// write the last row group and the footer
+    pub fn finish(mut self) -> Result<W> {
+        self.flush()?;
+        let _size = self.writer.end(None)?;
+
+        Ok(self.writer.into_inner())
+    }
+}
+
+// field as written and its encoding
+fn encode(field: &Field, encoding: ColumnEncoding) -> Result<(Field, parquet_write::Encoding)> {
+    let (data_type, encoding) = match encoding {
+        ColumnEncoding::Plain => (field.data_type().clone(), parquet_write::Encoding::Plain),
+        ColumnEncoding::Dictionary => match field.data_type() {
+            dt @ DataType::Dictionary(..) => (dt.clone(), parquet_write::Encoding::RleDictionary),
+            dt => (
+                DataType::Dictionary(IntegerType::UInt32, Box::new(dt.clone()), false),
+                parquet_write::Encoding::RleDictionary,
+            ),
+        },
+        ColumnEncoding::DeltaBinaryPacked => (
+            field.data_type().clone(),
+            parquet_write::Encoding::DeltaBinaryPacked,
+        ),
+        ColumnEncoding::DeltaLengthByteArray => (
+            field.data_type().clone(),
+            parquet_write::Encoding::DeltaLengthByteArray,
+        ),
+    };
+
+    if !parquet_write::can_encode(&data_type, encoding) {
+        return Err(anyhow!(
+            "column {} of {:?} can't be encoded as {encoding:?}",
+            field.name,
+            field.data_type()
+        ));
+    }
+
+    Ok((
+        Field::new(&field.name, data_type, field.is_nullable),
+        encoding,
+    ))
+}
+
+#[cfg(test)]
+mod test_parquet {
+    use std::io::Cursor;
+
+    use arrow2::array::*;
+    use arrow2::scalar::PrimitiveScalar;
+
+    use super::*;
+
+    fn write(row_group_size: usize) -> Vec<u8> {
+        let datagrid = |range: std::ops::Range<i32>| {
+            let a = Int32Array::from(range.clone().map(Some).collect::<Vec<_>>()).boxed();
+            let b = Utf8Array::<i32>::from(
+                range
+                    .map(|i| Some(format!("v{}", i % 3)))
+                    .collect::<Vec<_>>(),
+            )
+            .boxed();
+            Datagrid::try_from_names(&["a", "b"], vec![a, b]).unwrap()
+        };
+        let schema = datagrid(0..1).schema().clone();
+
+        let options = ParquetWriteOptions {
+            row_group_size,
+            encodings: HashMap::from([
+                ("a".to_owned(), ColumnEncoding::DeltaBinaryPacked),
+                ("b".to_owned(), ColumnEncoding::Dictionary),
+            ]),
+            ..Default::default()
+        };
+        let mut writer = ParquetWriter::try_new(vec![], &schema, options).unwrap();
+        writer.write(&datagrid(0..15)).unwrap();
+        writer.write(&datagrid(15..30)).unwrap();
+        writer.finish().unwrap()
+    }
+
+    #[test]
+    fn row_groups_success() {
+        let bytes = write(10);
+
+        let reader = ParquetReader::try_new(Cursor::new(bytes), Default::default()).unwrap();
+        assert_eq!(reader.row_groups(), 3);
+        let lens = reader.map(|d| d.unwrap().len()).collect::<Vec<_>>();
+        assert_eq!(lens, vec![10, 10, 10]);
+    }
+
+    #[test]
+    fn mismatch_failure() {
+        let schema = Schema::from(vec![Field::new("a", DataType::Int32, true)]);
+        let a = Int64Array::from_slice([1, 2]).boxed();
+        let datagrid = Datagrid::try_from_names(&["a"], vec![a]).unwrap();
+
+        let mut writer = ParquetWriter::try_new(vec![], &schema, Default::default()).unwrap();
+        let err = writer.write(&datagrid).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "column a: array of Int64 for a field of Int32"
+        );
+    }
+
+    #[test]
+    fn projection_success() {
+        let bytes = write(10);
+
+        let options = ParquetReadOptions {
+            columns: Some(vec!["b".to_owned()]),
+            ..Default::default()
+        };
+        let mut reader = ParquetReader::try_new(Cursor::new(bytes.clone()), options).unwrap();
+        assert_eq!(reader.schema().fields.len(), 1);
+        let grid = reader.next().unwrap().unwrap();
+        assert_eq!(grid.arrays().len(), 1);
+
+        let options = ParquetReadOptions {
+            columns: Some(vec!["c".to_owned()]),
+            ..Default::default()
+        };
+        assert!(ParquetReader::try_new(Cursor::new(bytes), options).is_err());
+    }
+
+    #[test]
+    fn predicate_success() {
+        let bytes = write(10);
+        let int = |v: i32| -> Box<dyn Scalar> { Box::new(PrimitiveScalar::from(Some(v))) };
+
+        let cases = [
+            (Predicate::Eq("a".to_owned(), int(12)), 1),
+            (Predicate::Lt("a".to_owned(), int(10)), 1),
+            (Predicate::GtEq("a".to_owned(), int(10)), 2),
+            (Predicate::Gt("a".to_owned(), int(100)), 0),
+            (
+                Predicate::Or(vec![
+                    Predicate::LtEq("a".to_owned(), int(0)),
+                    Predicate::Eq("a".to_owned(), int(29)),
+                ]),
+                2,
+            ),
+        ];
+        for (predicate, row_groups) in cases {
+            let options = ParquetReadOptions {
+                predicate: Some(predicate),
+                ..Default::default()
+            };
+            let reader = ParquetReader::try_new(Cursor::new(bytes.clone()), options).unwrap();
+            assert_eq!(reader.row_groups(), row_groups);
+        }
+
+        let options = ParquetReadOptions {
+            predicate: Some(Predicate::Eq("b".to_owned(), int(1))),
+            ..Default::default()
+        };
+        assert!(ParquetReader::try_new(Cursor::new(bytes), options).is_err());
+    }
+}
@@ -0,0 +1,650 @@
+//! Rows
+//!
+//! Query results as Arrow arrays, see `Connector::query_datagrid`.
+//!
+//! The kind of each column is chosen once, from the type info given by `describe`, so
+//! that every batch of a query has the same Arrow types:
+//!
+//! | Kind        | DataType                      | MySQL                   | Postgres          | MSSQL    |
+//! |-------------|-------------------------------|-------------------------|-------------------|----------|
+//! | Bool        | Boolean                       | BOOLEAN                 | BOOL              | BIT      |
+//! | integers    | same width & sign             | [UNSIGNED] INT...       | INT2/4/8, "CHAR"  | INT...   |
+//! | F32, F64    | Float32, Float64              | FLOAT, DOUBLE           | FLOAT4, FLOAT8    | REAL...  |
+//! | Text        | Utf8                          | CHAR, TEXT, ENUM, JSON  | TEXT, JSON, enums | NVARCHAR |
+//! | Decimal     | Utf8, not to lose digits      | DECIMAL                 | NUMERIC           |          |
+//! | Uuid        | Utf8, hyphenated              |                         | UUID              |          |
+//! | Bytes       | Binary                        | BINARY, BLOB, BIT       | BYTEA             |          |
+//! | Date        | Date32                        | DATE                    | DATE              |          |
+//! | Time        | Time64(µs)                    | TIME                    | TIME              |          |
+//! | Timestamp   | Timestamp(µs)                 | DATETIME                | TIMESTAMP         |          |
+//! | TimestampTz | Timestamp(µs, "+00:00")       | TIMESTAMP               | TIMESTAMPTZ       |          |
+//!
+//! Other types are rejected, and may be cast to text in the query.
+
+use anyhow::{anyhow, Result};
+use arrow2::array::*;
+use arrow2::datatypes::{DataType, Field, Schema, TimeUnit};
+use arrow2::types::NativeType;
+use futures::stream::BoxStream;
+use sqlx::mssql::MssqlTypeInfo;
+use sqlx::mysql::MySqlTypeInfo;
+use sqlx::postgres::{PgTypeInfo, PgTypeKind};
+use sqlx::types::chrono::{DateTime, NaiveDate, NaiveDateTime, NaiveTime, Utc};
+use sqlx::{Column, Database, Decode, Mssql, MySql, Postgres, Row, Type, TypeInfo};
+
+use crate::datagrid::Datagrid;
+
+/// time zone of `ColumnKind::TimestampTz`, values are UTC instants
+pub const UTC_OFFSET: &str = "+00:00";
+
+/// how a column is decoded
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ColumnKind {
+    Bool,
+    I8,
+    I16,
+    I32,
+    I64,
+    U8,
+    U16,
+    U32,
+    U64,
+    F32,
+    F64,
+    Text,
+    Decimal,
+    Uuid,
+    Bytes,
+    Date,
+    Time,
+    Timestamp,
+    TimestampTz,
+}
+
+impl ColumnKind {
+    pub fn data_type(&self) -> DataType {
+        match self {
+            ColumnKind::Bool => DataType::Boolean,
+            ColumnKind::I8 => DataType::Int8,
+            ColumnKind::I16 => DataType::Int16,
+            ColumnKind::I32 => DataType::Int32,
+            ColumnKind::I64 => DataType::Int64,
+            ColumnKind::U8 => DataType::UInt8,
+            ColumnKind::U16 => DataType::UInt16,
+            ColumnKind::U32 => DataType::UInt32,
+            ColumnKind::U64 => DataType::UInt64,
+            ColumnKind::F32 => DataType::Float32,
+            ColumnKind::F64 => DataType::Float64,
+            ColumnKind::Text | ColumnKind::Decimal | ColumnKind::Uuid => DataType::Utf8,
+            ColumnKind::Bytes => DataType::Binary,
+            ColumnKind::Date => DataType::Date32,
+            ColumnKind::Time => DataType::Time64(TimeUnit::Microsecond),
+            ColumnKind::Timestamp => DataType::Timestamp(TimeUnit::Microsecond, None),
+            ColumnKind::TimestampTz => {
+                DataType::Timestamp(TimeUnit::Microsecond, Some(UTC_OFFSET.to_owned()))
+            }
+        }
+    }
+
+    fn builder(&self, capacity: usize) -> Box<dyn MutableArray> {
+        fn primitive<T: NativeType>(capacity: usize, data_type: DataType) -> Box<dyn MutableArray> {
+            Box::new(MutablePrimitiveArray::<T>::with_capacity(capacity).to(data_type))
+        }
+
+        let data_type = self.data_type();
+        match self {
+            ColumnKind::Bool => Box::new(MutableBooleanArray::with_capacity(capacity)),
+            ColumnKind::I8 => primitive::<i8>(capacity, data_type),
+            ColumnKind::I16 => primitive::<i16>(capacity, data_type),
+            ColumnKind::I32 | ColumnKind::Date => primitive::<i32>(capacity, data_type),
+            ColumnKind::I64
+            | ColumnKind::Time
+            | ColumnKind::Timestamp
+            | ColumnKind::TimestampTz => primitive::<i64>(capacity, data_type),
+            ColumnKind::U8 => primitive::<u8>(capacity, data_type),
+            ColumnKind::U16 => primitive::<u16>(capacity, data_type),
+            ColumnKind::U32 => primitive::<u32>(capacity, data_type),
+            ColumnKind::U64 => primitive::<u64>(capacity, data_type),
+            ColumnKind::F32 => primitive::<f32>(capacity, data_type),
+            ColumnKind::F64 => primitive::<f64>(capacity, data_type),
+            ColumnKind::Text | ColumnKind::Decimal | ColumnKind::Uuid => {
+                Box::new(MutableUtf8Array::<i32>::with_capacity(capacity))
+            }
+            ColumnKind::Bytes => Box::new(MutableBinaryArray::<i32>::with_capacity(capacity)),
+        }
+    }
+}
+
+/// a column of a query result
+#[derive(Debug, Clone)]
+pub struct ColumnSpec {
+    pub name: String,
+    pub kind: ColumnKind,
+    pub nullable: bool,
+}
+
+impl ColumnSpec {
+    pub fn field(&self) -> Field {
+        Field::new(&self.name, self.kind.data_type(), self.nullable)
+    }
+}
+
+/// Arrow schema of columns, in order
+pub fn gen_schema(columns: &[ColumnSpec]) -> Schema {
+    Schema::from(columns.iter().map(ColumnSpec::field).collect::<Vec<_>>())
+}
+
+/// result of `Connector::query_datagrid`, the schema is known before any row is fetched
+pub struct DatagridStream<'a> {
+    pub schema: Schema,
+    pub batches: BoxStream<'a, Result<Datagrid>>,
+}
+
+/// Decoding of the columns of a database into Arrow arrays.
+pub trait ArrowDecode: Database {
+    /// `None` if the type is not supported
+    fn column_kind(type_info: &Self::TypeInfo) -> Option<ColumnKind>;
+
+    /// push the value of column `idx` to `array`, built for `kind`
+    fn push(
+        kind: ColumnKind,
+        array: &mut dyn MutableArray,
+        row: &Self::Row,
+        idx: usize,
+    ) -> Result<()>;
+}
+
+/// kinds of the columns given by `describe`
+pub fn column_specs<DB: ArrowDecode>(
+    columns: &[DB::Column],
+    nullable: impl Fn(usize) -> Option<bool>,
+) -> Result<Vec<ColumnSpec>> {
+    columns
+        .iter()
+        .enumerate()
+        .map(|(i, c)| {
+            let kind = DB::column_kind(c.type_info()).ok_or_else(|| {
+                anyhow!(
+                    "column {}: unsupported type {:?}, cast it to text",
+                    c.name(),
+                    c.type_info()
+                )
+            })?;
+            Ok(ColumnSpec {
+                name: c.name().to_owned(),
+                kind,
+                // unknown for expressions
+                nullable: nullable(i).unwrap_or(true),
+            })
+        })
+        .collect()
+}
+
+/// a batch of rows as a `Datagrid`
+pub fn rows_to_datagrid<DB: ArrowDecode>(
+    columns: &[ColumnSpec],
+    rows: &[DB::Row],
+) -> Result<Datagrid> {
+    let mut arrays = columns
+        .iter()
+        .map(|c| c.kind.builder(rows.len()))
+        .collect::<Vec<_>>();
+
+    for row in rows {
+        for (idx, (column, array)) in columns.iter().zip(arrays.iter_mut()).enumerate() {
+            DB::push(column.kind, array.as_mut(), row, idx)
+                .map_err(|e| anyhow!("column {}: {e}", column.name))?;
+        }
+    }
+
+    Datagrid::try_new(
+        gen_schema(columns),
+        arrays.iter_mut().map(|a| a.as_box()).collect(),
+    )
+}
+
+// ------------------------------------------------------------------------------
+// push
+
+fn downcast<A: 'static>(array: &mut dyn MutableArray) -> &mut A {
+    array
+        .as_mut_any()
+        .downcast_mut::<A>()
+        .expect("array built by `ColumnKind::builder`")
+}
+
+fn push_decoded<'r, DB, T, O, F>(
+    array: &mut dyn MutableArray,
+    row: &'r DB::Row,
+    idx: usize,
+    f: F,
+) -> Result<()>
+where
+    DB: Database,
+    T: Decode<'r, DB> + Type<DB>,
+    O: NativeType,
+    F: Fn(T) -> O,
+    usize: sqlx::ColumnIndex<DB::Row>,
+{
+    let value: Option<T> = row.try_get(idx)?;
+    downcast::<MutablePrimitiveArray<O>>(array).push(value.map(f));
+    Ok(())
+}
+
+fn push_primitive<'r, DB, T>(
+    array: &mut dyn MutableArray,
+    row: &'r DB::Row,
+    idx: usize,
+) -> Result<()>
+where
+    DB: Database,
+    T: Decode<'r, DB> + Type<DB> + NativeType,
+    usize: sqlx::ColumnIndex<DB::Row>,
+{
+    push_decoded::<DB, T, T, _>(array, row, idx, |v| v)
+}
+
+fn push_bool<'r, DB>(array: &mut dyn MutableArray, row: &'r DB::Row, idx: usize) -> Result<()>
+where
+    DB: Database,
+    bool: Decode<'r, DB> + Type<DB>,
+    usize: sqlx::ColumnIndex<DB::Row>,
+{
+    let value: Option<bool> = row.try_get(idx)?;
+    downcast::<MutableBooleanArray>(array).push(value);
+    Ok(())
+}
+
+// bytes of the value, whatever its type, e.g. text of a MySQL `DECIMAL`
+fn raw_bytes<'r, DB>(row: &'r DB::Row, idx: usize) -> Result<Option<&'r [u8]>>
+where
+    DB: Database,
+    &'r [u8]: Decode<'r, DB>,
+    usize: sqlx::ColumnIndex<DB::Row>,
+{
+    Ok(row.try_get_unchecked(idx)?)
+}
+
+fn push_raw_text<'r, DB, F>(
+    array: &mut dyn MutableArray,
+    row: &'r DB::Row,
+    idx: usize,
+    f: F,
+) -> Result<()>
+where
+    DB: Database,
+    &'r [u8]: Decode<'r, DB>,
+    usize: sqlx::ColumnIndex<DB::Row>,
+    F: Fn(&[u8]) -> Result<String>,
+{
+    let value = raw_bytes::<DB>(row, idx)?.map(f).transpose()?;
+    downcast::<MutableUtf8Array<i32>>(array).push(value);
+    Ok(())
+}
+
+fn push_bytes<'r, DB>(array: &mut dyn MutableArray, row: &'r DB::Row, idx: usize) -> Result<()>
+where
+    DB: Database,
+    &'r [u8]: Decode<'r, DB>,
+    usize: sqlx::ColumnIndex<DB::Row>,
+{
+    let value = raw_bytes::<DB>(row, idx)?;
+    downcast::<MutableBinaryArray<i32>>(array).push(value);
+    Ok(())
+}
+
+fn utf8(bytes: &[u8]) -> Result<String> {
+    Ok(std::str::from_utf8(bytes)?.to_owned())
+}
+
+fn date32(date: NaiveDate) -> i32 {
+    let epoch = NaiveDate::from_ymd_opt(1970, 1, 1).unwrap();
+    date.signed_duration_since(epoch).num_days() as i32
+}
+
+fn time64(time: NaiveTime) -> i64 {
+    let midnight = NaiveTime::from_hms_opt(0, 0, 0).unwrap();
+    // never overflows within a day
+    time.signed_duration_since(midnight)
+        .num_microseconds()
+        .unwrap_or_default()
+}
+
+fn timestamp(datetime: NaiveDateTime) -> i64 {
+    datetime.and_utc().timestamp_micros()
+}
+
+fn timestamp_tz(datetime: DateTime<Utc>) -> i64 {
+    datetime.timestamp_micros()
+}
+
+/// `NUMERIC` of Postgres in binary: digit count, weight, sign & scale, then base 10000 digits
+pub fn pg_numeric(bytes: &[u8]) -> Result<String> {
+    let word = |i: usize| -> Result<i16> {
+        let b = bytes
+            .get(i * 2..i * 2 + 2)
+            .ok_or_else(|| anyhow!("invalid NUMERIC of {} bytes", bytes.len()))?;
+        Ok(i16::from_be_bytes([b[0], b[1]]))
+    };
+    let (ndigits, weight, sign, dscale) = (word(0)?, word(1)?, word(2)? as u16, word(3)?);
+    let digits = (0..ndigits.max(0) as usize)
+        .map(|i| word(4 + i))
+        .collect::<Result<Vec<_>>>()?;
+    let digit = |i: i32| {
+        usize::try_from(i)
+            .ok()
+            .and_then(|i| digits.get(i).copied())
+            .unwrap_or(0)
+    };
+
+    let mut s = match sign {
+        0x0000 => String::new(),
+        0x4000 => String::from("-"),
+        0xC000 => return Ok("NaN".to_owned()),
+        0xD000 => return Ok("Infinity".to_owned()),
+        0xF000 => return Ok("-Infinity".to_owned()),
+        _ => return Err(anyhow!("invalid NUMERIC sign {sign:#x}")),
+    };
+
+    let weight = weight as i32;
+    if weight < 0 {
+        s.push('0');
+    }
+    for i in 0..=weight {
+        if i == 0 {
+            s.push_str(&digit(i).to_string());
+        } else {
+            s.push_str(&format!("{:04}", digit(i)));
+        }
+    }
+
+    let dscale = dscale.max(0) as usize;
+    if dscale > 0 {
+        let mut fraction = String::new();
+        let mut i = weight + 1;
+        while fraction.len() < dscale {
+            fraction.push_str(&format!("{:04}", digit(i)));
+            i += 1;
+        }
+        fraction.truncate(dscale);
+        s.push('.');
+        s.push_str(&fraction);
+    }
+
+    Ok(s)
+}
+
+/// `UUID` of Postgres in binary, 16 bytes
+pub fn pg_uuid(bytes: &[u8]) -> Result<String> {
+    if bytes.len() != 16 {
+        return Err(anyhow!("invalid UUID of {} bytes", bytes.len()));
+    }
+    let hex = bytes.iter().map(|b| format!("{b:02x}")).collect::<String>();
+    Ok(format!(
+        "{}-{}-{}-{}-{}",
+        &hex[..8],
+        &hex[8..12],
+        &hex[12..16],
+        &hex[16..20],
+        &hex[20..]
+    ))
+}
+
+// `JSONB` of Postgres in binary, a version byte then text
+fn pg_jsonb(bytes: &[u8]) -> Result<String> {
+    match bytes.split_first() {
+        Some((1, json)) => utf8(json),
+        _ => Err(anyhow!("invalid JSONB")),
+    }
+}
+
+// ------------------------------------------------------------------------------
+// databases
+
+impl ArrowDecode for MySql {
+    fn column_kind(type_info: &MySqlTypeInfo) -> Option<ColumnKind> {
+        let kind = match type_info.name() {
+            "BOOLEAN" => ColumnKind::Bool,
+            "TINYINT" => ColumnKind::I8,
+            "SMALLINT" => ColumnKind::I16,
+            "INT" | "MEDIUMINT" => ColumnKind::I32,
+            "BIGINT" => ColumnKind::I64,
+            "TINYINT UNSIGNED" => ColumnKind::U8,
+            "SMALLINT UNSIGNED" | "YEAR" => ColumnKind::U16,
+            "INT UNSIGNED" | "MEDIUMINT UNSIGNED" => ColumnKind::U32,
+            "BIGINT UNSIGNED" => ColumnKind::U64,
+            "FLOAT" => ColumnKind::F32,
+            "DOUBLE" => ColumnKind::F64,
+            "CHAR" | "VARCHAR" | "TINYTEXT" | "TEXT" | "MEDIUMTEXT" | "LONGTEXT" | "ENUM"
+            | "SET" | "JSON" | "NULL" => ColumnKind::Text,
+            "DECIMAL" => ColumnKind::Decimal,
+            "BINARY" | "VARBINARY" | "TINYBLOB" | "BLOB" | "MEDIUMBLOB" | "LONGBLOB" | "BIT"
+            | "GEOMETRY" => ColumnKind::Bytes,
+            "DATE" => ColumnKind::Date,
+            "TIME" => ColumnKind::Time,
+            "DATETIME" => ColumnKind::Timestamp,
+            "TIMESTAMP" => ColumnKind::TimestampTz,
+            _ => return None,
+        };
+        Some(kind)
+    }
+
+    fn push(
+        kind: ColumnKind,
+        array: &mut dyn MutableArray,
+        row: &Self::Row,
+        idx: usize,
+    ) -> Result<()> {
+        match kind {
+            ColumnKind::Bool => push_bool::<MySql>(array, row, idx),
+            ColumnKind::I8 => push_primitive::<MySql, i8>(array, row, idx),
+            ColumnKind::I16 => push_primitive::<MySql, i16>(array, row, idx),
+            ColumnKind::I32 => push_primitive::<MySql, i32>(array, row, idx),
+            ColumnKind::I64 => push_primitive::<MySql, i64>(array, row, idx),
+            ColumnKind::U8 => push_primitive::<MySql, u8>(array, row, idx),
+            ColumnKind::U16 => push_primitive::<MySql, u16>(array, row, idx),
+            ColumnKind::U32 => push_primitive::<MySql, u32>(array, row, idx),
+            ColumnKind::U64 => push_primitive::<MySql, u64>(array, row, idx),
+            ColumnKind::F32 => push_primitive::<MySql, f32>(array, row, idx),
+            ColumnKind::F64 => push_primitive::<MySql, f64>(array, row, idx),
+            // sent as text, whatever the collation
+            ColumnKind::Text | ColumnKind::Decimal | ColumnKind::Uuid => {
+                push_raw_text::<MySql, _>(array, row, idx, utf8)
+            }
+            ColumnKind::Bytes => push_bytes::<MySql>(array, row, idx),
+            ColumnKind::Date => push_decoded::<MySql, _, _, _>(array, row, idx, date32),
+            ColumnKind::Time => push_decoded::<MySql, _, _, _>(array, row, idx, time64),
+            ColumnKind::Timestamp => push_decoded::<MySql, _, _, _>(array, row, idx, timestamp),
+            ColumnKind::TimestampTz => {
+                push_decoded::<MySql, _, _, _>(array, row, idx, timestamp_tz)
+            }
+        }
+    }
+}
+
+impl ArrowDecode for Postgres {
+    fn column_kind(type_info: &PgTypeInfo) -> Option<ColumnKind> {
+        let kind = match type_info.name() {
+            "BOOL" => ColumnKind::Bool,
+            "\"CHAR\"" => ColumnKind::I8,
+            "INT2" => ColumnKind::I16,
+            "INT4" => ColumnKind::I32,
+            "INT8" => ColumnKind::I64,
+            "FLOAT4" => ColumnKind::F32,
+            "FLOAT8" => ColumnKind::F64,
+            "TEXT" | "VARCHAR" | "BPCHAR" | "NAME" | "UNKNOWN" | "CITEXT" | "JSON" | "JSONB" => {
+                ColumnKind::Text
+            }
+            "NUMERIC" => ColumnKind::Decimal,
+            "UUID" => ColumnKind::Uuid,
+            "BYTEA" => ColumnKind::Bytes,
+            "DATE" => ColumnKind::Date,
+            "TIME" => ColumnKind::Time,
+            "TIMESTAMP" => ColumnKind::Timestamp,
+            "TIMESTAMPTZ" => ColumnKind::TimestampTz,
+            // labels in binary as well
+            _ if matches!(type_info.kind(), PgTypeKind::Enum(_)) => ColumnKind::Text,
+            _ => return None,
+        };
+        Some(kind)
+    }
+
+    fn push(
+        kind: ColumnKind,
+        array: &mut dyn MutableArray,
+        row: &Self::Row,
+        idx: usize,
+    ) -> Result<()> {
+        match kind {
+            ColumnKind::Bool => push_bool::<Postgres>(array, row, idx),
+            ColumnKind::I8 => push_primitive::<Postgres, i8>(array, row, idx),
+            ColumnKind::I16 => push_primitive::<Postgres, i16>(array, row, idx),
+            ColumnKind::I32 => push_primitive::<Postgres, i32>(array, row, idx),
+            ColumnKind::I64 => push_primitive::<Postgres, i64>(array, row, idx),
+            ColumnKind::F32 => push_primitive::<Postgres, f32>(array, row, idx),
+            ColumnKind::F64 => push_primitive::<Postgres, f64>(array, row, idx),
+            ColumnKind::Text => {
+                let jsonb = row.column(idx).type_info().name() == "JSONB";
+                push_raw_text::<Postgres, _>(array, row, idx, if jsonb { pg_jsonb } else { utf8 })
+            }
+            ColumnKind::Decimal => push_raw_text::<Postgres, _>(array, row, idx, pg_numeric),
+            ColumnKind::Uuid => push_raw_text::<Postgres, _>(array, row, idx, pg_uuid),
+            ColumnKind::Bytes => push_bytes::<Postgres>(array, row, idx),
+            ColumnKind::Date => push_decoded::<Postgres, _, _, _>(array, row, idx, date32),
+            ColumnKind::Time => push_decoded::<Postgres, _, _, _>(array, row, idx, time64),
+            ColumnKind::Timestamp => push_decoded::<Postgres, _, _, _>(array, row, idx, timestamp),
+            ColumnKind::TimestampTz => {
+                push_decoded::<Postgres, _, _, _>(array, row, idx, timestamp_tz)
+            }
+            ColumnKind::U8 | ColumnKind::U16 | ColumnKind::U32 | ColumnKind::U64 => {
+                Err(anyhow!("{kind:?} is not a type of Postgres"))
+            }
+        }
+    }
+}
+
+impl ArrowDecode for Mssql {
+    // by compatibility, since sqlx only names the types it decodes
+    fn column_kind(type_info: &MssqlTypeInfo) -> Option<ColumnKind> {
+        let kind = if <bool as Type<Mssql>>::compatible(type_info) {
+            ColumnKind::Bool
+        } else if <u8 as Type<Mssql>>::compatible(type_info) {
+            // `TINYINT` is unsigned
+            ColumnKind::U8
+        } else if <i16 as Type<Mssql>>::compatible(type_info) {
+            ColumnKind::I16
+        } else if <i32 as Type<Mssql>>::compatible(type_info) {
+            ColumnKind::I32
+        } else if <i64 as Type<Mssql>>::compatible(type_info) {
+            ColumnKind::I64
+        } else if <f32 as Type<Mssql>>::compatible(type_info) {
+            ColumnKind::F32
+        } else if <f64 as Type<Mssql>>::compatible(type_info) {
+            ColumnKind::F64
+        } else if <String as Type<Mssql>>::compatible(type_info) {
+            ColumnKind::Text
+        } else {
+            return None;
+        };
+        Some(kind)
+    }
+
+    fn push(
+        kind: ColumnKind,
+        array: &mut dyn MutableArray,
+        row: &Self::Row,
+        idx: usize,
+    ) -> Result<()> {
+        match kind {
+            ColumnKind::Bool => push_bool::<Mssql>(array, row, idx),
+            ColumnKind::U8 => push_primitive::<Mssql, u8>(array, row, idx),
+            ColumnKind::I16 => push_primitive::<Mssql, i16>(array, row, idx),
+            ColumnKind::I32 => push_primitive::<Mssql, i32>(array, row, idx),
+            ColumnKind::I64 => push_primitive::<Mssql, i64>(array, row, idx),
+            ColumnKind::F32 => push_primitive::<Mssql, f32>(array, row, idx),
+            ColumnKind::F64 => push_primitive::<Mssql, f64>(array, row, idx),
+            // UTF-16 on the wire, hence owned
+            ColumnKind::Text => {
+                let value: Option<String> = row.try_get(idx)?;
+                downcast::<MutableUtf8Array<i32>>(array).push(value);
+                Ok(())
+            }
+            _ => Err(anyhow!("{kind:?} is not supported by MSSQL")),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test_rows {
+    use super::*;
+
+    fn numeric(ndigits: i16, weight: i16, sign: u16, dscale: i16, digits: &[i16]) -> Vec<u8> {
+        let mut bytes = vec![];
+        bytes.extend(ndigits.to_be_bytes());
+        bytes.extend(weight.to_be_bytes());
+        bytes.extend(sign.to_be_bytes());
+        bytes.extend(dscale.to_be_bytes());
+        digits.iter().for_each(|d| bytes.extend(d.to_be_bytes()));
+        bytes
+    }
+
+    #[test]
+    fn pg_numeric_success() {
+        let cases = [
+            (numeric(3, 1, 0, 3, &[1, 2345, 6780]), "12345.678"),
+            (numeric(1, -1, 0, 4, &[1]), "0.0001"),
+            (numeric(1, -2, 0x4000, 5, &[1000]), "-0.00001"),
+            (numeric(1, 2, 0, 0, &[7]), "700000000"),
+            (numeric(0, 0, 0, 2, &[]), "0.00"),
+            (numeric(0, 0, 0xC000, 0, &[]), "NaN"),
+        ];
+        for (bytes, expected) in cases {
+            assert_eq!(pg_numeric(&bytes).unwrap(), expected);
+        }
+        assert!(pg_numeric(&[0, 1]).is_err());
+    }
+
+    #[test]
+    fn pg_uuid_success() {
+        let bytes = (0..16u8).map(|b| b * 17).collect::<Vec<_>>();
+        assert_eq!(
+            pg_uuid(&bytes).unwrap(),
+            "00112233-4455-6677-8899-aabbccddeeff"
+        );
+        assert!(pg_uuid(&bytes[1..]).is_err());
+    }
+
+    #[test]
+    fn chrono_success() {
+        let date = NaiveDate::from_ymd_opt(1970, 1, 2).unwrap();
+        assert_eq!(date32(date), 1);
+        let time = NaiveTime::from_hms_micro_opt(0, 0, 1, 5).unwrap();
+        assert_eq!(time64(time), 1_000_005);
+        assert_eq!(timestamp(date.and_time(time)), 86_401_000_005);
+    }
+
+    #[test]
+    fn schema_success() {
+        let columns = vec![
+            ColumnSpec {
+                name: "id".to_owned(),
+                kind: ColumnKind::I64,
+                nullable: false,
+            },
+            ColumnSpec {
+                name: "at".to_owned(),
+                kind: ColumnKind::TimestampTz,
+                nullable: true,
+            },
+        ];
+        let schema = gen_schema(&columns);
+        assert_eq!(schema.fields[0], Field::new("id", DataType::Int64, false));
+        assert_eq!(
+            schema.fields[1].data_type,
+            DataType::Timestamp(TimeUnit::Microsecond, Some(UTC_OFFSET.to_owned()))
+        );
+
+        let array = ColumnKind::Date.builder(2).as_box();
+        assert_eq!(array.data_type(), &DataType::Date32);
+    }
+}
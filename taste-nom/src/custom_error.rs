@@ -2,31 +2,82 @@
 //!
 //! Turn Nom error into custom error
 
-use nom::error::{ErrorKind, ParseError};
+use nom::error::{ContextError, ErrorKind, ParseError};
 use thiserror::Error;
 
+/// Nom error which remembers where it happened.
+///
+/// Parsers only know the input left, so `offset` is filled by `CustomError::locate`
+/// once the whole input is known again.
 #[derive(Debug)]
-pub struct CustomError(pub String);
+pub struct CustomError {
+    pub message: String,
+    /// innermost `nom::error::context` of the failure
+    pub context: Option<&'static str>,
+    /// byte offset in the whole input
+    pub offset: Option<usize>,
+    // length of the input left at the failure
+    remaining: usize,
+}
+
+impl CustomError {
+    pub fn new(input: &str, message: impl Into<String>) -> Self {
+        CustomError {
+            message: message.into(),
+            context: None,
+            offset: None,
+            remaining: input.len(),
+        }
+    }
+
+    /// turn the remaining length into an offset of `input`
+    pub fn locate(mut self, input: &str) -> Self {
+        self.offset = Some(input.len().saturating_sub(self.remaining));
+        self
+    }
+}
 
 impl std::fmt::Display for CustomError {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(f, "{}", self.0)
+        if let Some(ctx) = self.context {
+            write!(f, "invalid {ctx}")?;
+            if let Some(offset) = self.offset {
+                write!(f, " at byte {offset}")?;
+            }
+            write!(f, ": {}", self.message)
+        } else {
+            match self.offset {
+                Some(offset) => write!(f, "{} at byte {offset}", self.message),
+                None => write!(f, "{}", self.message),
+            }
+        }
     }
 }
 
 impl<'a> From<(&'a str, ErrorKind)> for CustomError {
     fn from(error: (&'a str, ErrorKind)) -> Self {
-        CustomError(format!("error code was: {error:?}"))
+        CustomError::new(error.0, format!("error code was: {:?}", error.1))
     }
 }
 
 impl<'a> ParseError<&'a str> for CustomError {
-    fn from_error_kind(_: &'a str, kind: ErrorKind) -> Self {
-        CustomError(format!("error code was: {kind:?}"))
+    fn from_error_kind(input: &'a str, kind: ErrorKind) -> Self {
+        CustomError::new(input, format!("error code was: {kind:?}"))
     }
 
+    // the innermost position is kept, it is the most accurate one
     fn append(_: &'a str, kind: ErrorKind, other: CustomError) -> Self {
-        CustomError(format!("{other:?}\nerror code was: {kind:?}"))
+        CustomError {
+            message: format!("{}\nerror code was: {kind:?}", other.message),
+            ..other
+        }
+    }
+}
+
+impl<'a> ContextError<&'a str> for CustomError {
+    fn add_context(_: &'a str, ctx: &'static str, mut other: Self) -> Self {
+        other.context.get_or_insert(ctx);
+        other
     }
 }
 
@@ -35,6 +86,9 @@ pub enum TasteNomError {
     #[error(transparent)]
     CustomNom(nom::Err<CustomError>),
 
+    #[error("parsing error: {0}")]
+    Parse(CustomError),
+
     #[error("sql error: {0}")]
     Sql(String),
 
@@ -42,6 +96,18 @@ pub enum TasteNomError {
     ParseInt(#[from] std::num::ParseIntError),
 }
 
+impl TasteNomError {
+    /// a located `Parse` error out of a failed parser of `input`
+    pub fn located(input: &str, error: nom::Err<CustomError>) -> Self {
+        match error {
+            nom::Err::Error(e) | nom::Err::Failure(e) => TasteNomError::Parse(e.locate(input)),
+            nom::Err::Incomplete(_) => {
+                TasteNomError::Parse(CustomError::new("", "incomplete input").locate(input))
+            }
+        }
+    }
+}
+
 impl From<nom::Err<CustomError>> for TasteNomError {
     fn from(error: nom::Err<CustomError>) -> Self {
         TasteNomError::CustomNom(error)
@@ -0,0 +1,1231 @@
+//! Database DDL
+//!
+//! `CREATE TABLE` statements of MySQL, Postgres, SQLite & MSSQL:
+//! 1. `parse_ddl` reads a statement into a `CreateTable`
+//! 1. `CreateTable::to_ddl` writes it in any of them, e.g. to move a table from MySQL to Postgres
+//!
+//! Columns, types, nullability, defaults, auto increments, primary, unique & foreign keys
+//! are kept. Inline constraints, e.g. `id INT PRIMARY KEY`, are moved to the table.
+//! Indexes, checks, collations, comments & table options are skipped.
+
+use nom::branch::alt;
+use nom::bytes::complete::{tag, tag_no_case, take_while1};
+use nom::character::complete::{char, digit0, digit1, multispace0, none_of};
+use nom::combinator::{all_consuming, cut, eof, map, not, opt, recognize, rest, verify};
+use nom::error::context;
+use nom::multi::{many0, separated_list1};
+use nom::sequence::{delimited, pair, preceded, terminated, tuple};
+use nom::IResult;
+
+use crate::custom_error::{CustomError, TasteNomError};
+use crate::database_conn::quoted;
+use crate::database_types_nom::{parse_sql_type, DbType, ParsingError, SqlType, ValueType};
+
+/// `DEFAULT` of a column
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DefaultValue {
+    Null,
+    Bool(bool),
+    /// as written, e.g. `-1.5`
+    Number(String),
+    /// unquoted
+    Text(String),
+    /// `CURRENT_TIMESTAMP`, `NOW()`, `GETDATE()`...
+    CurrentTimestamp,
+    /// any other expression, as written
+    Expr(String),
+}
+
+/// `ON DELETE` & `ON UPDATE` of a foreign key
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RefAction {
+    Cascade,
+    SetNull,
+    SetDefault,
+    Restrict,
+    NoAction,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ColumnDef {
+    pub name: String,
+    pub value_type: ValueType,
+    pub nullable: bool,
+    pub default: Option<DefaultValue>,
+    /// `AUTO_INCREMENT`, `SERIAL`, `IDENTITY`...
+    pub auto_increment: bool,
+    /// `ON UPDATE CURRENT_TIMESTAMP` of MySQL, only written for MySQL as the other
+    /// databases need a trigger for it
+    pub on_update_current_timestamp: bool,
+}
+
+/// `PRIMARY KEY` or `UNIQUE`
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct KeyDef {
+    /// name of the constraint
+    pub name: Option<String>,
+    pub columns: Vec<String>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ForeignKey {
+    pub name: Option<String>,
+    pub columns: Vec<String>,
+    pub foreign_schema: Option<String>,
+    pub foreign_table: String,
+    /// empty for the primary key of the foreign table
+    pub foreign_columns: Vec<String>,
+    pub on_delete: Option<RefAction>,
+    pub on_update: Option<RefAction>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct CreateTable {
+    pub schema: Option<String>,
+    pub name: String,
+    pub temporary: bool,
+    pub if_not_exists: bool,
+    pub columns: Vec<ColumnDef>,
+    pub primary_key: Option<KeyDef>,
+    pub unique_keys: Vec<KeyDef>,
+    pub foreign_keys: Vec<ForeignKey>,
+}
+
+// ------------------------------------------------------------------------------
+// grammar
+
+// words starting a table constraint rather than a column
+const TABLE_CONSTRAINTS: [&str; 10] = [
+    "CONSTRAINT",
+    "PRIMARY",
+    "UNIQUE",
+    "FOREIGN",
+    "CHECK",
+    "EXCLUDE",
+    "KEY",
+    "INDEX",
+    "FULLTEXT",
+    "SPATIAL",
+];
+
+// words starting an attribute of a column, they end a default expression,
+// and a column of SQLite may have no type
+const COLUMN_ATTRIBUTES: [&str; 15] = [
+    "CONSTRAINT",
+    "NOT",
+    "NULL",
+    "PRIMARY",
+    "UNIQUE",
+    "CHECK",
+    "DEFAULT",
+    "REFERENCES",
+    "COLLATE",
+    "GENERATED",
+    "AUTO_INCREMENT",
+    "AUTOINCREMENT",
+    "IDENTITY",
+    "COMMENT",
+    "ON",
+];
+
+// a column or table constraint, inline ones name their column
+enum Constraint {
+    PrimaryKey(KeyDef),
+    Unique(KeyDef),
+    ForeignKey(ForeignKey),
+}
+
+// a column before its type is mapped
+struct RawColumn {
+    name: String,
+    sql_type: Option<SqlType>,
+    not_null: bool,
+    default: Option<DefaultValue>,
+    auto_increment: bool,
+    on_update_current_timestamp: bool,
+    constraints: Vec<Constraint>,
+}
+
+enum ColumnAttr {
+    NotNull,
+    Null,
+    Default(DefaultValue),
+    AutoIncrement,
+    OnUpdateCurrentTimestamp,
+    PrimaryKey,
+    Unique,
+    References(ForeignKey),
+    Other,
+}
+
+enum TableElement {
+    Column(RawColumn),
+    Constraint(Constraint),
+    Other,
+}
+
+struct Statement {
+    name: (Option<String>, String),
+    temporary: bool,
+    if_not_exists: bool,
+    elements: Vec<TableElement>,
+}
+
+fn is_identifier_char(c: char) -> bool {
+    c.is_alphanumeric() || c == '_' || c == '$'
+}
+
+fn keyword<'a>(
+    word: &'static str,
+) -> impl FnMut(&'a str) -> IResult<&'a str, &'a str, CustomError> {
+    terminated(tag_no_case(word), not(take_while1(is_identifier_char)))
+}
+
+// keywords separated by whitespace, e.g. `PRIMARY KEY`, leading whitespace included
+fn phrase<'a>(
+    words: &'static str,
+) -> impl FnMut(&'a str) -> IResult<&'a str, &'a str, CustomError> {
+    move |input: &'a str| {
+        let mut rest = input;
+        for w in words.split(' ') {
+            (rest, _) = preceded(multispace0, keyword(w))(rest)?;
+        }
+        Ok((rest, &input[..input.len() - rest.len()]))
+    }
+}
+
+// a plain word, or quoted by `"`, backticks or brackets, `#` starts a temporary table of MSSQL
+fn identifier(input: &str) -> IResult<&str, String, CustomError> {
+    preceded(
+        multispace0,
+        alt((
+            quoted('"', '"'),
+            quoted('`', '`'),
+            quoted('[', ']'),
+            map(
+                recognize(pair(many0(char('#')), take_while1(is_identifier_char))),
+                String::from,
+            ),
+        )),
+    )(input)
+}
+
+// `[catalog.][schema.]name`
+fn table_name(input: &str) -> IResult<&str, (Option<String>, String), CustomError> {
+    let dot = preceded(multispace0, char('.'));
+    let (rest, mut parts) = separated_list1(dot, identifier)(input)?;
+    let name = parts.pop().unwrap_or_default();
+    Ok((rest, (parts.pop(), name)))
+}
+
+fn group(input: &str) -> IResult<&str, &str, CustomError> {
+    recognize(delimited(char('('), many0(token_in_group), cut(char(')'))))(input)
+}
+
+// a word, a literal, a group in parentheses or any other character, but `,` & `)`
+fn token(input: &str) -> IResult<&str, &str, CustomError> {
+    preceded(
+        multispace0,
+        alt((
+            take_while1(is_identifier_char),
+            recognize(quoted('\'', '\'')),
+            recognize(quoted('"', '"')),
+            recognize(quoted('`', '`')),
+            recognize(quoted('[', ']')),
+            group,
+            recognize(none_of(",)")),
+        )),
+    )(input)
+}
+
+// within parentheses, `,` is a token as well
+fn token_in_group(input: &str) -> IResult<&str, &str, CustomError> {
+    alt((token, preceded(multispace0, tag(","))))(input)
+}
+
+// `(a, b(10) DESC)`, lengths & orders are skipped
+fn column_list(input: &str) -> IResult<&str, Vec<String>, CustomError> {
+    let key_part = terminated(identifier, many0(token));
+    let comma = preceded(multispace0, char(','));
+    delimited(
+        preceded(multispace0, char('(')),
+        separated_list1(comma, key_part),
+        preceded(multispace0, cut(char(')'))),
+    )(input)
+}
+
+fn constraint_name(input: &str) -> IResult<&str, Option<String>, CustomError> {
+    opt(preceded(phrase("CONSTRAINT"), cut(identifier)))(input)
+}
+
+fn ref_action(input: &str) -> IResult<&str, RefAction, CustomError> {
+    alt((
+        map(phrase("CASCADE"), |_| RefAction::Cascade),
+        map(phrase("SET NULL"), |_| RefAction::SetNull),
+        map(phrase("SET DEFAULT"), |_| RefAction::SetDefault),
+        map(phrase("RESTRICT"), |_| RefAction::Restrict),
+        map(phrase("NO ACTION"), |_| RefAction::NoAction),
+    ))(input)
+}
+
+// `REFERENCES table [(columns)] [ON DELETE action] [ON UPDATE action]`
+fn references(input: &str) -> IResult<&str, ForeignKey, CustomError> {
+    let (rest, _) = phrase("REFERENCES")(input)?;
+    let (rest, (foreign_schema, foreign_table)) = context("foreign key", cut(table_name))(rest)?;
+    let (mut rest, foreign_columns) = opt(column_list)(rest)?;
+    let mut fk = ForeignKey {
+        foreign_schema,
+        foreign_table,
+        foreign_columns: foreign_columns.unwrap_or_default(),
+        ..Default::default()
+    };
+
+    loop {
+        if let Ok((r, action)) = preceded(phrase("ON DELETE"), cut(ref_action))(rest) {
+            fk.on_delete = Some(action);
+            rest = r;
+        } else if let Ok((r, action)) = preceded(phrase("ON UPDATE"), cut(ref_action))(rest) {
+            fk.on_update = Some(action);
+            rest = r;
+        } else {
+            return Ok((rest, fk));
+        }
+    }
+}
+
+impl DefaultValue {
+    // an expression as written
+    fn from_expr(expr: &str) -> Self {
+        let mut expr = expr.trim();
+        // parentheses of MSSQL, e.g. `((0))`
+        while let Ok(("", _)) = group(expr) {
+            expr = expr[1..expr.len() - 1].trim();
+        }
+
+        let mut number = all_consuming(recognize(tuple((
+            opt(char::<_, CustomError>('-')),
+            digit1,
+            opt(pair(char('.'), digit0)),
+        ))));
+        // `N'text'` of MSSQL, `'text'::character varying` of Postgres
+        let mut text = all_consuming(terminated(
+            preceded(opt(tag_no_case("N")), quoted('\'', '\'')),
+            opt(preceded(tag("::"), rest)),
+        ));
+        let mut now = all_consuming(pair(
+            alt((
+                tag_no_case::<_, _, CustomError>("CURRENT_TIMESTAMP"),
+                tag_no_case("LOCALTIMESTAMP"),
+                tag_no_case("NOW"),
+                tag_no_case("GETDATE"),
+                tag_no_case("SYSDATETIME"),
+            )),
+            opt(delimited(char('('), digit0, char(')'))),
+        ));
+
+        match expr.to_uppercase().as_str() {
+            "NULL" => return DefaultValue::Null,
+            "TRUE" => return DefaultValue::Bool(true),
+            "FALSE" => return DefaultValue::Bool(false),
+            "DATETIME('NOW')" => return DefaultValue::CurrentTimestamp,
+            _ => {}
+        }
+        if number(expr).is_ok() {
+            DefaultValue::Number(expr.to_owned())
+        } else if let Ok((_, t)) = text(expr) {
+            DefaultValue::Text(t)
+        } else if now(expr).is_ok() {
+            DefaultValue::CurrentTimestamp
+        } else {
+            DefaultValue::Expr(expr.to_owned())
+        }
+    }
+}
+
+// an expression up to the next attribute of the column
+fn default_value(input: &str) -> IResult<&str, DefaultValue, CustomError> {
+    let not_attribute = |t: &&str| !COLUMN_ATTRIBUTES.contains(&t.to_uppercase().as_str());
+    let (rest, expr) = recognize(pair(token, many0(verify(token, not_attribute))))(input)?;
+    Ok((rest, DefaultValue::from_expr(expr)))
+}
+
+// `ON UPDATE` of MySQL only takes the current time
+fn is_now(value: &DefaultValue) -> bool {
+    *value == DefaultValue::CurrentTimestamp
+}
+
+fn auto_increment(input: &str) -> IResult<&str, &str, CustomError> {
+    let identity = alt((phrase("GENERATED ALWAYS"), phrase("GENERATED BY DEFAULT")));
+    alt((
+        phrase("AUTO_INCREMENT"),
+        phrase("AUTOINCREMENT"),
+        // `IDENTITY(1, 1)` of MSSQL
+        recognize(pair(phrase("IDENTITY"), opt(preceded(multispace0, group)))),
+        recognize(tuple((
+            identity,
+            phrase("AS IDENTITY"),
+            opt(preceded(multispace0, group)),
+        ))),
+    ))(input)
+}
+
+fn column_attr(input: &str) -> IResult<&str, ColumnAttr, CustomError> {
+    let key_options = many0(alt((
+        phrase("ASC"),
+        phrase("DESC"),
+        phrase("CLUSTERED"),
+        phrase("NONCLUSTERED"),
+    )));
+    alt((
+        map(phrase("NOT NULL"), |_| ColumnAttr::NotNull),
+        map(phrase("NULL"), |_| ColumnAttr::Null),
+        map(
+            preceded(phrase("DEFAULT"), context("default", cut(default_value))),
+            ColumnAttr::Default,
+        ),
+        map(auto_increment, |_| ColumnAttr::AutoIncrement),
+        map(
+            preceded(
+                phrase("ON UPDATE"),
+                context("on update", cut(verify(default_value, is_now))),
+            ),
+            |_| ColumnAttr::OnUpdateCurrentTimestamp,
+        ),
+        map(pair(phrase("PRIMARY KEY"), key_options), |_| {
+            ColumnAttr::PrimaryKey
+        }),
+        map(pair(phrase("UNIQUE"), opt(phrase("KEY"))), |_| {
+            ColumnAttr::Unique
+        }),
+        map(references, ColumnAttr::References),
+        map(token, |_| ColumnAttr::Other),
+    ))(input)
+}
+
+fn column_def(input: &str) -> IResult<&str, RawColumn, CustomError> {
+    let (rest, name) = identifier(input)?;
+
+    let (mut rest, sql_type) = match token(rest) {
+        Ok((_, t)) if !COLUMN_ATTRIBUTES.contains(&t.to_uppercase().as_str()) => {
+            let (rest, sql_type) = context("column type", parse_sql_type)(rest)?;
+            (rest, Some(sql_type))
+        }
+        _ => (rest, None),
+    };
+
+    let mut column = RawColumn {
+        name,
+        sql_type,
+        not_null: false,
+        default: None,
+        auto_increment: false,
+        on_update_current_timestamp: false,
+        constraints: vec![],
+    };
+    loop {
+        let (r, _) = multispace0(rest)?;
+        if r.is_empty() || r.starts_with([',', ')']) {
+            return Ok((r, column));
+        }
+        let (r, name) = constraint_name(r)?;
+        let (r, attr) = column_attr(r)?;
+        let key = || KeyDef {
+            name: name.clone(),
+            columns: vec![column.name.clone()],
+        };
+        match attr {
+            ColumnAttr::NotNull => column.not_null = true,
+            ColumnAttr::Null => column.not_null = false,
+            ColumnAttr::Default(d) => column.default = Some(d),
+            ColumnAttr::AutoIncrement => column.auto_increment = true,
+            ColumnAttr::OnUpdateCurrentTimestamp => column.on_update_current_timestamp = true,
+            ColumnAttr::PrimaryKey => {
+                let key = key();
+                column.constraints.push(Constraint::PrimaryKey(key));
+            }
+            ColumnAttr::Unique => {
+                let key = key();
+                column.constraints.push(Constraint::Unique(key));
+            }
+            ColumnAttr::References(fk) => {
+                let fk = ForeignKey {
+                    name,
+                    columns: vec![column.name.clone()],
+                    ..fk
+                };
+                column.constraints.push(Constraint::ForeignKey(fk));
+            }
+            ColumnAttr::Other => {}
+        }
+        rest = r;
+    }
+}
+
+// `[CONSTRAINT name] PRIMARY KEY (a, b)`, `UNIQUE`, `FOREIGN KEY` or any other constraint, skipped
+fn table_constraint(input: &str) -> IResult<&str, TableElement, CustomError> {
+    let clustered = || opt(alt((phrase("CLUSTERED"), phrase("NONCLUSTERED"))));
+    let (rest, name) = constraint_name(input)?;
+
+    let (rest, element) = if let Ok((r, _)) = pair(phrase("PRIMARY KEY"), clustered())(rest) {
+        let (r, columns) = context("primary key", cut(column_list))(r)?;
+        let key = KeyDef { name, columns };
+        (r, TableElement::Constraint(Constraint::PrimaryKey(key)))
+    } else if let Ok((r, _)) = phrase("UNIQUE")(rest) {
+        // `UNIQUE KEY name (a)` of MySQL
+        let index = alt((phrase("KEY"), phrase("INDEX")));
+        let (r, (_, _, index_name)) = tuple((opt(index), clustered(), opt(identifier)))(r)?;
+        let (r, columns) = context("unique key", cut(column_list))(r)?;
+        let key = KeyDef {
+            name: name.or(index_name),
+            columns,
+        };
+        (r, TableElement::Constraint(Constraint::Unique(key)))
+    } else if let Ok((r, _)) = phrase("FOREIGN KEY")(rest) {
+        let (r, index_name) = opt(identifier)(r)?;
+        let (r, columns) = context("foreign key", cut(column_list))(r)?;
+        let (r, fk) = context("foreign key", cut(references))(r)?;
+        let fk = ForeignKey {
+            name: name.or(index_name),
+            columns,
+            ..fk
+        };
+        (r, TableElement::Constraint(Constraint::ForeignKey(fk)))
+    } else {
+        (rest, TableElement::Other)
+    };
+
+    // e.g. `WITH (...) ON [PRIMARY]` of MSSQL, `DEFERRABLE` of Postgres
+    let (rest, _) = many0(token)(rest)?;
+    Ok((rest, element))
+}
+
+fn table_element(input: &str) -> IResult<&str, TableElement, CustomError> {
+    let (input, _) = multispace0(input)?;
+    match token(input) {
+        Ok((_, t)) if TABLE_CONSTRAINTS.contains(&t.to_uppercase().as_str()) => {
+            table_constraint(input)
+        }
+        _ => context("column", map(column_def, TableElement::Column))(input),
+    }
+}
+
+// `CREATE [TEMPORARY] TABLE [IF NOT EXISTS] name (element, ...)`, table options are ignored
+fn create_table(input: &str) -> IResult<&str, Statement, CustomError> {
+    let temporary = alt((phrase("TEMPORARY"), phrase("TEMP"), phrase("UNLOGGED")));
+    let elements = delimited(
+        pair(multispace0, char('(')),
+        separated_list1(char(','), table_element),
+        pair(multispace0, char(')')),
+    );
+
+    let (rest, (_, temporary, _)) =
+        tuple((phrase("CREATE"), opt(temporary), phrase("TABLE")))(input)?;
+    let (rest, if_not_exists) = opt(phrase("IF NOT EXISTS"))(rest)?;
+    let (rest, name) = context("table name", cut(table_name))(rest)?;
+    let (rest, elements) = context("table definition", cut(elements))(rest)?;
+    let (rest, _) = many0(alt((token, tag(","), tag(")"))))(rest)?;
+    let (rest, _) = multispace0(rest)?;
+
+    let statement = Statement {
+        name,
+        temporary: temporary.is_some_and(|t| !t.trim().eq_ignore_ascii_case("UNLOGGED")),
+        if_not_exists: if_not_exists.is_some(),
+        elements,
+    };
+    Ok((rest, statement))
+}
+
+impl RawColumn {
+    fn build(self, db: DbType) -> Result<(ColumnDef, Vec<Constraint>), ParsingError> {
+        let value_type = match &self.sql_type {
+            Some(t) => t.value_type(db)?,
+            // no type affinity, any value is stored as given
+            None if db == DbType::Sqlite => ValueType::Binary(None),
+            None => return Err(ParsingError::InvalidDataType(format!("{db:?}"), self.name)),
+        };
+        let serial = self
+            .sql_type
+            .as_ref()
+            .is_some_and(|t| t.name.ends_with("SERIAL"));
+        // `SERIAL` of Postgres as dumped by `pg_dump`
+        let sequence = matches!(
+            &self.default,
+            Some(DefaultValue::Expr(e)) if e.to_lowercase().starts_with("nextval(")
+        );
+
+        let column = ColumnDef {
+            name: self.name,
+            value_type,
+            nullable: !(self.not_null || serial),
+            default: if sequence { None } else { self.default },
+            auto_increment: self.auto_increment || serial || sequence,
+            on_update_current_timestamp: self.on_update_current_timestamp,
+        };
+        Ok((column, self.constraints))
+    }
+}
+
+impl CreateTable {
+    fn add_constraint(&mut self, constraint: Constraint) {
+        match constraint {
+            Constraint::PrimaryKey(key) => self.primary_key = Some(key),
+            Constraint::Unique(key) => self.unique_keys.push(key),
+            Constraint::ForeignKey(fk) => self.foreign_keys.push(fk),
+        }
+    }
+}
+
+/// a `CREATE TABLE` statement of `db`, trailing table options & `;` are ignored
+pub fn parse_ddl(db: DbType, input: &str) -> Result<CreateTable, ParsingError> {
+    let statement = match terminated(create_table, eof)(input) {
+        Ok((_, s)) => s,
+        Err(e) => {
+            let e = TasteNomError::located(input, e);
+            return Err(ParsingError::Parsing(e.to_string()));
+        }
+    };
+
+    let (schema, mut name) = statement.name;
+    let mut temporary = statement.temporary;
+    // `#name` of MSSQL
+    if name.starts_with('#') {
+        name = name.trim_start_matches('#').to_owned();
+        temporary = true;
+    }
+    let mut table = CreateTable {
+        schema,
+        name,
+        temporary,
+        if_not_exists: statement.if_not_exists,
+        ..Default::default()
+    };
+
+    for element in statement.elements {
+        match element {
+            TableElement::Column(raw) => {
+                let (column, constraints) = raw.build(db)?;
+                table.columns.push(column);
+                constraints
+                    .into_iter()
+                    .for_each(|c| table.add_constraint(c));
+            }
+            TableElement::Constraint(c) => table.add_constraint(c),
+            TableElement::Other => {}
+        }
+    }
+
+    // columns of the primary key are never null
+    if let Some(pk) = &table.primary_key {
+        for column in table.columns.iter_mut() {
+            if pk.columns.contains(&column.name) {
+                column.nullable = false;
+            }
+        }
+    }
+
+    Ok(table)
+}
+
+// ------------------------------------------------------------------------------
+// emission
+
+/// `name` quoted the way of `db`
+pub fn quote_identifier(name: &str, db: DbType) -> String {
+    match db {
+        DbType::Mysql => format!("`{}`", name.replace('`', "``")),
+        DbType::Postgres | DbType::Sqlite => format!("\"{}\"", name.replace('"', "\"\"")),
+        DbType::Mssql => format!("[{}]", name.replace(']', "]]")),
+    }
+}
+
+fn quote_literal(text: &str) -> String {
+    format!("'{}'", text.replace('\'', "''"))
+}
+
+// `(p)` bounded by the max precision of a database, empty if unknown
+fn precision(p: Option<u32>, max: u32) -> String {
+    p.map(|p| format!("({})", p.min(max))).unwrap_or_default()
+}
+
+impl ValueType {
+    /// type of a column in `db`, the closest one if `db` has no such type, e.g. unsigned
+    /// integers are widened, and arrays are stored as JSON but in Postgres
+    pub fn to_sql(&self, db: DbType) -> String {
+        use DbType::*;
+
+        match (self, db) {
+            (ValueType::Bool, Mssql) => "BIT".to_owned(),
+            (ValueType::Bool, _) => "BOOLEAN".to_owned(),
+            (
+                ValueType::U8
+                | ValueType::U16
+                | ValueType::U32
+                | ValueType::U64
+                | ValueType::I8
+                | ValueType::I16
+                | ValueType::I32
+                | ValueType::I64,
+                Sqlite,
+            ) => "INTEGER".to_owned(),
+            (ValueType::U8, Mysql) => "TINYINT UNSIGNED".to_owned(),
+            (ValueType::U8, Mssql) => "TINYINT".to_owned(),
+            (ValueType::I8, Mysql) => "TINYINT".to_owned(),
+            (ValueType::U8 | ValueType::I8 | ValueType::I16, _) => "SMALLINT".to_owned(),
+            (ValueType::U16, Mysql) => "SMALLINT UNSIGNED".to_owned(),
+            (ValueType::U16 | ValueType::I32, Postgres) => "INTEGER".to_owned(),
+            (ValueType::U16 | ValueType::I32, _) => "INT".to_owned(),
+            (ValueType::U32, Mysql) => "INT UNSIGNED".to_owned(),
+            (ValueType::U32 | ValueType::I64, _) => "BIGINT".to_owned(),
+            (ValueType::U64, Mysql) => "BIGINT UNSIGNED".to_owned(),
+            (ValueType::U64, Postgres) => "NUMERIC(20)".to_owned(),
+            (ValueType::U64, _) => "DECIMAL(20)".to_owned(),
+            (ValueType::F32, Mysql) => "FLOAT".to_owned(),
+            (ValueType::F32, _) => "REAL".to_owned(),
+            (ValueType::F64, Mysql) => "DOUBLE".to_owned(),
+            (ValueType::F64, Postgres) => "DOUBLE PRECISION".to_owned(),
+            (ValueType::F64, Sqlite) => "REAL".to_owned(),
+            (ValueType::F64, Mssql) => "FLOAT".to_owned(),
+            (ValueType::String, Mysql) => "LONGTEXT".to_owned(),
+            (ValueType::String, Mssql) => "NVARCHAR(MAX)".to_owned(),
+            (ValueType::String, _) => "TEXT".to_owned(),
+            (ValueType::Char(n), Mssql) => format!("NCHAR({n})"),
+            (ValueType::Char(n), _) => format!("CHAR({n})"),
+            (ValueType::Varchar(n), Mssql) if *n > 4000 => "NVARCHAR(MAX)".to_owned(),
+            (ValueType::Varchar(n), Mssql) => format!("NVARCHAR({n})"),
+            (ValueType::Varchar(n), _) => format!("VARCHAR({n})"),
+            (ValueType::Binary(Some(n)), Mysql) => format!("VARBINARY({n})"),
+            (ValueType::Binary(None), Mysql) => "LONGBLOB".to_owned(),
+            (ValueType::Binary(_), Postgres) => "BYTEA".to_owned(),
+            (ValueType::Binary(_), Sqlite) => "BLOB".to_owned(),
+            (ValueType::Binary(Some(n)), Mssql) if *n <= 8000 => format!("VARBINARY({n})"),
+            (ValueType::Binary(_), Mssql) => "VARBINARY(MAX)".to_owned(),
+            (ValueType::Decimal { precision, scale }, _) => {
+                let (name, max) = match db {
+                    Mysql => ("DECIMAL", 65),
+                    Postgres | Sqlite => ("NUMERIC", 1000),
+                    Mssql => ("DECIMAL", 38),
+                };
+                match (precision, scale) {
+                    (Some(p), Some(s)) => format!("{name}({}, {s})", p.min(&max)),
+                    (Some(p), None) => format!("{name}({})", p.min(&max)),
+                    // unbounded, with as many decimals as integer digits
+                    (None, _) if db == Mysql || db == Mssql => {
+                        format!("{name}({max}, {})", max / 2)
+                    }
+                    (None, _) => name.to_owned(),
+                }
+            }
+            (ValueType::Date, _) => "DATE".to_owned(),
+            (ValueType::Time { .. }, Sqlite) => "TIME".to_owned(),
+            (
+                ValueType::Time {
+                    precision: p,
+                    time_zone: true,
+                },
+                Postgres,
+            ) => format!("TIME{} WITH TIME ZONE", precision(*p, 6)),
+            (ValueType::Time { precision: p, .. }, Mssql) => format!("TIME{}", precision(*p, 7)),
+            (ValueType::Time { precision: p, .. }, _) => format!("TIME{}", precision(*p, 6)),
+            (ValueType::Timestamp { time_zone, .. }, Sqlite) => {
+                if *time_zone { "TIMESTAMP" } else { "DATETIME" }.to_owned()
+            }
+            (
+                ValueType::Timestamp {
+                    precision: p,
+                    time_zone,
+                },
+                Postgres,
+            ) => {
+                let tz = if *time_zone { " WITH TIME ZONE" } else { "" };
+                format!("TIMESTAMP{}{tz}", precision(*p, 6))
+            }
+            (
+                ValueType::Timestamp {
+                    precision: p,
+                    time_zone,
+                },
+                Mysql,
+            ) => {
+                // `TIMESTAMP` is converted from & to the session time zone
+                let name = if *time_zone { "TIMESTAMP" } else { "DATETIME" };
+                format!("{name}{}", precision(*p, 6))
+            }
+            (
+                ValueType::Timestamp {
+                    precision: p,
+                    time_zone,
+                },
+                Mssql,
+            ) => {
+                let name = if *time_zone {
+                    "DATETIMEOFFSET"
+                } else {
+                    "DATETIME2"
+                };
+                format!("{name}{}", precision(*p, 7))
+            }
+            (ValueType::Uuid, Mysql) => "CHAR(36)".to_owned(),
+            (ValueType::Uuid, Postgres) => "UUID".to_owned(),
+            (ValueType::Uuid, Sqlite) => "TEXT".to_owned(),
+            (ValueType::Uuid, Mssql) => "UNIQUEIDENTIFIER".to_owned(),
+            (ValueType::Json, Mysql) => "JSON".to_owned(),
+            (ValueType::Json, Postgres) => "JSONB".to_owned(),
+            (ValueType::Json, Sqlite) => "TEXT".to_owned(),
+            (ValueType::Json, Mssql) => "NVARCHAR(MAX)".to_owned(),
+            (ValueType::Enum(values), Mysql) => {
+                let values = values.iter().map(|v| quote_literal(v)).collect::<Vec<_>>();
+                format!("ENUM({})", values.join(", "))
+            }
+            (ValueType::Enum(values), _) => {
+                let len = values.iter().map(|v| v.chars().count()).max().unwrap_or(1);
+                let name = if db == Mssql { "NVARCHAR" } else { "VARCHAR" };
+                format!("{name}({})", len.max(1))
+            }
+            (
+                ValueType::Array {
+                    element,
+                    dimensions,
+                },
+                Postgres,
+            ) => {
+                let mut sql = element.to_sql(db);
+                for d in dimensions {
+                    match d {
+                        Some(n) => sql.push_str(&format!("[{n}]")),
+                        None => sql.push_str("[]"),
+                    }
+                }
+                sql
+            }
+            (ValueType::Array { .. }, _) => ValueType::Json.to_sql(db),
+        }
+    }
+}
+
+impl DefaultValue {
+    /// the value in `db`, for a column of `value_type`
+    pub fn to_sql(&self, value_type: &ValueType, db: DbType) -> String {
+        match self {
+            DefaultValue::Null => "NULL".to_owned(),
+            DefaultValue::Bool(b) => match db {
+                DbType::Mysql | DbType::Postgres => if *b { "TRUE" } else { "FALSE" }.to_owned(),
+                DbType::Sqlite | DbType::Mssql => if *b { "1" } else { "0" }.to_owned(),
+            },
+            DefaultValue::Number(n) => n.clone(),
+            DefaultValue::Text(t) => quote_literal(t),
+            DefaultValue::CurrentTimestamp => match (db, value_type) {
+                // must match the precision of the column
+                (
+                    DbType::Mysql,
+                    ValueType::Timestamp {
+                        precision: Some(p @ 1..),
+                        ..
+                    },
+                ) => format!("CURRENT_TIMESTAMP({})", p.min(&6)),
+                _ => "CURRENT_TIMESTAMP".to_owned(),
+            },
+            // expressions are only allowed in parentheses by MySQL & SQLite
+            DefaultValue::Expr(e) => format!("({e})"),
+        }
+    }
+}
+
+impl RefAction {
+    pub fn to_sql(self, db: DbType) -> &'static str {
+        match self {
+            RefAction::Cascade => "CASCADE",
+            RefAction::SetNull => "SET NULL",
+            RefAction::SetDefault => "SET DEFAULT",
+            // not supported by MSSQL, which behaves the same with `NO ACTION`
+            RefAction::Restrict if db == DbType::Mssql => "NO ACTION",
+            RefAction::Restrict => "RESTRICT",
+            RefAction::NoAction => "NO ACTION",
+        }
+    }
+}
+
+impl CreateTable {
+    /// `CREATE TABLE` statement in `db`, without trailing `;`
+    pub fn to_ddl(&self, db: DbType) -> String {
+        let quote = |name: &str| quote_identifier(name, db);
+        let quote_all = |names: &[String]| {
+            let names = names.iter().map(|n| quote(n)).collect::<Vec<_>>();
+            names.join(", ")
+        };
+        let constraint = |name: &Option<String>| match name {
+            Some(name) => format!("CONSTRAINT {} ", quote(name)),
+            None => String::new(),
+        };
+
+        let mut ddl = String::from("CREATE ");
+        if self.temporary && db != DbType::Mssql {
+            ddl.push_str("TEMPORARY ");
+        }
+        ddl.push_str("TABLE ");
+        // not supported by MSSQL
+        if self.if_not_exists && db != DbType::Mssql {
+            ddl.push_str("IF NOT EXISTS ");
+        }
+        if let Some(schema) = &self.schema {
+            ddl.push_str(&quote(schema));
+            ddl.push('.');
+        }
+        if self.temporary && db == DbType::Mssql {
+            ddl.push_str(&quote(&format!("#{}", self.name)));
+        } else {
+            ddl.push_str(&quote(&self.name));
+        }
+
+        // SQLite only takes `AUTOINCREMENT` on an `INTEGER PRIMARY KEY`
+        let rowid = match &self.primary_key {
+            Some(pk) if db == DbType::Sqlite && pk.columns.len() == 1 => self
+                .columns
+                .iter()
+                .find(|c| c.auto_increment && c.name == pk.columns[0]),
+            _ => None,
+        };
+
+        let mut lines = vec![];
+        for column in &self.columns {
+            let mut line = format!("{} ", quote(&column.name));
+            if rowid.is_some_and(|c| c.name == column.name) {
+                line.push_str("INTEGER PRIMARY KEY AUTOINCREMENT");
+                lines.push(line);
+                continue;
+            }
+
+            // an identity of Postgres is an integer, `NUMERIC(20)` is not
+            if column.auto_increment
+                && db == DbType::Postgres
+                && column.value_type == ValueType::U64
+            {
+                line.push_str("BIGINT");
+            } else {
+                line.push_str(&column.value_type.to_sql(db));
+            }
+            if !column.nullable {
+                line.push_str(" NOT NULL");
+            }
+            if let Some(default) = &column.default {
+                line.push_str(" DEFAULT ");
+                line.push_str(&default.to_sql(&column.value_type, db));
+            }
+            if column.on_update_current_timestamp && db == DbType::Mysql {
+                let now = DefaultValue::CurrentTimestamp.to_sql(&column.value_type, db);
+                line.push_str(&format!(" ON UPDATE {now}"));
+            }
+            if column.auto_increment {
+                line.push_str(match db {
+                    DbType::Mysql => " AUTO_INCREMENT",
+                    DbType::Postgres => " GENERATED BY DEFAULT AS IDENTITY",
+                    DbType::Mssql => " IDENTITY(1, 1)",
+                    DbType::Sqlite => "",
+                });
+            }
+            lines.push(line);
+        }
+
+        if let Some(pk) = self.primary_key.as_ref().filter(|_| rowid.is_none()) {
+            let cols = quote_all(&pk.columns);
+            lines.push(format!("{}PRIMARY KEY ({cols})", constraint(&pk.name)));
+        }
+        for key in &self.unique_keys {
+            let cols = quote_all(&key.columns);
+            lines.push(format!("{}UNIQUE ({cols})", constraint(&key.name)));
+        }
+        for fk in &self.foreign_keys {
+            let mut line = format!(
+                "{}FOREIGN KEY ({}) REFERENCES ",
+                constraint(&fk.name),
+                quote_all(&fk.columns)
+            );
+            if let Some(schema) = &fk.foreign_schema {
+                line.push_str(&quote(schema));
+                line.push('.');
+            }
+            line.push_str(&quote(&fk.foreign_table));
+            if !fk.foreign_columns.is_empty() {
+                line.push_str(&format!(" ({})", quote_all(&fk.foreign_columns)));
+            }
+            if let Some(action) = fk.on_delete {
+                line.push_str(&format!(" ON DELETE {}", action.to_sql(db)));
+            }
+            if let Some(action) = fk.on_update {
+                line.push_str(&format!(" ON UPDATE {}", action.to_sql(db)));
+            }
+            lines.push(line);
+        }
+
+        ddl.push_str(" (\n    ");
+        ddl.push_str(&lines.join(",\n    "));
+        ddl.push_str("\n)");
+        ddl
+    }
+}
+
+#[cfg(test)]
+mod database_ddl_tests {
+    use super::*;
+
+    const MYSQL_DDL: &str = "CREATE TABLE `order` (
+  `id` bigint unsigned NOT NULL AUTO_INCREMENT,
+  `user_id` int NOT NULL,
+  `code` varchar(32) COLLATE utf8mb4_bin NOT NULL DEFAULT 'it''s',
+  `amount` decimal(10,2) DEFAULT NULL COMMENT 'total',
+  `paid` tinyint(1) NOT NULL DEFAULT '0',
+  `created_at` datetime(3) NOT NULL DEFAULT CURRENT_TIMESTAMP(3) ON UPDATE CURRENT_TIMESTAMP(3),
+  PRIMARY KEY (`id`),
+  UNIQUE KEY `uk_code` (`code`(16)),
+  KEY `idx_user` (`user_id`),
+  CONSTRAINT `fk_user` FOREIGN KEY (`user_id`) REFERENCES `user` (`id`) ON DELETE CASCADE
+) ENGINE=InnoDB AUTO_INCREMENT=7 DEFAULT CHARSET=utf8mb4;";
+
+    #[test]
+    fn mysql_ddl() {
+        let table = parse_ddl(DbType::Mysql, MYSQL_DDL).unwrap();
+        assert_eq!(table.name, "order");
+        assert_eq!(table.columns.len(), 6);
+
+        let id = &table.columns[0];
+        assert_eq!(id.value_type, ValueType::U64);
+        assert!(id.auto_increment && !id.nullable);
+        let code = &table.columns[2];
+        assert_eq!(code.default, Some(DefaultValue::Text("it's".to_owned())));
+        let amount = &table.columns[3];
+        assert_eq!(amount.default, Some(DefaultValue::Null));
+        assert!(amount.nullable);
+        assert_eq!(table.columns[4].value_type, ValueType::Bool);
+        let created_at = &table.columns[5];
+        assert_eq!(created_at.default, Some(DefaultValue::CurrentTimestamp));
+        assert!(created_at.on_update_current_timestamp);
+
+        let pk = table.primary_key.as_ref().unwrap();
+        assert_eq!(
+            (pk.name.as_deref(), pk.columns.as_slice()),
+            (None, &["id".to_owned()][..])
+        );
+        assert_eq!(
+            table.unique_keys,
+            vec![KeyDef {
+                name: Some("uk_code".to_owned()),
+                columns: vec!["code".to_owned()],
+            }]
+        );
+        assert_eq!(
+            table.foreign_keys,
+            vec![ForeignKey {
+                name: Some("fk_user".to_owned()),
+                columns: vec!["user_id".to_owned()],
+                foreign_schema: None,
+                foreign_table: "user".to_owned(),
+                foreign_columns: vec!["id".to_owned()],
+                on_delete: Some(RefAction::Cascade),
+                on_update: None,
+            }]
+        );
+
+        // to Postgres
+        let expected = r#"CREATE TABLE "order" (
+    "id" BIGINT NOT NULL GENERATED BY DEFAULT AS IDENTITY,
+    "user_id" INTEGER NOT NULL,
+    "code" VARCHAR(32) NOT NULL DEFAULT 'it''s',
+    "amount" NUMERIC(10, 2) DEFAULT NULL,
+    "paid" BOOLEAN NOT NULL DEFAULT '0',
+    "created_at" TIMESTAMP(3) NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    PRIMARY KEY ("id"),
+    CONSTRAINT "uk_code" UNIQUE ("code"),
+    CONSTRAINT "fk_user" FOREIGN KEY ("user_id") REFERENCES "user" ("id") ON DELETE CASCADE
+)"#;
+        assert_eq!(table.to_ddl(DbType::Postgres), expected);
+
+        // same dialect
+        let ddl = table.to_ddl(DbType::Mysql);
+        assert_eq!(parse_ddl(DbType::Mysql, &ddl).unwrap(), table);
+    }
+
+    #[test]
+    fn postgres_ddl() {
+        let ddl = r#"
+            CREATE UNLOGGED TABLE IF NOT EXISTS public.users (
+                id integer DEFAULT nextval('users_id_seq'::regclass) NOT NULL,
+                team_id bigint REFERENCES teams ON DELETE SET NULL ON UPDATE RESTRICT,
+                name character varying(64) DEFAULT 'anon'::character varying,
+                active boolean DEFAULT true NOT NULL,
+                tags text[] DEFAULT '{}'::text[],
+                valid_until date DEFAULT (CURRENT_DATE + 30),
+                CONSTRAINT users_pkey PRIMARY KEY (id),
+                CONSTRAINT users_name_key UNIQUE (name) DEFERRABLE,
+                CHECK (char_length(name) > 0)
+            );
+        "#;
+        let table = parse_ddl(DbType::Postgres, ddl).unwrap();
+        assert_eq!(table.schema.as_deref(), Some("public"));
+        assert!(table.if_not_exists && !table.temporary);
+
+        let id = &table.columns[0];
+        assert!(id.auto_increment && id.default.is_none());
+        let team_id = &table.columns[1];
+        assert!(team_id.nullable);
+        let fk = &table.foreign_keys[0];
+        assert_eq!(fk.columns, vec!["team_id"]);
+        assert_eq!(
+            (fk.foreign_table.as_str(), fk.foreign_columns.len()),
+            ("teams", 0)
+        );
+        assert_eq!(
+            (fk.on_delete, fk.on_update),
+            (Some(RefAction::SetNull), Some(RefAction::Restrict))
+        );
+        assert_eq!(
+            table.columns[2].default,
+            Some(DefaultValue::Text("anon".to_owned()))
+        );
+        assert_eq!(table.columns[3].default, Some(DefaultValue::Bool(true)));
+        assert_eq!(
+            table.columns[5].default,
+            Some(DefaultValue::Expr("CURRENT_DATE + 30".to_owned()))
+        );
+        assert_eq!(table.unique_keys[0].name.as_deref(), Some("users_name_key"));
+
+        // to MSSQL
+        let expected = "CREATE TABLE [public].[users] (
+    [id] INT NOT NULL IDENTITY(1, 1),
+    [team_id] BIGINT,
+    [name] NVARCHAR(64) DEFAULT 'anon',
+    [active] BIT NOT NULL DEFAULT 1,
+    [tags] NVARCHAR(MAX) DEFAULT '{}',
+    [valid_until] DATE DEFAULT (CURRENT_DATE + 30),
+    CONSTRAINT [users_pkey] PRIMARY KEY ([id]),
+    CONSTRAINT [users_name_key] UNIQUE ([name]),
+    FOREIGN KEY ([team_id]) REFERENCES [teams] ON DELETE SET NULL ON UPDATE NO ACTION
+)";
+        assert_eq!(table.to_ddl(DbType::Mssql), expected);
+
+        let ddl = table.to_ddl(DbType::Postgres);
+        assert_eq!(parse_ddl(DbType::Postgres, &ddl).unwrap(), table);
+    }
+
+    #[test]
+    fn mssql_ddl() {
+        let ddl = "CREATE TABLE [dbo].[#events](
+	[id] [int] IDENTITY(1,1) NOT NULL,
+	[kind] [nvarchar](max) NULL,
+	[score] [float] NOT NULL DEFAULT ((0)),
+	[at] [datetime2](7) NOT NULL CONSTRAINT [DF_at] DEFAULT (getdate()),
+	[uid] [uniqueidentifier] NOT NULL,
+ CONSTRAINT [PK_events] PRIMARY KEY CLUSTERED ([id] ASC)
+ WITH (PAD_INDEX = OFF, STATISTICS_NORECOMPUTE = OFF) ON [PRIMARY]
+) ON [PRIMARY] TEXTIMAGE_ON [PRIMARY]";
+        let table = parse_ddl(DbType::Mssql, ddl).unwrap();
+        assert_eq!((table.name.as_str(), table.temporary), ("events", true));
+        assert_eq!(table.columns[0].value_type, ValueType::I32);
+        assert!(table.columns[0].auto_increment);
+        assert_eq!(table.columns[1].value_type, ValueType::String);
+        assert_eq!(
+            table.columns[2].default,
+            Some(DefaultValue::Number("0".to_owned()))
+        );
+        assert_eq!(
+            table.columns[3].default,
+            Some(DefaultValue::CurrentTimestamp)
+        );
+        assert_eq!(
+            table.primary_key.as_ref().unwrap().name.as_deref(),
+            Some("PK_events")
+        );
+
+        // to SQLite
+        let expected = r#"CREATE TEMPORARY TABLE "dbo"."events" (
+    "id" INTEGER PRIMARY KEY AUTOINCREMENT,
+    "kind" TEXT,
+    "score" REAL NOT NULL DEFAULT 0,
+    "at" DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    "uid" TEXT NOT NULL
+)"#;
+        assert_eq!(table.to_ddl(DbType::Sqlite), expected);
+        // to MySQL
+        let ddl = table.to_ddl(DbType::Mysql);
+        assert!(
+            ddl.contains("`at` DATETIME(6) NOT NULL DEFAULT CURRENT_TIMESTAMP(6),"),
+            "{ddl}"
+        );
+        assert!(ddl.contains("`uid` CHAR(36) NOT NULL,"), "{ddl}");
+
+        let ddl = table.to_ddl(DbType::Mssql);
+        assert!(ddl.starts_with("CREATE TABLE [dbo].[#events] ("), "{ddl}");
+        assert_eq!(parse_ddl(DbType::Mssql, &ddl).unwrap(), table);
+    }
+
+    #[test]
+    fn mysql_timestamp() {
+        let ddl = "CREATE TABLE t (
+            seen timestamp NOT NULL DEFAULT CURRENT_TIMESTAMP ON UPDATE CURRENT_TIMESTAMP,
+            at datetime(3)
+        )";
+        let table = parse_ddl(DbType::Mysql, ddl).unwrap();
+        let time_zones = table.columns.iter().map(|c| match c.value_type {
+            ValueType::Timestamp { time_zone, .. } => time_zone,
+            _ => panic!("unexpected {:?}", c.value_type),
+        });
+        assert_eq!(time_zones.collect::<Vec<_>>(), vec![true, false]);
+        assert!(table.columns[0].on_update_current_timestamp);
+
+        let expected = "CREATE TABLE `t` (
+    `seen` TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP ON UPDATE CURRENT_TIMESTAMP,
+    `at` DATETIME(3)
+)";
+        let ddl = table.to_ddl(DbType::Mysql);
+        assert_eq!(ddl, expected);
+        assert_eq!(parse_ddl(DbType::Mysql, &ddl).unwrap(), table);
+    }
+
+    #[test]
+    fn sqlite_ddl() {
+        let ddl = "CREATE TABLE IF NOT EXISTS t (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            parent INTEGER REFERENCES t(id),
+            payload,
+            created TEXT DEFAULT (datetime('now')),
+            UNIQUE (parent, payload)
+        )";
+        let table = parse_ddl(DbType::Sqlite, ddl).unwrap();
+        assert!(table.columns[0].auto_increment && !table.columns[0].nullable);
+        assert_eq!(table.columns[2].value_type, ValueType::Binary(None));
+        assert_eq!(
+            table.columns[3].default,
+            Some(DefaultValue::CurrentTimestamp)
+        );
+        assert_eq!(table.unique_keys[0].columns, vec!["parent", "payload"]);
+        assert_eq!(table.foreign_keys[0].foreign_columns, vec!["id"]);
+
+        let ddl = table.to_ddl(DbType::Sqlite);
+        assert_eq!(parse_ddl(DbType::Sqlite, &ddl).unwrap(), table);
+    }
+
+    #[test]
+    fn ddl_errors() {
+        let err = |db, ddl| match parse_ddl(db, ddl) {
+            Err(ParsingError::Parsing(msg)) => msg,
+            res => panic!("unexpected {res:?}"),
+        };
+
+        assert_eq!(
+            err(DbType::Mysql, "CREATE TABLE t (a int, PRIMARY KEY a)"),
+            "parsing error: invalid primary key at byte 35: error code was: Char"
+        );
+        let msg = err(DbType::Postgres, "CREATE TABLE t (a int REFERENCES)");
+        assert!(
+            msg.starts_with("parsing error: invalid foreign key at byte 32: "),
+            "{msg}"
+        );
+        assert_eq!(
+            err(DbType::Mysql, "CREATE TABLE t (a datetime ON UPDATE 1)"),
+            "parsing error: invalid on update at byte 36: error code was: Verify"
+        );
+        assert!(err(DbType::Sqlite, "DROP TABLE t").contains("at byte 0"));
+        assert!(matches!(
+            parse_ddl(DbType::Postgres, "CREATE TABLE t (a)"),
+            Err(ParsingError::InvalidDataType(..))
+        ));
+    }
+}
@@ -0,0 +1,422 @@
+//! Database schema
+//!
+//! Arrow schema of a table, known before any row is read:
+//! 1. `parse_create_table` reads the columns of a `CREATE TABLE` statement
+//! 1. `parse_columns_dump` reads the output of a query on `information_schema.columns`
+//!
+//! A dump has one line per column, `name`, `type` & `is_nullable` separated by `|` or tabs,
+//! e.g. out of `psql` or `mysql -B`. Headers, borders & footers are skipped. The type must
+//! be written in full, as given by:
+//!
+//! - MySQL: `SELECT column_name, column_type, is_nullable FROM information_schema.columns`
+//! - Postgres: `format_type(atttypid, atttypmod)` of `pg_attribute`, since `data_type` of
+//!   `information_schema.columns` has neither length nor element type
+//! - SQLite: `SELECT name, type, iif("notnull", 'NO', 'YES') FROM pragma_table_info('t')`
+//!
+//! `ValueType::data_type` is the Arrow type of a column:
+//!
+//! | ValueType             | DataType                                       |
+//! |-----------------------|------------------------------------------------|
+//! | integers & floats     | same width & sign                              |
+//! | String, Char, Varchar | Utf8                                           |
+//! | Enum, Json, Uuid      | Utf8, as printed by the database               |
+//! | Binary                | Binary                                         |
+//! | Decimal               | Decimal or Decimal256, Utf8 if unbounded       |
+//! | Date                  | Date32                                         |
+//! | Time                  | Time32 or Time64, by precision                 |
+//! | Timestamp             | Timestamp by precision, `+00:00` if time zoned |
+//! | Array                 | List per dimension, of nullable items          |
+
+use arrow2::datatypes::{DataType, Field, Schema, TimeUnit};
+
+use crate::database_ddl::{parse_ddl, CreateTable};
+use crate::database_types_nom::{parse_value_type, DbType, ParsingError, ValueType};
+
+// max precision of `DataType::Decimal` & `DataType::Decimal256`
+const DECIMAL128_PRECISION: u32 = 38;
+const DECIMAL256_PRECISION: u32 = 76;
+
+/// time zone of `TIMESTAMP WITH TIME ZONE`, values are stored as UTC instants
+pub const UTC_OFFSET: &str = "+00:00";
+
+impl ValueType {
+    pub fn data_type(&self) -> DataType {
+        match self {
+            ValueType::Bool => DataType::Boolean,
+            ValueType::U8 => DataType::UInt8,
+            ValueType::U16 => DataType::UInt16,
+            ValueType::U32 => DataType::UInt32,
+            ValueType::U64 => DataType::UInt64,
+            ValueType::I8 => DataType::Int8,
+            ValueType::I16 => DataType::Int16,
+            ValueType::I32 => DataType::Int32,
+            ValueType::I64 => DataType::Int64,
+            ValueType::F32 => DataType::Float32,
+            ValueType::F64 => DataType::Float64,
+            ValueType::String
+            | ValueType::Char(_)
+            | ValueType::Varchar(_)
+            | ValueType::Uuid
+            | ValueType::Json
+            | ValueType::Enum(_) => DataType::Utf8,
+            ValueType::Binary(_) => DataType::Binary,
+            ValueType::Decimal { precision, scale } => {
+                let scale = scale.unwrap_or_default() as usize;
+                match *precision {
+                    Some(p) if p <= DECIMAL128_PRECISION => DataType::Decimal(p as usize, scale),
+                    Some(p) if p <= DECIMAL256_PRECISION => DataType::Decimal256(p as usize, scale),
+                    // e.g. `NUMERIC` of Postgres, kept as text not to lose digits
+                    _ => DataType::Utf8,
+                }
+            }
+            ValueType::Date => DataType::Date32,
+            // Arrow has no time of day with time zone
+            ValueType::Time { precision, .. } => match time_unit(*precision) {
+                unit @ (TimeUnit::Second | TimeUnit::Millisecond) => DataType::Time32(unit),
+                unit => DataType::Time64(unit),
+            },
+            ValueType::Timestamp {
+                precision,
+                time_zone,
+            } => DataType::Timestamp(
+                time_unit(*precision),
+                time_zone.then(|| UTC_OFFSET.to_owned()),
+            ),
+            ValueType::Array {
+                element,
+                dimensions,
+            } => dimensions.iter().fold(element.data_type(), |dt, _| {
+                DataType::List(Box::new(Field::new("item", dt, true)))
+            }),
+        }
+    }
+}
+
+// the coarsest unit which holds `precision` fractional digits, microseconds by default
+fn time_unit(precision: Option<u32>) -> TimeUnit {
+    match precision {
+        Some(0) => TimeUnit::Second,
+        Some(1..=3) => TimeUnit::Millisecond,
+        Some(4..=6) | None => TimeUnit::Microsecond,
+        Some(_) => TimeUnit::Nanosecond,
+    }
+}
+
+/// a column of a table
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Column {
+    pub name: String,
+    pub value_type: ValueType,
+    pub nullable: bool,
+}
+
+impl Column {
+    pub fn field(&self) -> Field {
+        Field::new(&self.name, self.value_type.data_type(), self.nullable)
+    }
+}
+
+/// Arrow schema of columns, in order
+pub fn arrow_schema(columns: &[Column]) -> Schema {
+    Schema::from(columns.iter().map(Column::field).collect::<Vec<_>>())
+}
+
+/// a table read from `CREATE TABLE`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Table {
+    /// database schema, e.g. `public` of `public.users`
+    pub schema: Option<String>,
+    pub name: String,
+    pub columns: Vec<Column>,
+}
+
+impl Table {
+    pub fn arrow_schema(&self) -> Schema {
+        arrow_schema(&self.columns)
+    }
+}
+
+impl From<CreateTable> for Table {
+    fn from(ddl: CreateTable) -> Self {
+        let columns = ddl
+            .columns
+            .into_iter()
+            .map(|c| Column {
+                name: c.name,
+                value_type: c.value_type,
+                nullable: c.nullable,
+            })
+            .collect();
+
+        Table {
+            schema: ddl.schema,
+            name: ddl.name,
+            columns,
+        }
+    }
+}
+
+/// columns of a `CREATE TABLE` statement mapped to `ValueType`s of `db`, see `parse_ddl`
+pub fn parse_create_table(db: DbType, input: &str) -> Result<Table, ParsingError> {
+    parse_ddl(db, input).map(Table::from)
+}
+
+// ------------------------------------------------------------------------------
+// information_schema.columns
+
+// `YES`/`NO` of `is_nullable`, or a boolean
+fn nullability(s: &str) -> Option<bool> {
+    match s.to_uppercase().as_str() {
+        "YES" | "TRUE" | "T" | "1" => Some(true),
+        "NO" | "FALSE" | "F" | "0" => Some(false),
+        _ => None,
+    }
+}
+
+// fields of a line, without the borders of a `mysql` table
+fn dump_fields(line: &str) -> Vec<&str> {
+    let line = line.trim();
+    if line.contains('\t') {
+        return line.split('\t').map(str::trim).collect();
+    }
+    let line = line.strip_prefix('|').unwrap_or(line);
+    let line = line.strip_suffix('|').unwrap_or(line);
+    // a type may contain `|`, e.g. `enum('a|b')`, hence name & nullability are split first
+    let Some((name, rest)) = line.split_once('|') else {
+        return vec![line.trim()];
+    };
+    match rest.rsplit_once('|') {
+        Some((ty, nullable)) => vec![name.trim(), ty.trim(), nullable.trim()],
+        None => vec![name.trim(), rest.trim()],
+    }
+}
+
+/// columns of an `information_schema.columns` dump, see the module doc
+pub fn parse_columns_dump(db: DbType, input: &str) -> Result<Vec<Column>, ParsingError> {
+    let mut columns = vec![];
+
+    for (i, line) in input.lines().enumerate() {
+        let line = line.trim();
+        let border = line.chars().all(|c| "-+=| ".contains(c));
+        let footer = line.starts_with('(') && line.ends_with(')');
+        if border || footer {
+            continue;
+        }
+
+        let fields = dump_fields(line);
+        let [name, ty, nullable] = fields[..] else {
+            let msg = format!("line {}: expected name, type & nullable", i + 1);
+            return Err(ParsingError::Parsing(msg));
+        };
+        let Some(nullable) = nullability(nullable) else {
+            // header
+            if columns.is_empty() {
+                continue;
+            }
+            let msg = format!("line {}: invalid nullable {nullable}", i + 1);
+            return Err(ParsingError::Parsing(msg));
+        };
+
+        let value_type = match parse_value_type(db, ty) {
+            Err(ParsingError::Parsing(msg)) => {
+                return Err(ParsingError::Parsing(format!("line {}: {msg}", i + 1)))
+            }
+            res => res?,
+        };
+        columns.push(Column {
+            name: name.to_owned(),
+            value_type,
+            nullable,
+        });
+    }
+
+    Ok(columns)
+}
+
+#[cfg(test)]
+mod database_schema_tests {
+    use super::*;
+
+    #[test]
+    fn data_types() {
+        let cases = [
+            (ValueType::U16, DataType::UInt16),
+            (ValueType::Varchar(10), DataType::Utf8),
+            (ValueType::Binary(Some(16)), DataType::Binary),
+            (
+                ValueType::Decimal {
+                    precision: Some(10),
+                    scale: Some(2),
+                },
+                DataType::Decimal(10, 2),
+            ),
+            (
+                ValueType::Decimal {
+                    precision: Some(50),
+                    scale: None,
+                },
+                DataType::Decimal256(50, 0),
+            ),
+            (
+                ValueType::Decimal {
+                    precision: None,
+                    scale: None,
+                },
+                DataType::Utf8,
+            ),
+            (ValueType::Date, DataType::Date32),
+            (
+                ValueType::Time {
+                    precision: Some(0),
+                    time_zone: true,
+                },
+                DataType::Time32(TimeUnit::Second),
+            ),
+            (
+                ValueType::Time {
+                    precision: None,
+                    time_zone: false,
+                },
+                DataType::Time64(TimeUnit::Microsecond),
+            ),
+            (
+                ValueType::Timestamp {
+                    precision: Some(3),
+                    time_zone: true,
+                },
+                DataType::Timestamp(TimeUnit::Millisecond, Some(UTC_OFFSET.to_owned())),
+            ),
+            (
+                ValueType::Timestamp {
+                    precision: Some(9),
+                    time_zone: false,
+                },
+                DataType::Timestamp(TimeUnit::Nanosecond, None),
+            ),
+        ];
+        for (vt, dt) in cases {
+            assert_eq!(vt.data_type(), dt, "{vt:?}");
+        }
+
+        let array = ValueType::Array {
+            element: Box::new(ValueType::I32),
+            dimensions: vec![None, Some(3)],
+        };
+        let item = Field::new("item", DataType::Int32, true);
+        let inner = DataType::List(Box::new(item));
+        let outer = DataType::List(Box::new(Field::new("item", inner, true)));
+        assert_eq!(array.data_type(), outer);
+    }
+
+    #[test]
+    fn create_table() {
+        let ddl = r#"
+            CREATE TABLE IF NOT EXISTS public."order" (
+                id bigint NOT NULL,
+                "user" varchar(64) DEFAULT 'a, b)' NOT NULL,
+                amount numeric(12, 2) CHECK (amount > 0),
+                tags text[],
+                created_at timestamp(3) with time zone DEFAULT now(),
+                CONSTRAINT order_pk PRIMARY KEY (id),
+                UNIQUE ("user", created_at)
+            );
+        "#;
+        let table = parse_create_table(DbType::Postgres, ddl).unwrap();
+        assert_eq!(table.schema.as_deref(), Some("public"));
+        assert_eq!(table.name, "order");
+
+        let schema = table.arrow_schema();
+        let expected = vec![
+            Field::new("id", DataType::Int64, false),
+            Field::new("user", DataType::Utf8, false),
+            Field::new("amount", DataType::Decimal(12, 2), true),
+            Field::new(
+                "tags",
+                DataType::List(Box::new(Field::new("item", DataType::Utf8, true))),
+                true,
+            ),
+            Field::new(
+                "created_at",
+                DataType::Timestamp(TimeUnit::Millisecond, Some(UTC_OFFSET.to_owned())),
+                true,
+            ),
+        ];
+        assert_eq!(schema.fields, expected);
+
+        let ddl = "CREATE TABLE `t` (
+            `id` int(10) unsigned NOT NULL AUTO_INCREMENT,
+            `flag` tinyint(1) DEFAULT NULL,
+            `size` enum('S','M') NOT NULL,
+            PRIMARY KEY (`id`),
+            KEY `idx_size` (`size`)
+        ) ENGINE=InnoDB DEFAULT CHARSET=utf8mb4";
+        let table = parse_create_table(DbType::Mysql, ddl).unwrap();
+        let fields = table.arrow_schema().fields;
+        assert_eq!(fields[0], Field::new("id", DataType::UInt32, false));
+        assert_eq!(fields[1], Field::new("flag", DataType::Boolean, true));
+        assert_eq!(fields[2], Field::new("size", DataType::Utf8, false));
+
+        let ddl = "create temp table t (id integer primary key, v, [w x] real)";
+        let table = parse_create_table(DbType::Sqlite, ddl).unwrap();
+        let columns = table.columns;
+        assert!(!columns[0].nullable);
+        assert_eq!(columns[1].value_type, ValueType::Binary(None));
+        assert_eq!(
+            (columns[2].name.as_str(), &columns[2].value_type),
+            ("w x", &ValueType::F64)
+        );
+    }
+
+    #[test]
+    fn create_table_errors() {
+        let err = |db, ddl| match parse_create_table(db, ddl) {
+            Err(ParsingError::Parsing(msg)) => msg,
+            res => panic!("unexpected {res:?}"),
+        };
+
+        assert_eq!(
+            err(DbType::Postgres, "CREATE TABLE t (a int, b numeric(10,)"),
+            "parsing error: invalid column type at byte 35: error code was: Char"
+        );
+        assert_eq!(
+            err(DbType::Postgres, "CREATE TABLE t AS SELECT 1"),
+            "parsing error: invalid table definition at byte 15: error code was: Char"
+        );
+        assert!(matches!(
+            parse_create_table(DbType::Mysql, "CREATE TABLE t (a geometry)"),
+            Err(ParsingError::InvalidDataType(..))
+        ));
+    }
+
+    #[test]
+    fn columns_dump() {
+        let psql = " column_name |        format_type          | is_nullable
+-------------+-----------------------------+-------------
+ id          | integer                     | NO
+ name        | character varying(20)       | YES
+ scores      | double precision[]          | YES
+(3 rows)
+";
+        let columns = parse_columns_dump(DbType::Postgres, psql).unwrap();
+        let schema = arrow_schema(&columns);
+        assert_eq!(schema.fields[0], Field::new("id", DataType::Int32, false));
+        assert_eq!(schema.fields[1], Field::new("name", DataType::Utf8, true));
+        let item = Field::new("item", DataType::Float64, true);
+        assert_eq!(schema.fields[2].data_type, DataType::List(Box::new(item)));
+
+        let mysql = "COLUMN_NAME\tCOLUMN_TYPE\tIS_NULLABLE\nid\tbigint unsigned\tNO\nsize\tenum('a|b','c')\tYES\n";
+        let columns = parse_columns_dump(DbType::Mysql, mysql).unwrap();
+        assert_eq!(columns[0].value_type, ValueType::U64);
+        let mysql = "+------+-----------------+------+\n| size | enum('a|b','c') | YES  |\n+------+-----------------+------+";
+        let columns = parse_columns_dump(DbType::Mysql, mysql).unwrap();
+        assert_eq!(
+            columns[0].value_type,
+            ValueType::Enum(vec!["a|b".to_owned(), "c".to_owned()])
+        );
+
+        match parse_columns_dump(DbType::Mysql, "id\tint\tNO\nv\tdecimal(10\tYES") {
+            Err(ParsingError::Parsing(msg)) => assert!(msg.starts_with("line 2: "), "{msg}"),
+            res => panic!("unexpected {res:?}"),
+        }
+    }
+}
@@ -1,32 +1,45 @@
 //! taste_nom
+//!
+//! Column types of MySQL, Postgres, SQLite & MSSQL:
+//! 1. `parse_sql_type` reads a type as written in a DDL, e.g. `DECIMAL(10, 2)`,
+//!    `TIMESTAMP(3) WITH TIME ZONE`, `INT[]` or `ENUM('a', 'b')`, into a `SqlType`
+//! 1. `SqlType::value_type` maps it to a `ValueType` of a database
+//!
+//! `parse_value_type` does both.
 
 use std::collections::HashMap;
 use std::str::FromStr;
 
 use nom::branch::alt;
-use nom::bytes::complete::{tag, take_until};
-use nom::character::complete::{alpha0, alpha1, alphanumeric1, space0, space1};
-use nom::combinator::recognize;
-use nom::sequence::{delimited, pair, separated_pair, tuple};
+use nom::bytes::complete::{tag, tag_no_case, take_until, take_while};
+use nom::character::complete::{
+    alpha0, alpha1, alphanumeric1, char, digit1, multispace0, multispace1, space0, space1,
+};
+use nom::combinator::{cut, map, opt, recognize};
+use nom::multi::{many0, separated_list1};
+use nom::sequence::{delimited, pair, preceded, separated_pair, tuple};
 use nom::IResult;
 use once_cell::sync::Lazy;
 
+use crate::custom_error::{CustomError, TasteNomError};
+use crate::database_conn::{failure, quoted};
+
 // error
 #[allow(dead_code)]
 #[derive(Debug)]
-enum ParsingError {
+pub enum ParsingError {
     InvalidDbType(String),
     InvalidDataType(String, String),
     Parsing(String),
 }
 
 // database type
-#[allow(dead_code)]
-#[derive(Debug, PartialEq, Eq)]
-enum DbType {
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DbType {
     Mysql,
     Postgres,
     Sqlite,
+    Mssql,
 }
 
 // str -> database type
@@ -38,6 +51,7 @@ impl FromStr for DbType {
             "MYSQL" => Ok(DbType::Mysql),
             "POSTGRES" => Ok(DbType::Postgres),
             "SQLITE" => Ok(DbType::Sqlite),
+            "MSSQL" => Ok(DbType::Mssql),
             _ => Err(ParsingError::InvalidDbType(s.to_string())),
         }
     }
@@ -57,7 +71,38 @@ pub enum ValueType {
     I64,
     F32,
     F64,
+    /// text without length limit
     String,
+    /// `CHAR(n)`, fixed length
+    Char(u32),
+    /// `VARCHAR(n)`, max length
+    Varchar(u32),
+    /// `BYTEA`, `BLOB`, `VARBINARY(n)`...
+    Binary(Option<u32>),
+    /// `DECIMAL(precision, scale)`
+    Decimal {
+        precision: Option<u32>,
+        scale: Option<u32>,
+    },
+    Date,
+    /// precision is the number of fractional digits of seconds
+    Time {
+        precision: Option<u32>,
+        time_zone: bool,
+    },
+    Timestamp {
+        precision: Option<u32>,
+        time_zone: bool,
+    },
+    Uuid,
+    /// `JSON` & `JSONB`
+    Json,
+    Enum(Vec<String>),
+    /// `INT[]`, `TEXT[3][]`: one item per dimension, with its size if given
+    Array {
+        element: Box<ValueType>,
+        dimensions: Vec<Option<u32>>,
+    },
 }
 
 static MYSQL_TMAP: Lazy<HashMap<&'static str, ValueType>> = Lazy::new(|| {
@@ -68,12 +113,22 @@ static MYSQL_TMAP: Lazy<HashMap<&'static str, ValueType>> = Lazy::new(|| {
         ("SMALLINT UNSIGNED", ValueType::U16),
         ("INT UNSIGNED", ValueType::U32),
         ("BIGINT UNSIGNED", ValueType::U64),
+        ("SERIAL", ValueType::U64),
+        ("MEDIUMINT UNSIGNED", ValueType::U32),
+        ("INTEGER UNSIGNED", ValueType::U32),
         ("TINYINT", ValueType::I8),
         ("SMALLINT", ValueType::I16),
         ("INT", ValueType::I32),
+        ("MEDIUMINT", ValueType::I32),
+        ("INTEGER", ValueType::I32),
         ("BIGINT", ValueType::I64),
         ("FLOAT", ValueType::F32),
         ("DOUBLE", ValueType::F64),
+        ("DOUBLE PRECISION", ValueType::F64),
+        ("REAL", ValueType::F64),
+        ("TINYTEXT", ValueType::String),
+        ("MEDIUMTEXT", ValueType::String),
+        ("LONGTEXT", ValueType::String),
         ("VARCHAR", ValueType::String),
         ("CHAR", ValueType::String),
         ("TEXT", ValueType::String),
@@ -83,7 +138,9 @@ static MYSQL_TMAP: Lazy<HashMap<&'static str, ValueType>> = Lazy::new(|| {
 static POSTGRES_TMAP: Lazy<HashMap<&'static str, ValueType>> = Lazy::new(|| {
     HashMap::from([
         ("BOOL", ValueType::Bool),
-        ("CHAR", ValueType::I8),
+        ("BOOLEAN", ValueType::Bool),
+        ("CHAR", ValueType::String),
+        ("\"char\"", ValueType::I8),
         ("TINYINT", ValueType::I8),
         ("SMALLINT", ValueType::I16),
         ("SMALLSERIAL", ValueType::I16),
@@ -91,38 +148,79 @@ static POSTGRES_TMAP: Lazy<HashMap<&'static str, ValueType>> = Lazy::new(|| {
         ("INT", ValueType::I32),
         ("SERIAL", ValueType::I32),
         ("INT4", ValueType::I32),
+        ("INTEGER", ValueType::I32),
         ("BIGINT", ValueType::I64),
         ("BIGSERIAL", ValueType::I64),
         ("INT8", ValueType::I64),
         ("REAL", ValueType::F32),
         ("FLOAT4", ValueType::F32),
+        ("FLOAT", ValueType::F64),
         ("DOUBLE PRECISION", ValueType::F64),
         ("FLOAT8", ValueType::F64),
         ("VARCHAR", ValueType::String),
-        ("CHAR(N)", ValueType::String),
         ("TEXT", ValueType::String),
         ("NAME", ValueType::String),
+        ("CITEXT", ValueType::String),
     ])
 });
 
 static SQLITE_TMAP: Lazy<HashMap<&'static str, ValueType>> = Lazy::new(|| {
     HashMap::from([
         ("BOOLEAN", ValueType::Bool),
+        ("INT", ValueType::I32),
         ("INTEGER", ValueType::I32),
         ("BIGINT", ValueType::I64),
         ("INT8", ValueType::I64),
         ("REAL", ValueType::F64),
+        ("DOUBLE", ValueType::F64),
+        ("FLOAT", ValueType::F64),
         ("VARCHAR", ValueType::String),
-        ("CHAR(N)", ValueType::String),
         ("TEXT", ValueType::String),
     ])
 });
 
+static MSSQL_TMAP: Lazy<HashMap<&'static str, ValueType>> = Lazy::new(|| {
+    HashMap::from([
+        ("BIT", ValueType::Bool),
+        ("TINYINT", ValueType::U8),
+        ("SMALLINT", ValueType::I16),
+        ("INT", ValueType::I32),
+        ("BIGINT", ValueType::I64),
+        ("REAL", ValueType::F32),
+        ("FLOAT", ValueType::F64),
+        (
+            "MONEY",
+            ValueType::Decimal {
+                precision: Some(19),
+                scale: Some(4),
+            },
+        ),
+        (
+            "SMALLMONEY",
+            ValueType::Decimal {
+                precision: Some(10),
+                scale: Some(4),
+            },
+        ),
+        ("TEXT", ValueType::String),
+        ("NTEXT", ValueType::String),
+        ("XML", ValueType::String),
+        ("IMAGE", ValueType::Binary(None)),
+        (
+            "SMALLDATETIME",
+            ValueType::Timestamp {
+                precision: Some(0),
+                time_zone: false,
+            },
+        ),
+    ])
+});
+
 #[test]
 fn test_get_tmap() {
     assert_eq!(MYSQL_TMAP.get("BIGINT UNSIGNED").unwrap(), &ValueType::U64);
     assert_eq!(POSTGRES_TMAP.get("REAL").unwrap(), &ValueType::F32);
-    assert_eq!(SQLITE_TMAP.get("CHAR(N)").unwrap(), &ValueType::String);
+    assert_eq!(SQLITE_TMAP.get("VARCHAR").unwrap(), &ValueType::String);
 }
 
 // ------------------------------------------------------------------------------
@@ -294,6 +392,9 @@ fn from_str_to_type(input: &str) -> Result<(DbType, ValueType), ParsingError> {
                     DbType::Sqlite => SQLITE_TMAP.get(data_type).ok_or_else(|| {
                         ParsingError::InvalidDataType("SQLITE".to_string(), data_type.to_string())
                     }),
+                    DbType::Mssql => MSSQL_TMAP.get(data_type).ok_or_else(|| {
+                        ParsingError::InvalidDataType("MSSQL".to_string(), data_type.to_string())
+                    }),
                 };
 
                 match rvt {
@@ -328,7 +429,7 @@ fn test_cvt() {
     );
 
     assert_eq!(
-        from_str_to_type("[SQLITE:CHAR(N)]").unwrap(),
+        from_str_to_type("[SQLITE:VARCHAR]").unwrap(),
         (DbType::Sqlite, ValueType::String)
     );
 
@@ -337,3 +438,431 @@ fn test_cvt() {
         (DbType::Mysql, ValueType::Bool)
     );
 }
+
+// ------------------------------------------------------------------------------
+// grammar
+
+/// A column type as written, before mapping to a `ValueType`.
+///
+/// ```text
+/// name [(param, ...)] [UNSIGNED | ZEROFILL | WITH TIME ZONE | WITHOUT TIME ZONE]... [[n]]...
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SqlType {
+    /// upper case words separated by a single space, e.g. `DOUBLE PRECISION`, but `"char"`
+    pub name: String,
+    /// numeric parameters, e.g. `[10, 2]` of `DECIMAL(10, 2)`, empty for `VARCHAR(MAX)`
+    pub params: Vec<u32>,
+    /// quoted parameters of `ENUM` & `SET`
+    pub values: Vec<String>,
+    pub unsigned: bool,
+    /// `Some(true)` if `WITH TIME ZONE`, `Some(false)` if `WITHOUT TIME ZONE`
+    pub time_zone: Option<bool>,
+    /// `[]` or `ARRAY` suffixes, with their size if given
+    pub dimensions: Vec<Option<u32>>,
+}
+
+// parameter of a type
+enum TypeParam {
+    Number(u32),
+    Text(String),
+    Max,
+}
+
+// words which continue the name of a type, e.g. `DOUBLE PRECISION`, `CHARACTER VARYING`
+const NAME_SUFFIXES: [&str; 2] = ["VARYING", "PRECISION"];
+// words followed by the rest of the name, e.g. `NATIONAL CHAR`, `LONG VARCHAR`
+const NAME_PREFIXES: [&str; 2] = ["NATIONAL", "LONG"];
+
+fn word(input: &str) -> IResult<&str, &str, CustomError> {
+    recognize(pair(
+        alpha1,
+        take_while(|c: char| c.is_ascii_alphanumeric() || c == '_'),
+    ))(input)
+}
+
+fn next_word(input: &str) -> IResult<&str, &str, CustomError> {
+    preceded(multispace1, word)(input)
+}
+
+fn number(input: &str) -> IResult<&str, u32, CustomError> {
+    let (rest, digits) = digit1(input)?;
+    match digits.parse() {
+        Ok(n) => Ok((rest, n)),
+        Err(_) => failure(input, format!("{digits} is out of range")),
+    }
+}
+
+// a name of several words only if known, so that `INT NOT NULL` stops after `INT`,
+// or a single word in brackets, e.g. `[int]` of MSSQL, or quoted
+fn type_name(input: &str) -> IResult<&str, String, CustomError> {
+    if let Ok((rest, name)) = delimited(char('['), word, char(']'))(input) {
+        return Ok((rest, name.to_uppercase()));
+    }
+    if let Ok((rest, name)) = delimited(char('"'), word, char('"'))(input) {
+        // `"char"` of Postgres is a single byte, whereas `CHAR` is `CHARACTER(1)`
+        let name = match name {
+            "char" => "\"char\"".to_owned(),
+            n => n.to_uppercase(),
+        };
+        return Ok((rest, name));
+    }
+    let (mut rest, first) = word(input)?;
+    let mut name = first.to_uppercase();
+    let mut last = name.clone();
+    while let Ok((r, w)) = next_word(rest) {
+        let w = w.to_uppercase();
+        if !NAME_SUFFIXES.contains(&w.as_str()) && !NAME_PREFIXES.contains(&last.as_str()) {
+            break;
+        }
+        name.push(' ');
+        name.push_str(&w);
+        last = w;
+        rest = r;
+    }
+    Ok((rest, name))
+}
+
+fn type_params(input: &str) -> IResult<&str, Vec<TypeParam>, CustomError> {
+    let param = alt((
+        map(number, TypeParam::Number),
+        map(quoted('\'', '\''), TypeParam::Text),
+        map(tag_no_case("MAX"), |_| TypeParam::Max),
+    ));
+    let params = separated_list1(delimited(multispace0, char(','), multispace0), param);
+
+    preceded(
+        pair(multispace0, char('(')),
+        cut(delimited(multispace0, params, pair(multispace0, char(')')))),
+    )(input)
+}
+
+// `[]`, `[3]`, `ARRAY` or `ARRAY[3]`
+fn dimension(input: &str) -> IResult<&str, Option<u32>, CustomError> {
+    let brackets = |i| delimited(char('['), opt(number), cut(char(']')))(i);
+    let array = preceded(
+        pair(multispace1, tag_no_case("ARRAY")),
+        map(opt(preceded(multispace0, brackets)), Option::flatten),
+    );
+    alt((preceded(multispace0, brackets), array))(input)
+}
+
+/// parse a column type, the input left starts after the type (e.g. `NOT NULL` of a DDL)
+pub fn parse_sql_type(input: &str) -> IResult<&str, SqlType, CustomError> {
+    let (rest, _) = multispace0(input)?;
+    let (mut rest, name) = type_name(rest)?;
+    let mut sql_type = SqlType {
+        name,
+        ..Default::default()
+    };
+
+    if let (r, Some(params)) = opt(type_params)(rest)? {
+        for p in params {
+            match p {
+                TypeParam::Number(n) => sql_type.params.push(n),
+                TypeParam::Text(t) => sql_type.values.push(t),
+                TypeParam::Max => {}
+            }
+        }
+        rest = r;
+    }
+
+    while let Ok((r, w)) = next_word(rest) {
+        match w.to_uppercase().as_str() {
+            "UNSIGNED" | "ZEROFILL" => sql_type.unsigned = true,
+            "SIGNED" => sql_type.unsigned = false,
+            m @ ("WITH" | "WITHOUT") => {
+                let time_zone = tuple((
+                    multispace1,
+                    tag_no_case("TIME"),
+                    multispace1,
+                    tag_no_case("ZONE"),
+                ));
+                let (r, _) = cut(time_zone)(r)?;
+                sql_type.time_zone = Some(m == "WITH");
+                rest = r;
+                continue;
+            }
+            _ => break,
+        }
+        rest = r;
+    }
+
+    let (rest, dimensions) = many0(dimension)(rest)?;
+    sql_type.dimensions = dimensions;
+
+    Ok((rest, sql_type))
+}
+
+/// parse a whole column type and map it to a `ValueType` of `db`
+pub fn parse_value_type(db: DbType, input: &str) -> Result<ValueType, ParsingError> {
+    let sql_type = match parse_sql_type(input) {
+        Ok((rest, t)) if rest.trim().is_empty() => t,
+        Ok((rest, _)) => {
+            let e = CustomError::new(rest, "unexpected input").locate(input);
+            return Err(ParsingError::Parsing(e.to_string()));
+        }
+        Err(e) => {
+            let e = TasteNomError::located(input, e);
+            return Err(ParsingError::Parsing(e.to_string()));
+        }
+    };
+
+    sql_type.value_type(db)
+}
+
+impl SqlType {
+    /// `ValueType` in `db`, names without parameter are looked up in the maps of each
+    /// database first (e.g. `"char"` of Postgres is a single byte)
+    pub fn value_type(&self, db: DbType) -> Result<ValueType, ParsingError> {
+        if !self.dimensions.is_empty() {
+            let element = SqlType {
+                dimensions: vec![],
+                ..self.clone()
+            };
+            return Ok(ValueType::Array {
+                element: Box::new(element.value_type(db)?),
+                dimensions: self.dimensions.clone(),
+            });
+        }
+
+        let tmap = match db {
+            DbType::Mysql => &MYSQL_TMAP,
+            DbType::Postgres => &POSTGRES_TMAP,
+            DbType::Sqlite => &SQLITE_TMAP,
+            DbType::Mssql => &MSSQL_TMAP,
+        };
+        let mut key = self.name.clone();
+        if self.unsigned {
+            key.push_str(" UNSIGNED");
+        }
+        let plain = self.params.is_empty() && self.values.is_empty() && self.time_zone.is_none();
+        if plain {
+            if let Some(vt) = tmap.get(key.as_str()) {
+                return Ok(vt.clone());
+            }
+        }
+
+        let p0 = self.params.first().copied();
+        let p1 = self.params.get(1).copied();
+        let time_zone = self.time_zone.unwrap_or_default();
+        let vt = match self.name.as_str() {
+            "TINYINT" if db == DbType::Mysql && p0 == Some(1) && !self.unsigned => ValueType::Bool,
+            "DECIMAL" | "NUMERIC" | "DEC" | "FIXED" => ValueType::Decimal {
+                precision: p0,
+                scale: p1,
+            },
+            // precision in bits
+            "FLOAT" if p0.is_some() => {
+                if p0 <= Some(24) {
+                    ValueType::F32
+                } else {
+                    ValueType::F64
+                }
+            }
+            "CHAR" | "CHARACTER" | "NCHAR" | "BPCHAR" => ValueType::Char(p0.unwrap_or(1)),
+            "VARCHAR" | "CHARACTER VARYING" | "NVARCHAR" | "VARCHAR2" => match p0 {
+                Some(n) => ValueType::Varchar(n),
+                None => ValueType::String,
+            },
+            "TEXT" | "CLOB" => ValueType::String,
+            "BYTEA" | "BLOB" | "TINYBLOB" | "MEDIUMBLOB" | "LONGBLOB" | "BINARY" | "VARBINARY" => {
+                ValueType::Binary(p0)
+            }
+            "DATE" => ValueType::Date,
+            "TIME" => ValueType::Time {
+                precision: p0,
+                time_zone,
+            },
+            "TIMETZ" => ValueType::Time {
+                precision: p0,
+                time_zone: true,
+            },
+            // converted from and to the time zone of the session, unlike `DATETIME`
+            "TIMESTAMP" if db == DbType::Mysql => ValueType::Timestamp {
+                precision: p0,
+                time_zone: true,
+            },
+            "TIMESTAMP" | "DATETIME" | "DATETIME2" => ValueType::Timestamp {
+                precision: p0,
+                time_zone,
+            },
+            "TIMESTAMPTZ" | "DATETIMEOFFSET" => ValueType::Timestamp {
+                precision: p0,
+                time_zone: true,
+            },
+            "UUID" | "UNIQUEIDENTIFIER" => ValueType::Uuid,
+            "JSON" | "JSONB" => ValueType::Json,
+            "ENUM" => ValueType::Enum(self.values.clone()),
+            // display width of an integer, e.g. `INT(11)`, is ignored
+            _ => tmap
+                .get(key.as_str())
+                .cloned()
+                .ok_or_else(|| ParsingError::InvalidDataType(format!("{db:?}"), key))?,
+        };
+
+        Ok(vt)
+    }
+}
+
+#[test]
+fn test_parse_sql_type() {
+    let (rest, t) = parse_sql_type("decimal ( 10 , 2 ) NOT NULL").unwrap();
+    assert_eq!(rest, " NOT NULL");
+    assert_eq!((t.name.as_str(), t.params), ("DECIMAL", vec![10, 2]));
+
+    let (_, t) = parse_sql_type("timestamp(3) with time zone").unwrap();
+    assert_eq!((t.params, t.time_zone), (vec![3], Some(true)));
+
+    let (_, t) = parse_sql_type("INT(11) UNSIGNED ZEROFILL").unwrap();
+    assert_eq!((t.name.as_str(), t.unsigned), ("INT", true));
+    let (rest, t) = parse_sql_type("national character varying PRIMARY KEY").unwrap();
+    assert_eq!(
+        (t.name.as_str(), rest),
+        ("NATIONAL CHARACTER VARYING", " PRIMARY KEY")
+    );
+
+    let (_, t) = parse_sql_type("character varying(20)[][3]").unwrap();
+    assert_eq!(t.name, "CHARACTER VARYING");
+    assert_eq!(t.dimensions, vec![None, Some(3)]);
+    let (_, t) = parse_sql_type("integer ARRAY[4]").unwrap();
+    assert_eq!(t.dimensions, vec![Some(4)]);
+
+    let (_, t) = parse_sql_type("ENUM('a', 'it''s')").unwrap();
+    assert_eq!(t.values, vec!["a", "it's"]);
+    let (_, t) = parse_sql_type("NVARCHAR(MAX)").unwrap();
+    assert!(t.params.is_empty());
+
+    assert!(parse_sql_type("DECIMAL(10, 2").is_err());
+    assert!(parse_sql_type("TIME WITH ZONE").is_err());
+    assert!(parse_sql_type("(10)").is_err());
+}
+
+#[test]
+fn test_parse_value_type() {
+    let cases = [
+        (DbType::Mysql, "TINYINT(1)", ValueType::Bool),
+        (DbType::Mysql, "tinyint(4) unsigned", ValueType::U8),
+        (DbType::Mysql, "INT(11)", ValueType::I32),
+        (DbType::Mysql, "BIGINT UNSIGNED", ValueType::U64),
+        (DbType::Mysql, "VARCHAR(255)", ValueType::Varchar(255)),
+        (DbType::Mysql, "CHAR(2)", ValueType::Char(2)),
+        (DbType::Mysql, "FLOAT(53)", ValueType::F64),
+        (DbType::Mysql, "LONGBLOB", ValueType::Binary(None)),
+        (
+            DbType::Mysql,
+            "ENUM('S','M','L')",
+            ValueType::Enum(vec!["S".into(), "M".into(), "L".into()]),
+        ),
+        (
+            DbType::Mysql,
+            "DATETIME(6)",
+            ValueType::Timestamp {
+                precision: Some(6),
+                time_zone: false,
+            },
+        ),
+        (
+            DbType::Mysql,
+            "TIMESTAMP(3)",
+            ValueType::Timestamp {
+                precision: Some(3),
+                time_zone: true,
+            },
+        ),
+        (
+            DbType::Postgres,
+            "NUMERIC",
+            ValueType::Decimal {
+                precision: None,
+                scale: None,
+            },
+        ),
+        (
+            DbType::Postgres,
+            "numeric(12,4)",
+            ValueType::Decimal {
+                precision: Some(12),
+                scale: Some(4),
+            },
+        ),
+        (DbType::Postgres, "DOUBLE PRECISION", ValueType::F64),
+        (
+            DbType::Postgres,
+            "TIMESTAMP WITH TIME ZONE",
+            ValueType::Timestamp {
+                precision: None,
+                time_zone: true,
+            },
+        ),
+        (
+            DbType::Postgres,
+            "time(0) without time zone",
+            ValueType::Time {
+                precision: Some(0),
+                time_zone: false,
+            },
+        ),
+        (
+            DbType::Postgres,
+            "TIMESTAMPTZ",
+            ValueType::Timestamp {
+                precision: None,
+                time_zone: true,
+            },
+        ),
+        (DbType::Postgres, "DATE", ValueType::Date),
+        (DbType::Postgres, "UUID", ValueType::Uuid),
+        (DbType::Postgres, "JSONB", ValueType::Json),
+        (DbType::Postgres, "BYTEA", ValueType::Binary(None)),
+        (DbType::Postgres, "CHAR", ValueType::String),
+        (DbType::Postgres, "\"char\"", ValueType::I8),
+        (DbType::Postgres, "\"int4\"", ValueType::I32),
+        (
+            DbType::Postgres,
+            "INT[]",
+            ValueType::Array {
+                element: Box::new(ValueType::I32),
+                dimensions: vec![None],
+            },
+        ),
+        (
+            DbType::Postgres,
+            "varchar(10)[2][2]",
+            ValueType::Array {
+                element: Box::new(ValueType::Varchar(10)),
+                dimensions: vec![Some(2), Some(2)],
+            },
+        ),
+        (DbType::Sqlite, "INTEGER", ValueType::I32),
+        (DbType::Sqlite, "VARCHAR(20)", ValueType::Varchar(20)),
+        (DbType::Sqlite, "BLOB", ValueType::Binary(None)),
+        (DbType::Mssql, "TINYINT", ValueType::U8),
+        (DbType::Mssql, "NVARCHAR(MAX)", ValueType::String),
+        (
+            DbType::Mssql,
+            "DATETIME2(7)",
+            ValueType::Timestamp {
+                precision: Some(7),
+                time_zone: false,
+            },
+        ),
+        (DbType::Mssql, "UNIQUEIDENTIFIER", ValueType::Uuid),
+    ];
+    for (db, input, expected) in cases {
+        assert_eq!(parse_value_type(db, input).unwrap(), expected, "{input}");
+    }
+
+    assert!(matches!(
+        parse_value_type(DbType::Sqlite, "GEOMETRY"),
+        Err(ParsingError::InvalidDataType(..))
+    ));
+    match parse_value_type(DbType::Mysql, "INT UNSIGNED NOT NULL") {
+        Err(ParsingError::Parsing(msg)) => assert_eq!(msg, "unexpected input at byte 12"),
+        res => panic!("unexpected {res:?}"),
+    }
+    assert!(matches!(
+        parse_value_type(DbType::Mysql, "DECIMAL(10,"),
+        Err(ParsingError::Parsing(_))
+    ));
+}
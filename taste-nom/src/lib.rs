@@ -1,3 +1,5 @@
-mod custom_error;
-mod database_conn;
-mod database_types_nom;
+pub mod custom_error;
+pub mod database_conn;
+pub mod database_ddl;
+pub mod database_schema;
+pub mod database_types_nom;